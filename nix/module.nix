@@ -16,12 +16,25 @@ let
       inherit (cfg.email)
         from
         reply_to
+        transport
         tls_relay
         credential_email_account
+        site_name
+        accent_color
+        dkim_selector
         ;
-      # Write password_file instead of an inline password
-      password_file = cfg.email.password_file;
-    };
+    }
+    # Write password_file instead of an inline password
+    // lib.optionalAttrs (cfg.email.password_file != null) { inherit (cfg.email) password_file; }
+    // lib.optionalAttrs (cfg.email.http_token_file != null) { inherit (cfg.email) http_token_file; }
+    // lib.optionalAttrs (cfg.email.sendmail_command != null) {
+      inherit (cfg.email) sendmail_command;
+    }
+    // lib.optionalAttrs (cfg.email.file_drop_dir != null) { inherit (cfg.email) file_drop_dir; }
+    // lib.optionalAttrs (cfg.email.http_url != null) { inherit (cfg.email) http_url; }
+    // lib.optionalAttrs (cfg.email.logo_url != null) { inherit (cfg.email) logo_url; }
+    // lib.optionalAttrs (cfg.email.dkim_key_file != null) { inherit (cfg.email) dkim_key_file; }
+    // lib.optionalAttrs (cfg.email.dkim_domain != null) { inherit (cfg.email) dkim_domain; };
 
     server = {
       # Keep dynamic webroot pointing at built dist, still matches TOML key
@@ -32,6 +45,10 @@ let
         data_dir
         port
         cors_allowed_origins
+        organizers
+        levels
+        deleted_post_retention_days
+        public_url
         ;
     };
   };
@@ -72,6 +89,35 @@ in
       description = "List of allowed CORS origins.";
     };
 
+    organizers = lib.mkOption {
+      type = lib.types.listOf lib.types.str;
+      default = [ ];
+      description = "Email addresses of the users allowed to manage topics.";
+    };
+
+    levels = lib.mkOption {
+      type = lib.types.listOf lib.types.str;
+      default = [
+        "Level 1"
+        "Level 2"
+        "Level 3"
+        "Club"
+      ];
+      description = "Skill levels offered for posts, ordered from lowest to highest.";
+    };
+
+    deleted_post_retention_days = lib.mkOption {
+      type = lib.types.ints.unsigned;
+      default = 30;
+      description = "Days a deleted post can still be restored before it is purged.";
+    };
+
+    public_url = lib.mkOption {
+      type = lib.types.str;
+      default = "http://localhost:${toString cfg.port}";
+      description = "Address the site is reachable at, used for links in emails.";
+    };
+
     email = {
       from = lib.mkOption {
         type = lib.types.str;
@@ -81,18 +127,81 @@ in
         type = lib.types.str;
         default = "noreply@example.com";
       };
+      transport = lib.mkOption {
+        type = lib.types.enum [
+          "starttls"
+          "tls"
+          "relay"
+          "sendmail"
+          "file"
+          "http"
+        ];
+        default = "starttls";
+        description = "How emails leave the server.";
+      };
       tls_relay = lib.mkOption {
         type = lib.types.str;
         default = "smtp.example.com";
+        description = "SMTP host, optionally followed by `:port`.";
       };
       credential_email_account = lib.mkOption {
         type = lib.types.str;
         default = "user@example.com";
       };
       password_file = lib.mkOption {
-        type = lib.types.path;
+        type = lib.types.nullOr lib.types.path;
         default = "/var/peer_practice/email-password.txt";
-        description = "Path to file containing the email account password.";
+        description = "Path to file containing the email account password. Required by the starttls and tls transports.";
+      };
+      http_token_file = lib.mkOption {
+        type = lib.types.nullOr lib.types.path;
+        default = null;
+        description = "Path to file containing the token of the HTTP mail API. Required by the http transport.";
+      };
+      sendmail_command = lib.mkOption {
+        type = lib.types.nullOr lib.types.path;
+        default = null;
+        description = "Command of the sendmail transport, sendmail from the PATH if unset.";
+      };
+      file_drop_dir = lib.mkOption {
+        type = lib.types.nullOr lib.types.path;
+        default = null;
+        description = "Directory the file transport writes emails to.";
+      };
+      http_url = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Endpoint of the HTTP transport.";
+      };
+      site_name = lib.mkOption {
+        type = lib.types.str;
+        default = "Peer Practice";
+        description = "Name shown in the header of emails.";
+      };
+      accent_color = lib.mkOption {
+        type = lib.types.str;
+        default = "#0d9488";
+        description = "CSS color of the header of emails.";
+      };
+      logo_url = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Logo shown in emails instead of the site name.";
+      };
+      dkim_key_file = lib.mkOption {
+        type = lib.types.nullOr lib.types.path;
+        default = null;
+        description = "RSA key in PEM that outgoing emails are DKIM-signed with, see `peer-practice dkim-key`.";
+      };
+      dkim_selector = lib.mkOption {
+        type = lib.types.str;
+        default = "peer-practice";
+        description = "Name of the DKIM key in DNS.";
+      };
+      dkim_domain = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Domain emails are DKIM-signed for, the domain of `from` if unset.";
       };
     };
   };
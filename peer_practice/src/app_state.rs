@@ -1,14 +1,33 @@
 use crate::input::config::current::Config;
-use peer_practice_server_services::{email, pending_logins, posts, storage, users, ws_hub};
+use peer_practice_server_services::{
+    comments, conversations, email, history, notifications, pending_logins, posts, push, storage,
+    topics, users, ws_hub,
+};
+use peer_practice_shared::email::Email;
+use peer_practice_shared::level::Levels;
+use std::collections::HashSet;
+use std::sync::Arc;
 use tokio::sync::mpsc::Sender;
 
 #[derive(Clone)]
 pub struct AppState {
     pub jwt_secret: String,
+    /// Address of the site without a trailing slash, for links in emails.
+    pub public_url: String,
     pub pending_logins: Sender<pending_logins::PendingLoginsMsg>,
     pub users: Sender<users::UsersMsg>,
     pub email: Sender<email::EmailMsg>,
     pub posts: Sender<posts::PostsMsg>,
+    pub history: Sender<history::HistoryMsg>,
+    pub notifications: Sender<notifications::NotificationsMsg>,
+    pub push: Sender<push::PushMsg>,
+    /// Public VAPID key browsers subscribe to Web Push with.
+    pub vapid_public_key: String,
+    pub topics: Sender<topics::TopicsMsg>,
+    pub comments: Sender<comments::CommentsMsg>,
+    pub conversations: Sender<conversations::ConversationsMsg>,
+    pub organizers: Arc<HashSet<Email>>,
+    pub levels: Arc<Levels>,
     pub ws_hub: Sender<ws_hub::WsHubMsg>,
 }
 
@@ -18,21 +37,62 @@ impl AppState {
         let ws_hub = ws_hub::spawn_ws_hub();
         let pending_logins = pending_logins::spawn_pending_logins_actor();
         let users = users::spawn_users_actor(storage.clone(), ws_hub.clone());
-        let email = email::spawn_email_actor(
-            config
-                .email
-                .clone()
-                .try_into()
-                .expect("Invalid email config."),
+        let email_config: email::EmailConfiguration = config
+            .email
+            .clone()
+            .try_into()
+            .expect("Invalid email config.");
+        let push_subject = format!("mailto:{}", email_config.from.email);
+        let email = email::spawn_email_actor(email_config, storage.clone())
+            .expect("Invalid email transport.");
+        let vapid_key =
+            push::VapidKey::load_or_create(&config.server.data_dir.join("vapid_private_key.pem"))
+                .expect("Invalid VAPID key.");
+        let vapid_public_key = vapid_key.public_key();
+        let push = push::spawn_push_actor(storage.clone(), vapid_key, push_subject);
+        let history = history::spawn_history_actor(storage.clone());
+        let topics = topics::spawn_topics_actor(storage.clone(), ws_hub.clone());
+        let notifications = notifications::spawn_notifications_actor(
+            storage.clone(),
+            ws_hub.clone(),
+            users.clone(),
+            topics.clone(),
+            email.clone(),
+            push.clone(),
+            config.server.levels.clone(),
         );
-        let posts = posts::spawn_posts_actor(storage.clone(), ws_hub.clone());
+        let posts = posts::spawn_posts_actor(
+            storage.clone(),
+            ws_hub.clone(),
+            history.clone(),
+            notifications.clone(),
+        );
+        let comments = comments::spawn_comments_actor(storage.clone(), ws_hub.clone());
+        let conversations =
+            conversations::spawn_conversations_actor(storage.clone(), ws_hub.clone());
+        let organizers = config
+            .server
+            .organizers
+            .iter()
+            .map(|email| Email::new(email).expect("Invalid organizer email."))
+            .collect();
 
         Self {
             jwt_secret: config.server.jwt_secret.clone(),
+            public_url: config.server.public_url.clone(),
             pending_logins,
             users,
             email,
             posts,
+            history,
+            notifications,
+            push,
+            vapid_public_key,
+            topics,
+            comments,
+            conversations,
+            organizers: Arc::new(organizers),
+            levels: Arc::new(config.server.levels.clone()),
             ws_hub,
         }
     }
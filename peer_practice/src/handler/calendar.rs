@@ -0,0 +1,73 @@
+use axum::extract::{Path, State};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use chrono::{Local, Utc};
+use std::collections::HashMap;
+use tokio::sync::oneshot;
+
+use crate::app_state::AppState;
+use peer_practice_server_services::calendar::Calendar;
+use peer_practice_server_services::posts::PostsMsg;
+use peer_practice_server_services::topics::TopicsMsg;
+use peer_practice_server_services::users::UsersMsg;
+use peer_practice_shared::post::{Post, PostId};
+use peer_practice_shared::topic::{Topic, TopicId};
+use peer_practice_shared::user::calendar_token::CalendarToken;
+use peer_practice_shared::ymd;
+
+const UPCOMING_SESSIONS: usize = 5;
+
+pub async fn public_calendar_handler() -> impl IntoResponse {
+    ics_response(Calendar::public(&upcoming_sessions()))
+}
+
+pub async fn user_calendar_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let token = token
+        .trim_end_matches(".ics")
+        .parse::<CalendarToken>()
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let (tx_user, rx_user) = oneshot::channel();
+    let _ = state
+        .users
+        .send(UsersMsg::GetByCalendarToken {
+            token,
+            respond_to: tx_user,
+        })
+        .await;
+    let user_id = rx_user
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let posts = list_posts(&state).await?;
+    let topics = list_topics(&state).await?;
+    let calendar = Calendar::personal(user_id, &posts, &topics, &upcoming_sessions());
+    Ok(ics_response(calendar))
+}
+
+async fn list_posts(state: &AppState) -> Result<Vec<(PostId, Post)>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.posts.send(PostsMsg::List(tx)).await;
+    rx.await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn list_topics(state: &AppState) -> Result<HashMap<TopicId, Topic>, StatusCode> {
+    let (tx, rx) = oneshot::channel();
+    let _ = state.topics.send(TopicsMsg::List(tx)).await;
+    rx.await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+fn upcoming_sessions() -> Vec<chrono::NaiveDate> {
+    ymd::next_second_and_fourth_fridays(Local::now().date_naive(), UPCOMING_SESSIONS)
+}
+
+fn ics_response(calendar: Calendar) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        calendar.to_ics(Utc::now()),
+    )
+}
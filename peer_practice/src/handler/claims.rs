@@ -1,3 +1,4 @@
+use peer_practice_shared::post::PostId;
 use peer_practice_shared::user::UserId;
 use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
@@ -5,3 +6,19 @@ pub struct Claims {
     pub user_id: UserId,
     pub exp: usize,
 }
+
+/// Lets the user join a post from a link in an email without signing in.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JoinClaims {
+    pub user_id: UserId,
+    pub post_id: PostId,
+    pub exp: usize,
+}
+
+impl JoinClaims {
+    /// Secret for join links, kept apart from the session secret so a link
+    /// can never pass as a session token.
+    pub fn secret(jwt_secret: &str) -> Vec<u8> {
+        format!("{jwt_secret}:join").into_bytes()
+    }
+}
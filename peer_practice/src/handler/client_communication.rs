@@ -1,19 +1,41 @@
-use axum::extract::ws::{Message, WebSocket};
 use tokio::sync::oneshot;
 use tracing::{error, info};
 
 use crate::app_state::AppState;
-use peer_practice_server_services::posts::PostsMsg;
+use crate::handler::protocol::ClientSocket;
+use crate::services::calendar_invites;
+use chrono::Utc;
+use peer_practice_server_services::calendar::CalendarMethod;
+use peer_practice_server_services::comments::CommentsMsg;
+use peer_practice_server_services::conversations::ConversationsMsg;
+use peer_practice_server_services::email::EmailMsg;
+use peer_practice_server_services::history::HistoryMsg;
+use peer_practice_server_services::notifications::NotificationsMsg;
+use peer_practice_server_services::posts::{Membership, PostsMsg};
+use peer_practice_server_services::push::{self, PushMsg};
+use peer_practice_server_services::rotation;
+use peer_practice_server_services::topics::TopicsMsg;
 use peer_practice_server_services::users::UsersMsg;
+use peer_practice_server_services::ws_hub::WsHubMsg;
+use peer_practice_shared::archive::Removal;
+use peer_practice_shared::comment::Comment;
+use peer_practice_shared::dance_role::DanceRole;
+use peer_practice_shared::direct_message::DirectMessage;
 use peer_practice_shared::messages::{ClientToServer, ServerToClient};
+use peer_practice_shared::notification::NotificationPreferences;
+use peer_practice_shared::post::PostId;
+use peer_practice_shared::request::RequestError;
+use peer_practice_shared::rotation::RotationSettings;
+use peer_practice_shared::sync::PostsSync;
+use peer_practice_shared::topic::TopicId;
 use peer_practice_shared::user::UserId;
 
 pub async fn handle_websocket_message(
-    socket: &mut WebSocket,
+    socket: &mut ClientSocket,
     state: &AppState,
     user_id: UserId,
     msg: ClientToServer,
-) {
+) -> Result<(), RequestError> {
     match msg {
         ClientToServer::GetUser(user) => {
             info!(
@@ -34,11 +56,7 @@ pub async fn handle_websocket_message(
                 && let Some(user) = &user
             {
                 match socket
-                    .send(Message::Text(
-                        serde_json::to_string(&ServerToClient::User(user.id, user.into()))
-                            .unwrap()
-                            .into(),
-                    ))
+                    .send(&ServerToClient::User(user.id, user.into()))
                     .await
                 {
                     Ok(()) => {}
@@ -53,6 +71,7 @@ pub async fn handle_websocket_message(
                 user_id = ?user_id,
                 target_user_id = ?user_display.id,
                 display_name = ?user_display.display_name,
+                level = ?user_display.level,
                 command = "UpdateUser",
                 "received client command"
             );
@@ -70,6 +89,9 @@ pub async fn handle_websocket_message(
                 {
                     let mut user = user.clone();
                     user.display_name = user_display.display_name;
+                    user.level = user_display
+                        .level
+                        .filter(|level| state.levels.rank(level).is_some());
                     _ = state
                         .users
                         .send(UsersMsg::Update { id: user_id, user })
@@ -84,26 +106,181 @@ pub async fn handle_websocket_message(
             if let Ok(posts) = prx.await {
                 for (post_id, post) in posts {
                     if socket
-                        .send(Message::Text(
-                            serde_json::to_string(&ServerToClient::Post(post_id, post))
-                                .unwrap()
-                                .into(),
-                        ))
+                        .send(&ServerToClient::Post(post_id, post))
                         .await
                         .is_err()
                     {}
                 }
             }
+            send_reactions(socket, state, user_id).await;
+        }
+        ClientToServer::SyncPosts(cursor) => {
+            info!(
+                user_id = ?user_id,
+                cursor = ?cursor,
+                command = "SyncPosts",
+                "received client command"
+            );
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::Sync(cursor, tx)).await;
+            let Ok(sync) = rx.await else {
+                return Err(RequestError::internal("Posts are unavailable."));
+            };
+            for page in paginate(sync) {
+                if let Err(err) = socket.send(&ServerToClient::PostsSync(page)).await {
+                    error!("Error sending posts: {:?}", err);
+                    return Ok(());
+                }
+            }
+            send_reactions(socket, state, user_id).await;
+        }
+        ClientToServer::GetPostHistory(post_id) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post_id,
+                command = "GetPostHistory",
+                "received client command"
+            );
+            // Deleted posts are hidden like in the archive.
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::GetArchived(post_id, tx)).await;
+            if let Ok(Some(archived)) = rx.await
+                && archived.is_deleted()
+                && archived.post.owner != user_id
+                && !is_organizer(state, user_id).await
+            {
+                return Err(RequestError::forbidden(
+                    "Only the owner or an organizer may see the history of a deleted post.",
+                ));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state.history.send(HistoryMsg::Versions(post_id, tx)).await;
+            if let Ok(versions) = rx.await
+                && let Err(err) = socket
+                    .send(&ServerToClient::PostHistory(post_id, versions))
+                    .await
+            {
+                error!("Error sending post history: {:?}", err);
+            }
+        }
+        ClientToServer::GetAuditLog => {
+            info!(user_id = ?user_id, command = "GetAuditLog", "received client command");
+            if !is_organizer(state, user_id).await {
+                return Err(RequestError::forbidden("Only organizers may do this."));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .history
+                .send(HistoryMsg::AuditLog(AUDIT_LOG_PAGE, tx))
+                .await;
+            if let Ok(entries) = rx.await
+                && let Err(err) = socket.send(&ServerToClient::AuditLog(entries)).await
+            {
+                error!("Error sending audit log: {:?}", err);
+            }
+        }
+        ClientToServer::GetUndeliveredEmails => {
+            info!(user_id = ?user_id, command = "GetUndeliveredEmails", "received client command");
+            if !is_organizer(state, user_id).await {
+                return Err(RequestError::forbidden("Only organizers may do this."));
+            }
+            send_undelivered_emails(socket, state).await;
+        }
+        ClientToServer::RetryEmail(id) => {
+            info!(user_id = ?user_id, id = %id, command = "RetryEmail", "received client command");
+            if !is_organizer(state, user_id).await {
+                return Err(RequestError::forbidden("Only organizers may do this."));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .email
+                .send(EmailMsg::Retry { id, respond_to: tx })
+                .await;
+            if !matches!(rx.await, Ok(true)) {
+                return Err(RequestError::not_found(
+                    "No such failed email, or it can no longer be sent.",
+                ));
+            }
+            send_undelivered_emails(socket, state).await;
+        }
+        ClientToServer::SearchPosts(query) => {
+            info!(
+                user_id = ?user_id,
+                query = ?query,
+                command = "SearchPosts",
+                "received client command"
+            );
+            let (tx, rx) = oneshot::channel();
+            _ = state.topics.send(TopicsMsg::List(tx)).await;
+            let topics = rx.await.unwrap_or_default();
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .posts
+                .send(PostsMsg::Search {
+                    query: query.clone(),
+                    user: user_id,
+                    levels: state.levels.as_ref().clone(),
+                    topics,
+                    respond_to: tx,
+                })
+                .await;
+            if let Ok(results) = rx.await
+                && let Err(err) = socket
+                    .send(&ServerToClient::SearchResults(query, results))
+                    .await
+            {
+                error!("Error sending search results: {:?}", err);
+            }
+        }
+        ClientToServer::ToggleReaction(post_id, reaction) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post_id,
+                reaction = ?reaction,
+                command = "ToggleReaction",
+                "received client command"
+            );
+            _ = state
+                .posts
+                .send(PostsMsg::ToggleReaction(post_id, user_id, reaction))
+                .await;
         }
         ClientToServer::Join(post) => {
             info!(user_id = ?user_id, post_id = ?post, command = "Join", "received client command");
-            _ = state.posts.send(PostsMsg::UserJoins(post, user_id)).await;
+            join_post(state, user_id, post, None).await?;
+        }
+        ClientToServer::JoinAs(post, role) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post,
+                role = ?role,
+                command = "JoinAs",
+                "received client command"
+            );
+            join_post(state, user_id, post, Some(role)).await?;
         }
         ClientToServer::Leave(post) => {
             info!(user_id = ?user_id, post_id = ?post, command = "Leave", "received client command");
-            _ = state.posts.send(PostsMsg::UserLeaves(post, user_id)).await;
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .posts
+                .send(PostsMsg::UserLeaves(post, user_id, tx))
+                .await;
+            let Ok(Some(left)) = rx.await else {
+                return Err(RequestError::not_found("The post does not exist anymore."));
+            };
+
+            if left.before == Membership::Partaking {
+                tokio::spawn(calendar_invites::send_for_post(
+                    state.clone(),
+                    user_id,
+                    post,
+                    CalendarMethod::Cancel,
+                ));
+            }
+            invite_promoted(state, post, left.promoted);
         }
-        ClientToServer::UpdatePost(id, post) => {
+        ClientToServer::UpdatePost(id, mut post) => {
             info!(
                 user_id = ?user_id,
                 post_id = ?id,
@@ -112,9 +289,26 @@ pub async fn handle_websocket_message(
                 command = "UpdatePost",
                 "received client command"
             );
-            if post.owner == user_id {
-                _ = state.posts.send(PostsMsg::Upsert(id, post)).await;
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::Get(id, tx)).await;
+            let Ok(Some(existing)) = rx.await else {
+                return Err(RequestError::not_found("The post does not exist anymore."));
+            };
+            if existing.owner != user_id {
+                return Err(RequestError::forbidden("Only the owner may edit a post."));
             }
+            post.owner = user_id;
+            let Some(level) = state.levels.normalize(post.level.clone()) else {
+                return Err(RequestError::invalid("Unknown level."));
+            };
+            post.level = level;
+            check_post_topics(state, &post.topics, &existing.topics).await?;
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::Upsert(id, post, tx)).await;
+            let Ok(Some(promoted)) = rx.await else {
+                return Err(RequestError::not_found("The post does not exist anymore."));
+            };
+            invite_promoted(state, id, promoted);
         }
         ClientToServer::NewPost(mut post) => {
             info!(
@@ -124,8 +318,16 @@ pub async fn handle_websocket_message(
                 command = "NewPost",
                 "received client command"
             );
+            let Some(level) = state.levels.normalize(post.level.clone()) else {
+                return Err(RequestError::invalid("Unknown level."));
+            };
+            post.level = level;
+            check_post_topics(state, &post.topics, &[]).await?;
             post.owner = user_id;
+            post.partaking_users.clear();
             post.partaking_users.insert(user_id);
+            post.waitlist.clear();
+            post.roles.retain(|user, _| *user == user_id);
             let (tx, rx) = oneshot::channel();
             _ = state.posts.send(PostsMsg::New(post, tx)).await;
             _ = rx.await;
@@ -139,11 +341,663 @@ pub async fn handle_websocket_message(
             );
             let (tx, rx) = oneshot::channel();
             _ = state.posts.send(PostsMsg::Get(post_id, tx)).await;
-            if let Ok(Some(post)) = rx.await
-                && post.owner == user_id
+            let Ok(Some(post)) = rx.await else {
+                return Err(RequestError::not_found("The post does not exist anymore."));
+            };
+            if post.owner != user_id {
+                return Err(RequestError::forbidden("Only the owner may delete a post."));
+            }
+            _ = state
+                .posts
+                .send(PostsMsg::Remove(post_id, Some(user_id)))
+                .await;
+            tokio::spawn(calendar_invites::send_to_users(
+                state.clone(),
+                post.partaking_users.iter().copied().collect(),
+                post_id,
+                post,
+                CalendarMethod::Cancel,
+            ));
+        }
+        ClientToServer::GetArchive => {
+            info!(user_id = ?user_id, command = "GetArchive", "received client command");
+            let organizer = is_organizer(state, user_id).await;
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::ListArchive(tx)).await;
+            let Ok(mut archive) = rx.await else {
+                return Err(RequestError::internal("The archive is unavailable."));
+            };
+            archive.retain(|(_, archived)| {
+                organizer || archived.post.owner == user_id || !archived.is_deleted()
+            });
+            if let Err(err) = socket.send(&ServerToClient::Archive(archive)).await {
+                error!("Error sending archive: {:?}", err);
+            }
+        }
+        ClientToServer::RestorePost(post_id) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post_id,
+                command = "RestorePost",
+                "received client command"
+            );
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::GetArchived(post_id, tx)).await;
+            let Ok(Some(archived)) = rx.await else {
+                return Err(RequestError::not_found("The post is not in the archive."));
+            };
+            let allowed = archived.removal == Removal::Deleted(user_id)
+                || archived.post.owner == user_id
+                || is_organizer(state, user_id).await;
+            if !allowed {
+                return Err(RequestError::forbidden(
+                    "Only the owner or an organizer may restore this post.",
+                ));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .posts
+                .send(PostsMsg::Restore(post_id, user_id, tx))
+                .await;
+            if !matches!(rx.await, Ok(true)) {
+                return Err(RequestError::not_found("The post is not in the archive."));
+            }
+            let post = archived.post;
+            tokio::spawn(calendar_invites::send_to_users(
+                state.clone(),
+                post.partaking_users.iter().copied().collect(),
+                post_id,
+                post,
+                CalendarMethod::Request,
+            ));
+        }
+        ClientToServer::GetPracticeStats => {
+            info!(user_id = ?user_id, command = "GetPracticeStats", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::PracticeStats(user_id, tx)).await;
+            if let Ok(stats) = rx.await
+                && let Err(err) = socket.send(&ServerToClient::PracticeStats(stats)).await
             {
-                _ = state.posts.send(PostsMsg::Remove(post_id)).await;
+                error!("Error sending practice stats: {:?}", err);
+            }
+        }
+        ClientToServer::GetCalendarToken => {
+            info!(user_id = ?user_id, command = "GetCalendarToken", "received client command");
+            send_calendar_token(socket, state, user_id, false).await;
+        }
+        ClientToServer::RenewCalendarToken => {
+            info!(user_id = ?user_id, command = "RenewCalendarToken", "received client command");
+            send_calendar_token(socket, state, user_id, true).await;
+        }
+        ClientToServer::GetRotationSchedule(settings) => {
+            info!(
+                user_id = ?user_id,
+                settings = ?settings,
+                command = "GetRotationSchedule",
+                "received client command"
+            );
+            if !RotationSettings::ROUNDS.contains(&settings.rounds) {
+                return Err(RequestError::invalid(format!(
+                    "Plan between {} and {} rounds.",
+                    RotationSettings::ROUNDS.start(),
+                    RotationSettings::ROUNDS.end()
+                )));
+            }
+            if !RotationSettings::ROUND_MINUTES.contains(&settings.round_minutes) {
+                return Err(RequestError::invalid(format!(
+                    "Rounds last between {} and {} minutes.",
+                    RotationSettings::ROUND_MINUTES.start(),
+                    RotationSettings::ROUND_MINUTES.end()
+                )));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::List(tx)).await;
+            if let Ok(posts) = rx.await {
+                let schedule = rotation::plan_rotation(&posts, settings);
+                if let Err(err) = socket
+                    .send(&ServerToClient::RotationSchedule(schedule))
+                    .await
+                {
+                    error!("Error sending rotation schedule: {:?}", err);
+                }
             }
         }
+        ClientToServer::GetTopics => {
+            info!(user_id = ?user_id, command = "GetTopics", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state.topics.send(TopicsMsg::List(tx)).await;
+            if let Ok(topics) = rx.await {
+                for (topic_id, topic) in topics {
+                    if let Err(err) = socket.send(&ServerToClient::Topic(topic_id, topic)).await {
+                        error!("Error sending topic: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+        ClientToServer::NewTopic(topic) => {
+            info!(
+                user_id = ?user_id,
+                name = topic.name,
+                command = "NewTopic",
+                "received client command"
+            );
+            check_topic(state, user_id, &topic.name).await?;
+            let (tx, rx) = oneshot::channel();
+            _ = state.topics.send(TopicsMsg::New(topic, tx)).await;
+            _ = rx.await;
+        }
+        ClientToServer::UpdateTopic(topic_id, topic) => {
+            info!(
+                user_id = ?user_id,
+                topic_id = ?topic_id,
+                name = topic.name,
+                archived = topic.archived,
+                command = "UpdateTopic",
+                "received client command"
+            );
+            check_topic(state, user_id, &topic.name).await?;
+            _ = state.topics.send(TopicsMsg::Update(topic_id, topic)).await;
+        }
+        ClientToServer::GetComments(post_id) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post_id,
+                command = "GetComments",
+                "received client command"
+            );
+            let (tx, rx) = oneshot::channel();
+            _ = state.comments.send(CommentsMsg::ForPost(post_id, tx)).await;
+            if let Ok(comments) = rx.await {
+                for (comment_id, comment) in comments {
+                    if let Err(err) = socket
+                        .send(&ServerToClient::Comment(comment_id, comment))
+                        .await
+                    {
+                        error!("Error sending comment: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+        ClientToServer::GetCommentCounts => {
+            info!(user_id = ?user_id, command = "GetCommentCounts", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state.comments.send(CommentsMsg::Counts(tx)).await;
+            let Ok(mut counts) = rx.await else {
+                return Err(RequestError::internal("Comments are unavailable."));
+            };
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::List(tx)).await;
+            let live = rx
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(id, _)| counts.remove(&id).map(|count| (id, count)))
+                .collect();
+            if let Err(err) = socket.send(&ServerToClient::CommentCounts(live)).await {
+                error!("Error sending comment counts: {:?}", err);
+            }
+        }
+        ClientToServer::NewComment(post_id, parent, content) => {
+            info!(
+                user_id = ?user_id,
+                post_id = ?post_id,
+                parent = ?parent,
+                command = "NewComment",
+                "received client command"
+            );
+            if !valid_comment(&content) {
+                return Err(RequestError::invalid(
+                    "Comments must not be empty or too long.",
+                ));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::Get(post_id, tx)).await;
+            if !matches!(rx.await, Ok(Some(_))) {
+                return Err(RequestError::not_found("The post does not exist anymore."));
+            }
+            if let Some(parent) = parent {
+                let (tx, rx) = oneshot::channel();
+                _ = state.comments.send(CommentsMsg::Get(parent, tx)).await;
+                if !matches!(rx.await, Ok(Some(existing)) if existing.post == post_id) {
+                    return Err(RequestError::not_found(
+                        "The comment does not exist anymore.",
+                    ));
+                }
+            }
+            let comment = Comment {
+                post: post_id,
+                parent,
+                author: user_id,
+                content,
+                created: Utc::now(),
+                edited: None,
+            };
+            let (tx, rx) = oneshot::channel();
+            _ = state.comments.send(CommentsMsg::New(comment, tx)).await;
+            _ = rx.await;
+        }
+        ClientToServer::EditComment(comment_id, content) => {
+            info!(
+                user_id = ?user_id,
+                comment_id = ?comment_id,
+                command = "EditComment",
+                "received client command"
+            );
+            if !valid_comment(&content) {
+                return Err(RequestError::invalid(
+                    "Comments must not be empty or too long.",
+                ));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state.comments.send(CommentsMsg::Get(comment_id, tx)).await;
+            let Ok(Some(mut comment)) = rx.await else {
+                return Err(RequestError::not_found(
+                    "The comment does not exist anymore.",
+                ));
+            };
+            if comment.author != user_id {
+                return Err(RequestError::forbidden(
+                    "Only the author may edit a comment.",
+                ));
+            }
+            comment.content = content;
+            comment.edited = Some(Utc::now());
+            _ = state
+                .comments
+                .send(CommentsMsg::Update(comment_id, comment))
+                .await;
+        }
+        ClientToServer::DeleteComment(comment_id) => {
+            info!(
+                user_id = ?user_id,
+                comment_id = ?comment_id,
+                command = "DeleteComment",
+                "received client command"
+            );
+            let (tx, rx) = oneshot::channel();
+            _ = state.comments.send(CommentsMsg::Get(comment_id, tx)).await;
+            let Ok(Some(comment)) = rx.await else {
+                return Err(RequestError::not_found(
+                    "The comment does not exist anymore.",
+                ));
+            };
+            let (tx, rx) = oneshot::channel();
+            _ = state.posts.send(PostsMsg::Get(comment.post, tx)).await;
+            let post_owner = matches!(rx.await, Ok(Some(post)) if post.owner == user_id);
+            if comment.author != user_id && !post_owner {
+                return Err(RequestError::forbidden(
+                    "Only the author or the post owner may delete a comment.",
+                ));
+            }
+            _ = state.comments.send(CommentsMsg::Remove(comment_id)).await;
+        }
+        ClientToServer::GetDirectMessages => {
+            info!(user_id = ?user_id, command = "GetDirectMessages", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .conversations
+                .send(ConversationsMsg::ListFor(user_id, tx))
+                .await;
+            if let Ok(messages) = rx.await {
+                for (message_id, message) in messages {
+                    if let Err(err) = socket
+                        .send(&ServerToClient::DirectMessage(message_id, message))
+                        .await
+                    {
+                        error!("Error sending direct message: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+        ClientToServer::GetNotifications => {
+            info!(user_id = ?user_id, command = "GetNotifications", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .notifications
+                .send(NotificationsMsg::ListFor(user_id, tx))
+                .await;
+            if let Ok(notifications) = rx.await {
+                for (notification_id, notification) in notifications {
+                    if let Err(err) = socket
+                        .send(&ServerToClient::Notification(notification_id, notification))
+                        .await
+                    {
+                        error!("Error sending notification: {:?}", err);
+                        break;
+                    }
+                }
+            }
+        }
+        ClientToServer::MarkNotificationsRead => {
+            info!(user_id = ?user_id, command = "MarkNotificationsRead", "received client command");
+            _ = state
+                .notifications
+                .send(NotificationsMsg::MarkRead(user_id))
+                .await;
+        }
+        ClientToServer::GetVapidPublicKey => {
+            info!(user_id = ?user_id, command = "GetVapidPublicKey", "received client command");
+            if let Err(err) = socket
+                .send(&ServerToClient::VapidPublicKey(
+                    state.vapid_public_key.clone(),
+                ))
+                .await
+            {
+                error!("Error sending VAPID public key: {:?}", err);
+            }
+        }
+        ClientToServer::SubscribePush(subscription) => {
+            info!(user_id = ?user_id, command = "SubscribePush", "received client command");
+            push::check_endpoint(&subscription.endpoint)
+                .map_err(|err| RequestError::invalid(err.to_string()))?;
+            _ = state
+                .push
+                .send(PushMsg::Subscribe(user_id, subscription))
+                .await;
+        }
+        ClientToServer::UnsubscribePush(endpoint) => {
+            info!(user_id = ?user_id, command = "UnsubscribePush", "received client command");
+            _ = state
+                .push
+                .send(PushMsg::Unsubscribe(user_id, endpoint))
+                .await;
+        }
+        ClientToServer::SendDirectMessage(to, content) => {
+            info!(
+                user_id = ?user_id,
+                to = ?to,
+                command = "SendDirectMessage",
+                "received client command"
+            );
+            if to == user_id
+                || content.trim().is_empty()
+                || content.len() > DirectMessage::MAX_LENGTH
+            {
+                return Err(RequestError::invalid(
+                    "Messages must not be empty or too long.",
+                ));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .users
+                .send(UsersMsg::GetById {
+                    id: to,
+                    respond_to: tx,
+                })
+                .await;
+            if !matches!(rx.await, Ok(Some(_))) {
+                return Err(RequestError::not_found("Unknown user."));
+            }
+            let message = DirectMessage {
+                from: user_id,
+                to,
+                content,
+                sent: Utc::now(),
+                read: false,
+            };
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .conversations
+                .send(ConversationsMsg::Send(message, tx))
+                .await;
+            _ = rx.await;
+        }
+        ClientToServer::MarkConversationRead(partner) => {
+            info!(
+                user_id = ?user_id,
+                partner = ?partner,
+                command = "MarkConversationRead",
+                "received client command"
+            );
+            _ = state
+                .conversations
+                .send(ConversationsMsg::MarkRead {
+                    reader: user_id,
+                    partner,
+                })
+                .await;
+        }
+        ClientToServer::GetUserConfig => {
+            info!(user_id = ?user_id, command = "GetUserConfig", "received client command");
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .users
+                .send(UsersMsg::GetById {
+                    id: user_id,
+                    respond_to: tx,
+                })
+                .await;
+            if let Ok(Some(user)) = rx.await
+                && let Err(err) = socket.send(&ServerToClient::UserConfig(user.config)).await
+            {
+                error!("Error sending user config: {:?}", err);
+            }
+        }
+        ClientToServer::UpdateUserConfig(config) => {
+            info!(
+                user_id = ?user_id,
+                config = ?config,
+                command = "UpdateUserConfig",
+                "received client command"
+            );
+            let hours_before = NotificationPreferences::REMINDER_HOURS_BEFORE;
+            if !hours_before.contains(&config.notifications.reminder_hours_before) {
+                return Err(RequestError::invalid(format!(
+                    "Reminders go out between {} and {} hours ahead.",
+                    hours_before.start(),
+                    hours_before.end()
+                )));
+            }
+            let (tx, rx) = oneshot::channel();
+            _ = state
+                .users
+                .send(UsersMsg::GetById {
+                    id: user_id,
+                    respond_to: tx,
+                })
+                .await;
+            if let Ok(Some(mut user)) = rx.await {
+                user.config = config.clone();
+                _ = state
+                    .users
+                    .send(UsersMsg::Update { id: user_id, user })
+                    .await;
+                _ = state
+                    .ws_hub
+                    .send(WsHubMsg::BroadcastUser {
+                        user_id,
+                        msg: ServerToClient::UserConfig(config),
+                    })
+                    .await;
+            } else {
+                return Err(RequestError::not_found("Unknown user."));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) async fn join_post(
+    state: &AppState,
+    user_id: UserId,
+    post: PostId,
+    role: Option<DanceRole>,
+) -> Result<(), RequestError> {
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .posts
+        .send(PostsMsg::UserJoins(post, user_id, role, tx))
+        .await;
+    let Ok(Some(before)) = rx.await else {
+        return Err(RequestError::not_found("The post does not exist anymore."));
+    };
+    if before != Membership::Partaking {
+        tokio::spawn(calendar_invites::send_for_post(
+            state.clone(),
+            user_id,
+            post,
+            CalendarMethod::Request,
+        ));
+    }
+    Ok(())
+}
+
+/// Sends calendar invites to users moved up from the waitlist.
+fn invite_promoted(state: &AppState, post: PostId, promoted: Vec<UserId>) {
+    for promoted_user in promoted {
+        info!(user_id = ?promoted_user, post_id = ?post, "promoted from waitlist");
+        tokio::spawn(calendar_invites::send_for_post(
+            state.clone(),
+            promoted_user,
+            post,
+            CalendarMethod::Request,
+        ));
+    }
+}
+
+fn valid_comment(content: &str) -> bool {
+    !content.trim().is_empty() && content.len() <= Comment::MAX_LENGTH
+}
+
+async fn send_undelivered_emails(socket: &mut ClientSocket, state: &AppState) {
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .email
+        .send(EmailMsg::Undelivered { respond_to: tx })
+        .await;
+    if let Ok(emails) = rx.await
+        && let Err(err) = socket
+            .send(&ServerToClient::UndeliveredEmails(emails))
+            .await
+    {
+        error!("Error sending undelivered emails: {:?}", err);
+    }
+}
+
+async fn check_topic(state: &AppState, user_id: UserId, name: &str) -> Result<(), RequestError> {
+    if name.trim().is_empty() {
+        return Err(RequestError::invalid("Topics need a name."));
+    }
+    if !is_organizer(state, user_id).await {
+        return Err(RequestError::forbidden(
+            "Only organizers may manage topics.",
+        ));
+    }
+    Ok(())
+}
+
+/// Topics of a post must exist. Archived ones may only stay on posts that
+/// already had them.
+async fn check_post_topics(
+    state: &AppState,
+    topics: &[TopicId],
+    kept: &[TopicId],
+) -> Result<(), RequestError> {
+    let (tx, rx) = oneshot::channel();
+    _ = state.topics.send(TopicsMsg::List(tx)).await;
+    let known = rx.await.unwrap_or_default();
+    for id in topics {
+        match known.get(id) {
+            None => return Err(RequestError::invalid("Unknown topic.")),
+            Some(topic) if topic.archived && !kept.contains(id) => {
+                return Err(RequestError::invalid(format!(
+                    "The topic {} is archived.",
+                    topic.name
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Whether `user_id` signed in with one of the configured organizer addresses.
+pub async fn is_organizer(state: &AppState, user_id: UserId) -> bool {
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .users
+        .send(UsersMsg::GetById {
+            id: user_id,
+            respond_to: tx,
+        })
+        .await;
+    matches!(rx.await, Ok(Some(user)) if state.organizers.contains(&user.email))
+}
+
+/// Audit entries sent to organizers at once.
+const AUDIT_LOG_PAGE: usize = 500;
+
+/// Posts sent per websocket frame while syncing.
+const SYNC_PAGE_SIZE: usize = 50;
+
+/// Splits a sync into frames of reasonable size, keeping the resync flag on the
+/// first page and the cursor on the last.
+fn paginate(sync: PostsSync) -> Vec<PostsSync> {
+    let mut pages = sync
+        .posts
+        .chunks(SYNC_PAGE_SIZE)
+        .map(|posts| PostsSync {
+            full_resync: false,
+            posts: posts.to_vec(),
+            removed: Vec::new(),
+            cursor: None,
+        })
+        .collect::<Vec<_>>();
+    if pages.is_empty() {
+        pages.push(PostsSync {
+            full_resync: false,
+            posts: Vec::new(),
+            removed: Vec::new(),
+            cursor: None,
+        });
+    }
+    pages[0].full_resync = sync.full_resync;
+    pages[0].removed = sync.removed;
+    if let Some(last) = pages.last_mut() {
+        last.cursor = sync.cursor;
+    }
+    pages
+}
+
+async fn send_reactions(socket: &mut ClientSocket, state: &AppState, user_id: UserId) {
+    let (tx, rx) = oneshot::channel();
+    _ = state.posts.send(PostsMsg::Reactions(user_id, tx)).await;
+    if let Ok(reactions) = rx.await {
+        for (post_id, counts, mine) in reactions {
+            for msg in [
+                ServerToClient::Reactions(post_id, counts),
+                ServerToClient::MyReactions(post_id, mine),
+            ] {
+                if let Err(err) = socket.send(&msg).await {
+                    error!("Error sending reactions: {:?}", err);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn send_calendar_token(
+    socket: &mut ClientSocket,
+    state: &AppState,
+    user_id: UserId,
+    renew: bool,
+) {
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .users
+        .send(UsersMsg::GetCalendarToken {
+            id: user_id,
+            renew,
+            respond_to: tx,
+        })
+        .await;
+    if let Ok(Some(token)) = rx.await
+        && let Err(err) = socket.send(&ServerToClient::CalendarToken(token)).await
+    {
+        error!("Error sending calendar token: {:?}", err);
     }
 }
@@ -0,0 +1,66 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{Html, Redirect};
+use jsonwebtoken::{DecodingKey, Validation, decode};
+use tracing::info;
+
+use crate::app_state::AppState;
+use crate::handler::claims::JoinClaims;
+use crate::handler::client_communication::join_post;
+
+/// Asks to confirm joining the post of a link from the practice digest. Link
+/// scanners and prefetchers follow links in emails, so opening one must not
+/// join anybody yet.
+pub async fn join_link_page(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Html<&'static str>, StatusCode> {
+    join_claims(&state, &token)?;
+    Ok(Html(CONFIRM_PAGE))
+}
+
+const CONFIRM_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Join practice</title>
+</head>
+<body style="font-family: sans-serif; color: #1f2937; max-width: 40rem; margin: 2rem auto; padding: 0 1rem;">
+<h1 style="font-size: 1.4rem;">Join this practice?</h1>
+<form method="post">
+<button type="submit" style="background: #14b8a6; color: white; padding: .5rem 1rem; border: none; border-radius: .4rem; font-size: 1rem; cursor: pointer;">Join</button>
+<a href="/" style="margin-left: 1rem; color: #6b7280;">Not now</a>
+</form>
+</body>
+</html>
+"#;
+
+/// Joins the post of a confirmed link from the practice digest, then opens
+/// the site.
+pub async fn join_link_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Redirect, StatusCode> {
+    let claims = join_claims(&state, &token)?;
+
+    info!(
+        user_id = ?claims.user_id,
+        post_id = ?claims.post_id,
+        "joining post from link"
+    );
+    join_post(&state, claims.user_id, claims.post_id, None)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    Ok(Redirect::to("/"))
+}
+
+fn join_claims(state: &AppState, token: &str) -> Result<JoinClaims, StatusCode> {
+    decode::<JoinClaims>(
+        token,
+        &DecodingKey::from_secret(&JoinClaims::secret(&state.jwt_secret)),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+    .map_err(|_| StatusCode::UNAUTHORIZED)
+}
@@ -10,12 +10,19 @@ use tokio::sync::oneshot;
 use crate::app_state::AppState;
 use crate::handler::claims::Claims;
 use peer_practice_server_services::email::EmailMsg;
-use peer_practice_server_services::pending_logins::PendingLoginsMsg;
+use peer_practice_server_services::email_queue::Delivery;
+use peer_practice_server_services::pending_logins::{
+    LOGIN_CODE_VALIDITY_MINUTES, PendingLoginsMsg,
+};
 use peer_practice_server_services::users::UsersMsg;
 use peer_practice_shared::authentication::login_data::{LoginData, PinLogin};
 use peer_practice_shared::user::UserId;
 use rand::prelude::*;
 use tower_sessions::cookie::time::OffsetDateTime;
+use tracing::{error, warn};
+
+/// How long logging in waits for the first attempt to send the code.
+const LOGIN_MAIL_WAIT: std::time::Duration = std::time::Duration::from_secs(15);
 
 #[axum::debug_handler]
 pub async fn login_handler(
@@ -47,23 +54,53 @@ pub async fn login_handler(
         })
         .await;
 
-    // Send login email (ignore result, but keep TODO note)
-    let (tx_mail, _rx_mail) = oneshot::channel();
+    let user_id = rx_user
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // A language picked in the settings wins over the browser's.
+    let mut language = None;
+    if let Some(id) = user_id {
+        let (tx, rx) = oneshot::channel();
+        let _ = state
+            .users
+            .send(UsersMsg::GetById { id, respond_to: tx })
+            .await;
+        language = rx
+            .await
+            .ok()
+            .flatten()
+            .and_then(|user| user.config.language);
+    }
+
+    let (tx_mail, rx_mail) = oneshot::channel();
     let _ = state
         .email
         .send(EmailMsg::SendLoginMail {
             respond_to: tx_mail,
             target: login_data.email.clone().into(),
             validation_code: pin,
+            language: language.or(login_data.language).unwrap_or_default(),
+            expires_at: Utc::now() + Duration::minutes(LOGIN_CODE_VALIDITY_MINUTES),
         })
         .await;
-    // TODO: consider logging the email send result from _rx_mail
+    // Only a rejected address is worth telling the user about, slow or
+    // failing servers are retried by the email actor.
+    match tokio::time::timeout(LOGIN_MAIL_WAIT, rx_mail).await {
+        Ok(Ok(Ok(Delivery::Sent))) => {}
+        Ok(Ok(Ok(Delivery::Queued(id)))) => {
+            warn!(id = %id, "login email queued for retry");
+        }
+        Ok(Ok(Err(err))) => {
+            warn!("Could not send login email: {:#}", err);
+            return Err(StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        Ok(Err(err)) => error!("Email actor dropped login email: {}", err),
+        Err(_) => warn!("Login email still being sent after {:?}", LOGIN_MAIL_WAIT),
+    }
 
     // Return existing user id (or None) if lookup succeeded
-    rx_user
-        .await
-        .map(Json)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    Ok(Json(user_id))
 }
 
 pub async fn pin_handler(
@@ -1,4 +1,7 @@
+pub mod calendar;
 pub mod claims;
 pub mod client_communication;
+pub mod join_link;
 pub mod login;
+pub mod protocol;
 pub mod websocket;
@@ -0,0 +1,141 @@
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use peer_practice_shared::Version;
+use peer_practice_shared::handshake::Welcome;
+use peer_practice_shared::messages::{ClientToServer, ServerToClient};
+use peer_practice_shared::request::{ClientRequest, RequestId};
+use peer_practice_shared::v2025_10_14;
+
+/// A websocket speaking the message version agreed on during the handshake.
+///
+/// Handlers work with the current messages; this adapts them to and from the
+/// client's version. Adding a message version means adding a match arm to
+/// `encode` and `decode` that converts between it and `current`. Versions
+/// left out of `Version::SUPPORTED` never get a socket, their arms refuse
+/// everything.
+pub struct ClientSocket {
+    socket: WebSocket,
+    version: Version,
+}
+
+pub type ParseError = (Option<RequestId>, serde_json::Error);
+
+impl ClientSocket {
+    pub fn new(socket: WebSocket, version: Version) -> Self {
+        Self { socket, version }
+    }
+
+    /// Tells the client which version was picked.
+    pub async fn welcome(&mut self) -> Result<(), axum::Error> {
+        let welcome = Welcome {
+            version: self.version,
+            supported: Version::SUPPORTED.to_vec(),
+        };
+        self.send_text(serde_json::to_string(&welcome).unwrap())
+            .await
+    }
+
+    pub async fn send(&mut self, msg: &ServerToClient) -> Result<(), axum::Error> {
+        match encode(self.version, msg) {
+            Some(text) => self.send_text(text).await,
+            None => Ok(()),
+        }
+    }
+
+    /// The next frame, with text frames still to be decoded.
+    pub async fn recv(&mut self) -> Option<Result<Message, axum::Error>> {
+        self.socket.recv().await
+    }
+
+    pub fn decode(&self, text: &str) -> Result<(Option<RequestId>, ClientToServer), ParseError> {
+        match self.version {
+            Version::V2025_10_14 => Err((None, serde::de::Error::custom("Unsupported version."))),
+            Version::V2026_10_19 => parse_request(text),
+        }
+    }
+
+    async fn send_text(&mut self, text: String) -> Result<(), axum::Error> {
+        self.socket.send(Message::Text(text.into())).await
+    }
+}
+
+/// Closes a socket whose client speaks no version the server does.
+pub async fn refuse(mut socket: WebSocket, code: u16, reason: &'static str) {
+    _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        })))
+        .await;
+}
+
+/// Tells a client from before the handshake to reload. It does not know the
+/// reload close code and would just reconnect, so it gets a post it can show
+/// instead, and the socket stays open until the client goes away.
+pub async fn remind_legacy_client(mut socket: WebSocket) {
+    for text in legacy_reload_notice(chrono::Utc::now()) {
+        if socket.send(Message::Text(text.into())).await.is_err() {
+            return;
+        }
+    }
+    while let Some(Ok(msg)) = socket.recv().await {
+        if let Message::Close(_) = msg {
+            break;
+        }
+    }
+}
+
+fn legacy_reload_notice(now: chrono::DateTime<chrono::Utc>) -> [String; 2] {
+    use v2025_10_14::messages::ServerToClient;
+
+    let author = v2025_10_14::user::UserId::new();
+    let user = ServerToClient::User(
+        author,
+        v2025_10_14::user::display_user::UserDisplay {
+            display_name: Some("Peer Practice".to_string()),
+            id: author,
+        },
+    );
+    let post = ServerToClient::Post(
+        v2025_10_14::post::PostId::NULL,
+        v2025_10_14::post::Post {
+            title: v2025_10_14::post::Topics::Basics,
+            content: "**This page is out of date.** Please reload it to see the current posts."
+                .to_string(),
+            level: v2025_10_14::level::Level::Beginner1,
+            owner: author,
+            date: now,
+            partaking_users: Default::default(),
+        },
+    );
+    [user, post].map(|msg| serde_json::to_string(&msg).unwrap())
+}
+
+/// The text a client speaking `version` receives for `msg`, `None` when that
+/// version has nothing like it.
+fn encode(version: Version, msg: &ServerToClient) -> Option<String> {
+    match version {
+        Version::V2025_10_14 => None,
+        Version::V2026_10_19 => serde_json::to_string(msg).ok(),
+    }
+}
+
+/// Reads a `ClientRequest`, or a bare `ClientToServer` from clients that do
+/// not send request ids. A request id is recovered for errors where possible.
+fn parse_request(text: &str) -> Result<(Option<RequestId>, ClientToServer), ParseError> {
+    #[derive(serde::Deserialize)]
+    struct IdOnly {
+        id: RequestId,
+    }
+
+    match serde_json::from_str::<ClientRequest>(text) {
+        Ok(request) => return Ok((Some(request.id), request.message)),
+        Err(err) => {
+            if let Ok(only) = serde_json::from_str::<IdOnly>(text) {
+                return Err((Some(only.id), err));
+            }
+        }
+    }
+    serde_json::from_str::<ClientToServer>(text)
+        .map(|msg| (None, msg))
+        .map_err(|err| (None, err))
+}
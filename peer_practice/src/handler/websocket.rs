@@ -1,22 +1,28 @@
 use axum::extract::ws::{Message, WebSocket};
-use axum::extract::{State, WebSocketUpgrade};
+use axum::extract::{Query, State, WebSocketUpgrade};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use axum_extra::extract::CookieJar;
 use jsonwebtoken::{DecodingKey, Validation, decode};
+use std::collections::HashMap;
 use tokio::sync::oneshot;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::app_state::AppState;
 use crate::handler::claims::Claims;
-use crate::handler::client_communication::handle_websocket_message;
+use crate::handler::client_communication::{handle_websocket_message, is_organizer};
+use crate::handler::protocol::{self, ClientSocket};
 use peer_practice_server_services::ws_hub::WsHubMsg;
-use peer_practice_shared::messages::{ClientToServer, ServerToClient};
+use peer_practice_shared::Version;
+use peer_practice_shared::handshake::{self, Mismatch};
+use peer_practice_shared::messages::ServerToClient;
+use peer_practice_shared::request::RequestError;
 use peer_practice_shared::user::UserId;
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
     jar: CookieJar,
 ) -> Response {
     let access_token = match jar.get("access_token") {
@@ -31,7 +37,13 @@ pub async fn ws_handler(
                 "User '{:?}' connected via WebSocket",
                 token_data.claims.user_id
             );
-            ws.on_upgrade(move |socket| handle_socket(socket, token_data.claims.user_id, state))
+            let user_id = token_data.claims.user_id;
+            let offered = query.get(handshake::VERSIONS_PARAM).cloned();
+            ws.on_upgrade(move |socket| async move {
+                if let Some(socket) = open(socket, offered.as_deref(), user_id).await {
+                    handle_socket(socket, user_id, state).await
+                }
+            })
         }
         Err(e) => {
             error!("{e}");
@@ -40,7 +52,43 @@ pub async fn ws_handler(
     }
 }
 
-async fn handle_socket(mut socket: WebSocket, user_id: UserId, state: AppState) {
+/// Agrees on a message version with the client, or tells it why there is none.
+async fn open(socket: WebSocket, offered: Option<&str>, user_id: UserId) -> Option<ClientSocket> {
+    match handshake::negotiate(offered.unwrap_or(handshake::LEGACY_VERSION)) {
+        Ok(version) => {
+            let mut socket = ClientSocket::new(socket, version);
+            if offered.is_some() && socket.welcome().await.is_err() {
+                return None;
+            }
+            Some(socket)
+        }
+        Err(mismatch) => {
+            warn!(
+                user_id = ?user_id,
+                offered = ?offered,
+                supported = ?Version::SUPPORTED,
+                "no common protocol version: {mismatch:?}"
+            );
+            match (offered, mismatch) {
+                (None, _) => protocol::remind_legacy_client(socket).await,
+                (Some(_), Mismatch::ClientTooOld) => {
+                    protocol::refuse(
+                        socket,
+                        mismatch.close_code(),
+                        "Client too old, please reload.",
+                    )
+                    .await
+                }
+                (Some(_), Mismatch::ClientTooNew) => {
+                    protocol::refuse(socket, mismatch.close_code(), "Server not updated yet.").await
+                }
+            }
+            None
+        }
+    }
+}
+
+async fn handle_socket(mut socket: ClientSocket, user_id: UserId, state: AppState) {
     let (tx, rx) = oneshot::channel();
     let _ = state
         .ws_hub
@@ -60,12 +108,19 @@ async fn handle_socket(mut socket: WebSocket, user_id: UserId, state: AppState)
             return;
         }
     };
+    if socket.send(&ServerToClient::YouAre(user_id)).await.is_err() {
+        return;
+    }
+    let organizer = is_organizer(&state, user_id).await;
+    if socket
+        .send(&ServerToClient::Organizer(organizer))
+        .await
+        .is_err()
+    {
+        return;
+    }
     if socket
-        .send(Message::Text(
-            serde_json::to_string(&ServerToClient::YouAre(user_id))
-                .unwrap()
-                .into(),
-        ))
+        .send(&ServerToClient::Levels(state.levels.as_ref().clone()))
         .await
         .is_err()
     {
@@ -77,11 +132,7 @@ async fn handle_socket(mut socket: WebSocket, user_id: UserId, state: AppState)
             maybe_msg = hub_rx.recv() => {
                 match maybe_msg {
                     Some(server_msg) => {
-                        let Ok(text) = serde_json::to_string(&server_msg) else {
-                            error!("Failed to serialize ServerToClient message for {:?}", user_id);
-                            continue;
-                        };
-                        if socket.send(Message::Text(text.into())).await.is_err() {
+                        if socket.send(&server_msg).await.is_err() {
                             // Client disconnected
                             break;
                         }
@@ -97,13 +148,32 @@ async fn handle_socket(mut socket: WebSocket, user_id: UserId, state: AppState)
                 match maybe_ws {
                     Some(Ok(Message::Text(text))) => {
                         info!("Received message from {:?}: {}", user_id, text);
-                        match serde_json::from_str::<ClientToServer>(&text) {
-                            Ok(msg) => {
-                                handle_websocket_message(&mut socket, &state, user_id, msg).await;
-                            }
-                            Err(e) => {
+                        let (request, result) = match socket.decode(&text) {
+                            Ok((request, msg)) => (
+                                request,
+                                handle_websocket_message(&mut socket, &state, user_id, msg).await,
+                            ),
+                            Err((request, e)) => {
                                 error!("Failed to parse ClientToServer from {:?}: {}", user_id, e);
+                                (request, Err(RequestError::invalid(e.to_string())))
                             }
+                        };
+                        if let Err(err) = &result {
+                            info!(user_id = ?user_id, request = ?request, "rejected client command: {}", err);
+                        }
+                        let Some(request) = request else {
+                            continue;
+                        };
+                        let reply = match result {
+                            Ok(()) => ServerToClient::Ack(request),
+                            Err(err) => ServerToClient::Error {
+                                request,
+                                code: err.code,
+                                message: err.message,
+                            },
+                        };
+                        if socket.send(&reply).await.is_err() {
+                            break;
                         }
                     }
                     Some(Ok(Message::Close(_))) => {
@@ -123,3 +193,86 @@ async fn handle_socket(mut socket: WebSocket, user_id: UserId, state: AppState)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::routing::get;
+    use futures_util::StreamExt;
+    use peer_practice_shared::handshake::Welcome;
+    use peer_practice_shared::v2025_10_14;
+    use tokio_tungstenite::tungstenite;
+
+    async fn serve() -> String {
+        async fn upgrade(
+            ws: WebSocketUpgrade,
+            Query(query): Query<HashMap<String, String>>,
+        ) -> Response {
+            let offered = query.get(handshake::VERSIONS_PARAM).cloned();
+            ws.on_upgrade(move |socket| async move {
+                if let Some(mut socket) = open(socket, offered.as_deref(), UserId::new()).await {
+                    let _ = socket.send(&ServerToClient::YouAre(UserId::new())).await;
+                }
+            })
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::serve(listener, Router::new().route("/v1/ws", get(upgrade))).into_future(),
+        );
+        format!("ws://{addr}/v1/ws")
+    }
+
+    async fn next_text<S>(client: &mut S) -> String
+    where
+        S: StreamExt<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin,
+    {
+        match client.next().await {
+            Some(Ok(tungstenite::Message::Text(text))) => text.to_string(),
+            other => panic!("expected a text frame, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn clients_from_before_the_handshake_are_asked_to_reload() {
+        let url = serve().await;
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let user = next_text(&mut client).await;
+        assert!(matches!(
+            serde_json::from_str(&user),
+            Ok(v2025_10_14::messages::ServerToClient::User(..))
+        ));
+        match serde_json::from_str(&next_text(&mut client).await) {
+            Ok(v2025_10_14::messages::ServerToClient::Post(_, post)) => {
+                assert!(post.content.contains("reload"))
+            }
+            other => panic!("expected the reload notice, got {other:?}"),
+        }
+        let closed =
+            tokio::time::timeout(std::time::Duration::from_millis(100), client.next()).await;
+        assert!(
+            closed.is_err(),
+            "the socket should stay open, got {closed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn clients_announcing_versions_are_welcomed() {
+        let url = format!(
+            "{}?{}",
+            serve().await,
+            handshake::query(&[Version::CURRENT])
+        );
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+        let welcome = serde_json::from_str::<Welcome>(&next_text(&mut client).await).unwrap();
+        assert_eq!(welcome.version, Version::CURRENT);
+        assert!(matches!(
+            serde_json::from_str(&next_text(&mut client).await),
+            Ok(ServerToClient::YouAre(_))
+        ));
+    }
+}
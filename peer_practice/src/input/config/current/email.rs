@@ -1,37 +1,97 @@
 use eyre::WrapErr;
-use peer_practice_server_services::email::EmailConfiguration;
+use peer_practice_server_services::dkim;
+use peer_practice_server_services::email::{Branding, EmailConfiguration};
+use peer_practice_server_services::transport::{
+    Credentials, SmtpSecurity, Transport, TransportKind,
+};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmailConfig {
     pub from: String,
     pub reply_to: String,
+    pub transport: TransportKind,
     pub tls_relay: String,
     pub credential_email_account: String,
     pub password: String,
+    /// Token of the HTTP mail API.
+    pub http_token: String,
+    pub sendmail_command: Option<PathBuf>,
+    pub file_drop_dir: Option<PathBuf>,
+    pub http_url: Option<String>,
+    pub site_name: String,
+    pub accent_color: String,
+    pub logo_url: Option<String>,
+    pub dkim_key_file: Option<PathBuf>,
+    pub dkim_selector: String,
+    pub dkim_domain: Option<String>,
 }
 impl TryFrom<EmailConfig> for EmailConfiguration {
     type Error = eyre::Error;
     fn try_from(value: EmailConfig) -> Result<Self, Self::Error> {
-        EmailConfiguration::new(
-            value.tls_relay,
-            value.credential_email_account,
-            value.password,
-            &value.from,
-            &value.reply_to,
-        )
-        .with_context(|| "Could not create email configuration.")
+        let credentials = Credentials::new(value.credential_email_account, value.password);
+        let smtp = |security| Transport::Smtp {
+            security,
+            relay: value.tls_relay.clone(),
+            credentials: (security != SmtpSecurity::None).then(|| credentials.clone()),
+        };
+        let transport = match value.transport {
+            TransportKind::Starttls => smtp(SmtpSecurity::Starttls),
+            TransportKind::Tls => smtp(SmtpSecurity::Tls),
+            TransportKind::Relay => smtp(SmtpSecurity::None),
+            TransportKind::Sendmail => Transport::Sendmail {
+                command: value.sendmail_command,
+            },
+            TransportKind::File => Transport::FileDrop {
+                dir: value
+                    .file_drop_dir
+                    .ok_or_else(|| eyre::eyre!("The file transport needs \"file_drop_dir\"."))?,
+            },
+            TransportKind::Http => Transport::Http {
+                url: value
+                    .http_url
+                    .ok_or_else(|| eyre::eyre!("The http transport needs \"http_url\"."))?,
+                token: value.http_token.trim().to_string(),
+            },
+        };
+        let mut configuration =
+            EmailConfiguration::with_transport(transport, &value.from, &value.reply_to)
+                .with_context(|| "Could not create email configuration.")?;
+        if let Some(key_file) = &value.dkim_key_file {
+            let domain = value
+                .dkim_domain
+                .unwrap_or_else(|| configuration.from.email.domain().to_string());
+            configuration.dkim = Some(dkim::load(key_file, value.dkim_selector, domain)?);
+        }
+        configuration.branding = Branding {
+            site_name: value.site_name,
+            accent_color: value.accent_color,
+            logo_url: value.logo_url,
+        };
+        Ok(configuration)
     }
 }
 
 impl Default for EmailConfig {
     fn default() -> Self {
         EmailConfig {
+            transport: TransportKind::default(),
             tls_relay: "smtp.gmail.com:587".to_string(),
+            sendmail_command: None,
+            file_drop_dir: None,
+            http_url: None,
             password: "change-me-email-password".to_string(),
+            http_token: String::new(),
             credential_email_account: "for@example.com".to_string(),
             reply_to: "replay.to@example.com".to_string(),
             from: "from@example.com".to_string(),
+            site_name: Branding::default().site_name,
+            accent_color: Branding::default().accent_color,
+            logo_url: None,
+            dkim_key_file: None,
+            dkim_selector: dkim::DEFAULT_SELECTOR.to_string(),
+            dkim_domain: None,
         }
     }
 }
@@ -1,3 +1,4 @@
+use peer_practice_shared::level::Levels;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -8,6 +9,10 @@ pub struct ServerConfig {
     pub data_dir: PathBuf,
     pub port: u16,
     pub cors_allowed_origins: Vec<String>,
+    pub organizers: Vec<String>,
+    pub levels: Levels,
+    pub deleted_post_retention_days: u32,
+    pub public_url: String,
 }
 
 impl Default for ServerConfig {
@@ -21,6 +26,10 @@ impl Default for ServerConfig {
                 "http://localhost".to_string(),
                 "https://localhost".to_string(),
             ],
+            organizers: Vec::new(),
+            levels: Levels::default(),
+            deleted_post_retention_days: 30,
+            public_url: "http://localhost:3000".to_string(),
         }
     }
 }
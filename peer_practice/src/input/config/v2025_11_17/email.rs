@@ -1,5 +1,7 @@
 use eyre::WrapErr;
-use peer_practice_server_services::email::EmailConfiguration;
+use peer_practice_server_services::dkim;
+use peer_practice_server_services::email::{Branding, EmailConfiguration};
+use peer_practice_server_services::transport::TransportKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,8 +32,19 @@ impl From<EmailConfig> for crate::input::config::current::email::EmailConfig {
             from: value.from,
             reply_to: value.reply_to,
             password: value.password,
+            http_token: String::new(),
+            transport: TransportKind::Starttls,
             tls_relay: value.tls_relay,
             credential_email_account: value.credential_email_account,
+            sendmail_command: None,
+            file_drop_dir: None,
+            http_url: None,
+            site_name: Branding::default().site_name,
+            accent_color: Branding::default().accent_color,
+            logo_url: None,
+            dkim_key_file: None,
+            dkim_selector: dkim::DEFAULT_SELECTOR.to_string(),
+            dkim_domain: None,
         }
     }
 }
@@ -1,3 +1,4 @@
+use peer_practice_shared::level::Levels;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -18,6 +19,10 @@ impl From<ServerConfig> for crate::input::config::current::server::ServerConfig
             port: value.port,
             webroot: value.webroot,
             cors_allowed_origins: value.cors_allowed_origins,
+            organizers: Vec::new(),
+            levels: Levels::default(),
+            deleted_post_retention_days: 30,
+            public_url: "http://localhost:3000".to_string(),
         }
     }
 }
@@ -1,5 +1,7 @@
 use eyre::WrapErr;
-use peer_practice_server_services::email::EmailConfiguration;
+use peer_practice_server_services::dkim;
+use peer_practice_server_services::email::{Branding, EmailConfiguration};
+use peer_practice_server_services::transport::TransportKind;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -7,22 +9,83 @@ use std::path::PathBuf;
 pub struct EmailConfig {
     pub from: String,
     pub reply_to: String,
+    /// How emails leave the server.
+    #[serde(default)]
+    pub transport: TransportKind,
+    /// SMTP host, optionally followed by `:port`.
+    #[serde(default)]
     pub tls_relay: String,
+    #[serde(default)]
     pub credential_email_account: String,
-    pub password_file: PathBuf,
+    /// SMTP password.
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
+    /// Token of the HTTP mail API.
+    #[serde(default)]
+    pub http_token_file: Option<PathBuf>,
+    /// Command of the `sendmail` transport, `sendmail` from the `PATH` if unset.
+    #[serde(default)]
+    pub sendmail_command: Option<PathBuf>,
+    /// Directory the `file` transport writes emails to.
+    #[serde(default)]
+    pub file_drop_dir: Option<PathBuf>,
+    /// Endpoint of the `http` transport.
+    #[serde(default)]
+    pub http_url: Option<String>,
+    /// Name shown in the header of emails.
+    #[serde(default = "default_site_name")]
+    pub site_name: String,
+    /// CSS color of the header of emails.
+    #[serde(default = "default_accent_color")]
+    pub accent_color: String,
+    /// Logo shown instead of the site name.
+    #[serde(default)]
+    pub logo_url: Option<String>,
+    /// RSA key in PEM that outgoing emails are DKIM-signed with, unsigned if
+    /// unset.
+    #[serde(default)]
+    pub dkim_key_file: Option<PathBuf>,
+    #[serde(default = "default_dkim_selector")]
+    pub dkim_selector: String,
+    /// The domain of `from` if unset.
+    #[serde(default)]
+    pub dkim_domain: Option<String>,
 }
+
+fn default_site_name() -> String {
+    Branding::default().site_name
+}
+
+fn default_accent_color() -> String {
+    Branding::default().accent_color
+}
+
+fn default_dkim_selector() -> String {
+    dkim::DEFAULT_SELECTOR.to_string()
+}
+
+/// Reads the secret in `file`, which `transport` cannot log in without if it
+/// `needs` it; the others do without.
+fn read_secret(
+    transport: TransportKind,
+    needs: bool,
+    name: &str,
+    file: &Option<PathBuf>,
+) -> Result<String, eyre::Error> {
+    match file {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read {}.", path.display())),
+        None if needs => Err(eyre::eyre!(
+            "The {transport:?} email transport needs a {name}."
+        )),
+        None => Ok(String::new()),
+    }
+}
+
 impl TryFrom<EmailConfig> for EmailConfiguration {
     type Error = eyre::Error;
     fn try_from(value: EmailConfig) -> Result<Self, Self::Error> {
-        let password = std::fs::read_to_string(&value.password_file)?;
-        EmailConfiguration::new(
-            value.tls_relay,
-            value.credential_email_account,
-            password,
-            &value.from,
-            &value.reply_to,
-        )
-        .with_context(|| "Could not create email configuration.")
+        crate::input::config::current::email::EmailConfig::try_from(value)?.try_into()
     }
 }
 
@@ -30,10 +93,21 @@ impl Default for EmailConfig {
     fn default() -> Self {
         EmailConfig {
             tls_relay: "smtp.gmail.com:587".to_string(),
-            password_file: PathBuf::from("/very/good/protected/email_password.txt"),
+            transport: TransportKind::default(),
+            password_file: Some(PathBuf::from("/very/good/protected/email_password.txt")),
+            http_token_file: None,
+            sendmail_command: None,
+            file_drop_dir: None,
+            http_url: None,
             credential_email_account: "for@example.com".to_string(),
             reply_to: "replay.to@example.com".to_string(),
             from: "from@example.com".to_string(),
+            site_name: default_site_name(),
+            accent_color: default_accent_color(),
+            logo_url: None,
+            dkim_key_file: None,
+            dkim_selector: default_dkim_selector(),
+            dkim_domain: None,
         }
     }
 }
@@ -41,14 +115,38 @@ impl Default for EmailConfig {
 impl TryFrom<EmailConfig> for crate::input::config::current::email::EmailConfig {
     type Error = eyre::Error;
     fn try_from(value: EmailConfig) -> Result<Self, Self::Error> {
-        let password =
-            std::fs::read_to_string(&value.password_file)?;
+        let password = read_secret(
+            value.transport,
+            matches!(
+                value.transport,
+                TransportKind::Starttls | TransportKind::Tls
+            ),
+            "password_file",
+            &value.password_file,
+        )?;
+        let http_token = read_secret(
+            value.transport,
+            matches!(value.transport, TransportKind::Http),
+            "http_token_file",
+            &value.http_token_file,
+        )?;
         Ok(Self {
             from: value.from,
             reply_to: value.reply_to,
             password,
+            http_token,
+            transport: value.transport,
             tls_relay: value.tls_relay,
             credential_email_account: value.credential_email_account,
+            sendmail_command: value.sendmail_command,
+            file_drop_dir: value.file_drop_dir,
+            http_url: value.http_url,
+            site_name: value.site_name,
+            accent_color: value.accent_color,
+            logo_url: value.logo_url,
+            dkim_key_file: value.dkim_key_file,
+            dkim_selector: value.dkim_selector,
+            dkim_domain: value.dkim_domain,
         })
     }
 }
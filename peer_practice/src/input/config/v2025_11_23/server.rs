@@ -1,3 +1,4 @@
+use peer_practice_shared::level::{Level, Levels};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -8,6 +9,34 @@ pub struct ServerConfig {
     pub data_dir: PathBuf,
     pub port: u16,
     pub cors_allowed_origins: Vec<String>,
+    /// Email addresses of the users allowed to manage topics.
+    #[serde(default)]
+    pub organizers: Vec<String>,
+    /// Skill levels offered for posts, ordered from lowest to highest.
+    #[serde(default = "default_levels")]
+    pub levels: Vec<String>,
+    /// Days a deleted post can still be restored before it is purged.
+    #[serde(default = "default_deleted_post_retention_days")]
+    pub deleted_post_retention_days: u32,
+    /// Address the site is reachable at, used for links in emails.
+    #[serde(default = "default_public_url")]
+    pub public_url: String,
+}
+
+fn default_public_url() -> String {
+    "http://localhost:3000".to_string()
+}
+
+fn default_deleted_post_retention_days() -> u32 {
+    30
+}
+
+fn default_levels() -> Vec<String> {
+    Levels::default()
+        .all()
+        .iter()
+        .map(|level| level.to_string())
+        .collect()
 }
 
 impl Default for ServerConfig {
@@ -21,6 +50,10 @@ impl Default for ServerConfig {
                 "http://localhost".to_string(),
                 "https://localhost".to_string(),
             ],
+            organizers: Vec::new(),
+            levels: default_levels(),
+            deleted_post_retention_days: default_deleted_post_retention_days(),
+            public_url: default_public_url(),
         }
     }
 }
@@ -28,6 +61,17 @@ impl Default for ServerConfig {
 impl TryFrom<ServerConfig> for crate::input::config::current::server::ServerConfig {
     type Error = eyre::Error;
     fn try_from(value: ServerConfig) -> Result<Self, Self::Error> {
+        if value.levels.is_empty() {
+            eyre::bail!("At least one level must be configured.");
+        }
+        if let Some(level) = value
+            .levels
+            .iter()
+            .enumerate()
+            .find_map(|(index, level)| value.levels[..index].contains(level).then_some(level))
+        {
+            eyre::bail!("The level {level} is configured more than once.");
+        }
         let jwt_secret = std::fs::read_to_string(&value.jwt_secret_file)?;
         Ok(Self {
             jwt_secret,
@@ -35,6 +79,10 @@ impl TryFrom<ServerConfig> for crate::input::config::current::server::ServerConf
             port: value.port,
             webroot: value.webroot,
             cors_allowed_origins: value.cors_allowed_origins,
+            organizers: value.organizers,
+            levels: Levels::new(value.levels.into_iter().map(Level::new).collect()),
+            deleted_post_retention_days: value.deleted_post_retention_days,
+            public_url: value.public_url.trim_end_matches('/').to_string(),
         })
     }
 }
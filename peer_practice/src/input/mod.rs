@@ -1,4 +1,5 @@
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use crate::input::config::current::{Config, Envelope};
@@ -6,6 +7,7 @@ use crate::run;
 use clap::{Parser, Subcommand};
 use config::ConfigEnvelope;
 use eyre::{Context, eyre};
+use peer_practice_server_services::dkim;
 
 pub mod config;
 
@@ -33,6 +35,20 @@ impl App {
 
                 run(file_cfg).await
             }
+            Commands::DkimKey {
+                path,
+                domain,
+                selector,
+            } => {
+                let record = generate_dkim_key(&path, &domain, &selector)?;
+                println!("Wrote DKIM key to {}", path.display());
+                println!("Publish this DNS record:\n\n{record}\n");
+                println!(
+                    "Then set email.dkim_key_file = \"{}\" and email.dkim_selector = \"{selector}\".",
+                    path.display()
+                );
+                Ok(())
+            }
             Commands::Show { config } => {
                 let file_cfg = read_config_file(&config)
                     .with_context(|| format!("Failed to read {}", config.display()))?;
@@ -64,6 +80,21 @@ enum Commands {
         config: PathBuf,
     },
 
+    /// Generate a DKIM signing key and print its DNS TXT record
+    DkimKey {
+        /// Where to write the private key
+        #[arg(value_name = "FILE")]
+        path: PathBuf,
+
+        /// Domain the emails are sent from
+        #[arg(long)]
+        domain: String,
+
+        /// Name of the key in DNS
+        #[arg(long, default_value = dkim::DEFAULT_SELECTOR)]
+        selector: String,
+    },
+
     /// Show current configuration (differences from defaults by default)
     Show {
         /// TOML config file path
@@ -93,6 +124,37 @@ fn generate_default_file(path: &Path, force: bool) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Writes a new key, never replacing one whose DNS record may be published.
+fn generate_dkim_key(path: &Path, domain: &str, selector: &str) -> eyre::Result<String> {
+    if path.exists() {
+        return Err(eyre!(
+            "Refusing to overwrite existing key: {} (delete it to rotate the key)",
+            path.display()
+        ));
+    }
+
+    let (pem, value) = dkim::generate()?;
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(pem.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(dkim::dns_record(selector, domain, &value))
+}
+
 fn read_config_file(path: &Path) -> eyre::Result<Config> {
     let data = fs::read_to_string(path)
         .with_context(|| format!("Failed to read config file: {}", path.display()))?;
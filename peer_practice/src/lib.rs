@@ -12,8 +12,11 @@ use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitEx
 
 use crate::input::config::current::Config;
 use app_state::AppState;
+use handler::calendar;
+use handler::join_link;
 use handler::login;
 use handler::websocket;
+use services::scheduler::{self, Schedule};
 
 mod app_state;
 mod handler;
@@ -32,10 +35,40 @@ async fn run(config: Config) -> Result<()> {
     let _logging_guard = init_file_logging(&config.server.data_dir)?;
     let state = AppState::new(config.clone());
 
-    tokio::spawn(services::run_expired_posts_reaper(
-        state.clone(),
-        chrono::Duration::hours(1),
-    ));
+    let retention = chrono::Duration::days(config.server.deleted_post_retention_days.into());
+    let reaper_state = state.clone();
+    scheduler::spawn_job(
+        "expired posts reaper",
+        Schedule::Every(chrono::Duration::hours(1)),
+        move |now| {
+            let state = reaper_state.clone();
+            async move {
+                services::remove_expired_posts(&state, now).await?;
+                services::purge_deleted_posts(&state, now - retention).await
+            }
+        },
+    );
+    let reminders_state = state.clone();
+    scheduler::spawn_job(
+        "reminders",
+        Schedule::Every(chrono::Duration::minutes(15)),
+        move |now| {
+            let state = reminders_state.clone();
+            async move { services::reminders::send_reminders(&state, now).await }
+        },
+    );
+    let digest_state = state.clone();
+    scheduler::spawn_job(
+        "practice digest",
+        Schedule::BeforePractice {
+            days_before: services::digest::DIGEST_DAYS_BEFORE,
+            at: chrono::NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+        },
+        move |at| {
+            let state = digest_state.clone();
+            async move { services::digest::send_digests(&state, at).await }
+        },
+    );
 
     info!(
         "Serving static files from: {}",
@@ -60,6 +93,12 @@ async fn run(config: Config) -> Result<()> {
         .route("/v1/pin", post(login::pin_handler))
         .route("/v1/login", post(login::login_handler))
         .route("/v1/ws", get(websocket::ws_handler))
+        .route("/v1/calendar.ics", get(calendar::public_calendar_handler))
+        .route("/v1/calendar/{token}", get(calendar::user_calendar_handler))
+        .route(
+            "/v1/join/{token}",
+            get(join_link::join_link_page).post(join_link::join_link_handler),
+        )
         .with_state(state)
         .layer(CorsLayer::new().allow_origin(cors_origin).allow_methods([
             Method::POST,
@@ -0,0 +1,101 @@
+use chrono::Utc;
+use tokio::sync::oneshot;
+use tracing::{error, info};
+
+use crate::app_state::AppState;
+use peer_practice_server_services::calendar::{Calendar, CalendarMethod};
+use peer_practice_server_services::email::EmailMsg;
+use peer_practice_server_services::posts::PostsMsg;
+use peer_practice_server_services::topics::TopicsMsg;
+use peer_practice_server_services::users::UsersMsg;
+use peer_practice_shared::post::{Post, PostId};
+use peer_practice_shared::user::UserId;
+
+/// Emails the current state of `post_id` to `user_id` if they opted in to calendar invites.
+///
+/// Invites are only sent once `user_id` actually partakes, not while on the waitlist.
+pub async fn send_for_post(
+    state: AppState,
+    user_id: UserId,
+    post_id: PostId,
+    method: CalendarMethod,
+) {
+    let (tx, rx) = oneshot::channel();
+    _ = state.posts.send(PostsMsg::Get(post_id, tx)).await;
+    if let Ok(Some(post)) = rx.await
+        && (method == CalendarMethod::Cancel || post.partaking_users.contains(&user_id))
+    {
+        send(&state, user_id, post_id, &post, method).await;
+    }
+}
+
+/// Emails `post` to every opted-in user in `users`, e.g. before the post is removed.
+pub async fn send_to_users(
+    state: AppState,
+    users: Vec<UserId>,
+    post_id: PostId,
+    post: Post,
+    method: CalendarMethod,
+) {
+    for user_id in users {
+        send(&state, user_id, post_id, &post, method).await;
+    }
+}
+
+async fn send(
+    state: &AppState,
+    user_id: UserId,
+    post_id: PostId,
+    post: &Post,
+    method: CalendarMethod,
+) {
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .users
+        .send(UsersMsg::GetById {
+            id: user_id,
+            respond_to: tx,
+        })
+        .await;
+    let Ok(Some(user)) = rx.await else {
+        return;
+    };
+    if !user.config.calendar_invites {
+        return;
+    }
+
+    let (tx, rx) = oneshot::channel();
+    _ = state.topics.send(TopicsMsg::List(tx)).await;
+    let topics = rx.await.unwrap_or_default();
+
+    let calendar = Calendar::invite(
+        method,
+        post_id,
+        post,
+        &topics,
+        user.email.value(),
+        Utc::now(),
+    );
+    let (tx, rx) = oneshot::channel();
+    _ = state
+        .email
+        .send(EmailMsg::SendCalendarInvite {
+            target: user.email.into(),
+            calendar,
+            respond_to: tx,
+        })
+        .await;
+    match rx.await {
+        Ok(Ok(_)) => info!(
+            user_id = ?user_id,
+            post_id = ?post_id,
+            method = method.as_str(),
+            "sent calendar invite"
+        ),
+        Ok(Err(err)) => error!("Failed to send calendar invite to {:?}: {:?}", user_id, err),
+        Err(err) => error!(
+            "Email actor dropped calendar invite for {:?}: {}",
+            user_id, err
+        ),
+    }
+}
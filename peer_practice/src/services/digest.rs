@@ -0,0 +1,103 @@
+use chrono::{DateTime, Days, Duration, Utc};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use tokio::sync::oneshot;
+use tracing::{error, info};
+
+use crate::app_state::AppState;
+use crate::handler::claims::JoinClaims;
+use peer_practice_server_services::digest::Digest;
+use peer_practice_server_services::email::EmailMsg;
+use peer_practice_server_services::posts::PostsMsg;
+use peer_practice_server_services::topics::TopicsMsg;
+use peer_practice_server_services::users::UsersMsg;
+use peer_practice_shared::convert_utc_to_local_date;
+use peer_practice_shared::post::PostId;
+use peer_practice_shared::user::UserId;
+
+/// How long before a practice evening the digest goes out.
+pub const DIGEST_DAYS_BEFORE: u64 = 2;
+
+/// Emails every opted-in user the posts of the practice evening
+/// `DIGEST_DAYS_BEFORE` days after `at`, if there are any.
+pub async fn send_digests(state: &AppState, at: DateTime<Utc>) -> eyre::Result<()> {
+    let Some(date) = convert_utc_to_local_date(at).checked_add_days(Days::new(DIGEST_DAYS_BEFORE))
+    else {
+        return Ok(());
+    };
+
+    let (tx, rx) = oneshot::channel();
+    state.posts.send(PostsMsg::List(tx)).await?;
+    let posts = rx
+        .await?
+        .into_iter()
+        .filter(|(_, post)| convert_utc_to_local_date(post.date) == date)
+        .collect::<Vec<_>>();
+    if posts.is_empty() {
+        info!(date = %date, "no posts for the practice digest");
+        return Ok(());
+    }
+
+    let (tx, rx) = oneshot::channel();
+    state.topics.send(TopicsMsg::List(tx)).await?;
+    let topics = rx.await?;
+    let (tx, rx) = oneshot::channel();
+    state.users.send(UsersMsg::List { respond_to: tx }).await?;
+    let users = rx.await?;
+    let name = |id: UserId| {
+        users
+            .iter()
+            .find(|user| user.id == id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    // Links stay valid until the evening is over.
+    let expires = at + Duration::days(DIGEST_DAYS_BEFORE as i64 + 1);
+
+    for user in users.iter().filter(|user| user.config.practice_digest) {
+        let digest = Digest::new(
+            date,
+            user.id,
+            &posts,
+            &topics,
+            &state.levels,
+            name,
+            |post| join_link(state, user.id, post, expires),
+        );
+        let (tx, rx) = oneshot::channel();
+        state
+            .email
+            .send(EmailMsg::SendDigest {
+                target: user.email.clone().into(),
+                digest,
+                respond_to: tx,
+            })
+            .await?;
+        match rx.await {
+            Ok(Ok(_)) => info!(user_id = ?user.id, date = %date, "sent practice digest"),
+            Ok(Err(err)) => error!("Failed to send digest to {:?}: {:?}", user.id, err),
+            Err(err) => error!("Email actor dropped digest for {:?}: {}", user.id, err),
+        }
+    }
+
+    Ok(())
+}
+
+/// A link that makes `user_id` join `post_id` when opened.
+fn join_link(state: &AppState, user_id: UserId, post_id: PostId, expires: DateTime<Utc>) -> String {
+    let claims = JoinClaims {
+        user_id,
+        post_id,
+        exp: expires.timestamp() as usize,
+    };
+    match encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(&JoinClaims::secret(&state.jwt_secret)),
+    ) {
+        Ok(token) => format!("{}/v1/join/{token}", state.public_url),
+        Err(err) => {
+            error!("Could not sign join link: {:?}", err);
+            state.public_url.clone()
+        }
+    }
+}
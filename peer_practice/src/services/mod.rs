@@ -1,6 +1,13 @@
 use crate::app_state::AppState;
 
+pub mod calendar_invites;
+pub mod digest;
+pub mod reminders;
+pub mod scheduler;
+
 use chrono::{DateTime, Duration, Utc};
+use peer_practice_server_services::comments::CommentsMsg;
+use peer_practice_server_services::history::HistoryMsg;
 use peer_practice_server_services::posts::PostsMsg;
 
 pub async fn remove_expired_posts(app_state: &AppState, now: DateTime<Utc>) -> eyre::Result<()> {
@@ -9,19 +16,28 @@ pub async fn remove_expired_posts(app_state: &AppState, now: DateTime<Utc>) -> e
     for (id, post) in rx.await? {
         let due = post.date + Duration::days(2);
         if due < now {
-            app_state.posts.send(PostsMsg::Remove(id)).await?;
+            app_state.posts.send(PostsMsg::Remove(id, None)).await?;
         }
     }
 
     Ok(())
 }
 
-pub async fn run_expired_posts_reaper(app_state: AppState, interval: Duration) {
-    loop {
-        let now = Utc::now();
-        if let Err(err) = remove_expired_posts(&app_state, now).await {
-            eprintln!("expired posts reaper error: {err}");
-        }
-        tokio::time::sleep(interval.to_std().unwrap()).await;
+/// Forgets deleted posts that could no longer be restored, together with
+/// their comments and versions.
+pub async fn purge_deleted_posts(app_state: &AppState, before: DateTime<Utc>) -> eyre::Result<()> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_state.posts.send(PostsMsg::Purge(before, tx)).await?;
+    let purged = rx.await?;
+    for id in &purged {
+        app_state
+            .comments
+            .send(CommentsMsg::RemoveForPost(*id))
+            .await?;
     }
+    if !purged.is_empty() {
+        app_state.history.send(HistoryMsg::Forget(purged)).await?;
+    }
+
+    Ok(())
 }
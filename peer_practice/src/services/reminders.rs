@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use tokio::sync::oneshot;
+
+use crate::app_state::AppState;
+use peer_practice_server_services::notifications::NotificationsMsg;
+use peer_practice_server_services::posts::PostsMsg;
+use peer_practice_server_services::reminders::due_reminders;
+use peer_practice_server_services::users::UsersMsg;
+
+/// Hands the reminders due at `now` to the notifications actor, which skips
+/// those already sent.
+pub async fn send_reminders(state: &AppState, now: DateTime<Utc>) -> eyre::Result<()> {
+    let (tx, rx) = oneshot::channel();
+    state.posts.send(PostsMsg::List(tx)).await?;
+    let posts = rx.await?;
+    let (tx, rx) = oneshot::channel();
+    state.users.send(UsersMsg::List { respond_to: tx }).await?;
+    let users = rx.await?;
+
+    let due = due_reminders(&posts, &users, now)
+        .into_iter()
+        .filter_map(|reminder| {
+            posts
+                .iter()
+                .find(|(id, _)| *id == reminder.post)
+                .map(|(_, post)| (reminder, post.clone()))
+        })
+        .collect::<Vec<_>>();
+    if !due.is_empty() {
+        state
+            .notifications
+            .send(NotificationsMsg::Remind(due))
+            .await?;
+    }
+
+    Ok(())
+}
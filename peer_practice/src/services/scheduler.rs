@@ -0,0 +1,95 @@
+use chrono::{DateTime, Days, Duration, Local, NaiveTime, TimeZone, Utc};
+use peer_practice_shared::ymd;
+use tracing::{error, info};
+
+/// When a scheduled job runs.
+#[derive(Debug, Clone, Copy)]
+pub enum Schedule {
+    /// Right away, then again after each interval.
+    Every(Duration),
+    /// At local time `at`, `days_before` each practice evening.
+    BeforePractice { days_before: u64, at: NaiveTime },
+}
+
+impl Schedule {
+    fn first_run(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Every(_) => now,
+            Schedule::BeforePractice { .. } => self.next_after(now),
+        }
+    }
+
+    /// The first run strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match *self {
+            Schedule::Every(interval) => after + interval,
+            Schedule::BeforePractice { days_before, at } => {
+                let today = after.with_timezone(&Local).date_naive();
+                ymd::next_second_and_fourth_fridays(today, 4)
+                    .into_iter()
+                    .filter_map(|practice| practice.checked_sub_days(Days::new(days_before)))
+                    .filter_map(|day| Local.from_local_datetime(&day.and_time(at)).earliest())
+                    .map(|run| run.with_timezone(&Utc))
+                    .find(|run| *run > after)
+                    // Four practice evenings always lie more than `days_before` ahead.
+                    .unwrap_or(after + Duration::days(14))
+            }
+        }
+    }
+}
+
+/// Runs `job` on `schedule` for as long as the server runs, passing it the
+/// time it was scheduled for.
+pub fn spawn_job<F, Fut>(name: &'static str, schedule: Schedule, job: F)
+where
+    F: Fn(DateTime<Utc>) -> Fut + Send + 'static,
+    Fut: Future<Output = eyre::Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut next = schedule.first_run(Utc::now());
+        loop {
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            info!(job = name, "running scheduled job");
+            if let Err(err) = job(next).await {
+                error!(job = name, "scheduled job failed: {err:?}");
+            }
+            next = schedule.next_after(next.max(Utc::now()));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn runs_the_given_days_before_each_practice() {
+        let schedule = Schedule::BeforePractice {
+            days_before: 2,
+            at: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+        };
+        let local = |y, m, d, h| {
+            Local
+                .from_local_datetime(
+                    &NaiveDate::from_ymd_opt(y, m, d)
+                        .unwrap()
+                        .and_hms_opt(h, 0, 0)
+                        .unwrap(),
+                )
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        // The second and fourth Fridays of March 2025 are the 14th and 28th.
+        assert_eq!(
+            schedule.next_after(local(2025, 3, 1, 12)),
+            local(2025, 3, 12, 10)
+        );
+        assert_eq!(
+            schedule.next_after(local(2025, 3, 12, 10)),
+            local(2025, 3, 26, 10)
+        );
+    }
+}
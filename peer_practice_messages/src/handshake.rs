@@ -0,0 +1,105 @@
+//! Agreeing on a message version when a websocket connects. Unlike the
+//! versioned modules this must not change, or old clients can no longer be
+//! told to reload.
+
+use crate::Version;
+use serde::{Deserialize, Serialize};
+
+/// Query parameter of the websocket URL listing the versions a client speaks,
+/// comma separated. Clients from before the handshake leave it out.
+pub const VERSIONS_PARAM: &str = "versions";
+
+/// What clients that do not announce versions speak.
+pub const LEGACY_VERSION: &str = "V2025_10_14";
+
+/// Close code telling a client it is too old for the server and has to reload.
+pub const RELOAD_REQUIRED: u16 = 4426;
+
+/// Close code telling a client that the server does not know its version yet,
+/// e.g. during a deploy. It should connect again later.
+pub const TRY_AGAIN_LATER: u16 = 1013;
+
+/// First frame the server sends to clients that announced their versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Welcome {
+    pub version: Version,
+    pub supported: Vec<Version>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mismatch {
+    ClientTooOld,
+    ClientTooNew,
+}
+
+impl Mismatch {
+    pub fn close_code(self) -> u16 {
+        match self {
+            Mismatch::ClientTooOld => RELOAD_REQUIRED,
+            Mismatch::ClientTooNew => TRY_AGAIN_LATER,
+        }
+    }
+}
+
+/// The URL query announcing `versions`.
+pub fn query(versions: &[Version]) -> String {
+    let versions = versions
+        .iter()
+        .map(|version| version.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("{VERSIONS_PARAM}={versions}")
+}
+
+/// Picks the newest version both sides speak from the comma separated list a
+/// client offered. Version names sort by date, which tells whether a client
+/// offering only unknown versions is behind or ahead of the server.
+pub fn negotiate(offered: &str) -> Result<Version, Mismatch> {
+    let offered = offered
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    if let Some(version) = offered
+        .iter()
+        .filter_map(|name| name.parse::<Version>().ok())
+        .filter(|version| Version::SUPPORTED.contains(version))
+        .max()
+    {
+        return Ok(version);
+    }
+    let newest = Version::SUPPORTED
+        .iter()
+        .max()
+        .map(|version| version.as_str());
+    match offered.iter().max() {
+        Some(offered) if newest.is_some_and(|newest| *offered > newest) => {
+            Err(Mismatch::ClientTooNew)
+        }
+        _ => Err(Mismatch::ClientTooOld),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_the_newest_common_version() {
+        assert_eq!(negotiate(LEGACY_VERSION), Err(Mismatch::ClientTooOld));
+        assert_eq!(
+            negotiate("V2025_10_14, V2026_10_19, V2099_01_01"),
+            Ok(Version::V2026_10_19)
+        );
+        assert_eq!(negotiate("V2099_01_01"), Err(Mismatch::ClientTooNew));
+        assert_eq!(negotiate("V2024_01_01"), Err(Mismatch::ClientTooOld));
+        assert_eq!(negotiate(""), Err(Mismatch::ClientTooOld));
+        assert_eq!(
+            query(Version::SUPPORTED),
+            format!("versions={}", Version::CURRENT)
+        );
+        for version in Version::SUPPORTED {
+            assert_eq!(version.as_str().parse::<Version>(), Ok(*version));
+        }
+    }
+}
@@ -1,13 +1,62 @@
 pub mod current {
-    pub use super::v2025_10_14::*;
+    pub use super::v2026_10_19::*;
 }
 
+pub mod handshake;
 pub mod v2025_10_14;
+pub mod v2026_10_19;
 
-#[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Message versions, oldest first.
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub enum Version {
     #[default]
     V2025_10_14,
+    V2026_10_19,
+}
+
+impl Version {
+    /// The version `current` points to.
+    pub const CURRENT: Version = Version::V2026_10_19;
+
+    /// Versions the server still speaks over the websocket, oldest first.
+    /// Dropping one here makes clients built against it reload.
+    pub const SUPPORTED: &'static [Version] = &[Version::V2026_10_19];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Version::V2025_10_14 => "V2025_10_14",
+            Version::V2026_10_19 => "V2026_10_19",
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "V2025_10_14" => Ok(Version::V2025_10_14),
+            "V2026_10_19" => Ok(Version::V2026_10_19),
+            _ => Err(format!("Unknown version {s}.")),
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
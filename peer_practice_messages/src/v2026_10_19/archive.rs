@@ -0,0 +1,27 @@
+use super::post::Post;
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a post left the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Removal {
+    /// Deleted by this user; restorable until the retention window ends.
+    Deleted(UserId),
+    /// Its date passed; kept to look back at past practice evenings.
+    Expired,
+}
+
+/// A post that is no longer on the board.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedPost {
+    pub post: Post,
+    pub removed_at: DateTime<Utc>,
+    pub removal: Removal,
+}
+
+impl ArchivedPost {
+    pub fn is_deleted(&self) -> bool {
+        matches!(self.removal, Removal::Deleted(_))
+    }
+}
@@ -0,0 +1,2 @@
+pub mod login_data;
+pub mod method;
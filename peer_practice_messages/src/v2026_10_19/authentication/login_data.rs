@@ -0,0 +1,20 @@
+use super::super::authentication::method::AuthenticationMethod;
+use super::super::email::Email;
+use super::super::language::Language;
+use super::super::user::UserId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginData {
+    pub email: Email,
+    pub auth: AuthenticationMethod,
+    /// Language of the browser, for the login email of users without one.
+    #[serde(default)]
+    pub language: Option<Language>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PinLogin {
+    pub pin: String,
+    pub id: UserId,
+}
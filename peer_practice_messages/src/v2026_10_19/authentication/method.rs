@@ -0,0 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AuthenticationMethod {
+    EmailOTP,
+    Password(String),
+}
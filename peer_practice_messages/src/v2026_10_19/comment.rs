@@ -0,0 +1,140 @@
+use super::post::PostId;
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A markdown comment on a post, optionally replying to another comment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub post: PostId,
+    pub parent: Option<CommentId>,
+    pub author: UserId,
+    pub content: String,
+    pub created: DateTime<Utc>,
+    pub edited: Option<DateTime<Utc>>,
+}
+
+impl Comment {
+    pub const MAX_LENGTH: usize = 4000;
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CommentId {
+    id: Uuid,
+}
+
+impl std::fmt::Display for CommentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for CommentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommentId {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+}
+
+/// Orders the comments of `post` depth first, oldest first within each level,
+/// and pairs each with its reply depth.
+pub fn thread(post: PostId, comments: &HashMap<CommentId, Comment>) -> Vec<(CommentId, usize)> {
+    let mut children: HashMap<Option<CommentId>, Vec<(CommentId, &Comment)>> = HashMap::new();
+    for (id, comment) in comments.iter().filter(|(_, c)| c.post == post) {
+        // Replies whose parent is gone are shown at the top level.
+        let parent = comment
+            .parent
+            .filter(|parent| comments.contains_key(parent));
+        children.entry(parent).or_default().push((*id, comment));
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| {
+            a.1.created
+                .cmp(&b.1.created)
+                .then_with(|| a.0.id.cmp(&b.0.id))
+        });
+    }
+
+    let mut ordered = Vec::new();
+    let mut stack: Vec<_> = children
+        .get(&None)
+        .map(|roots| roots.iter().rev().map(|(id, _)| (*id, 0)).collect())
+        .unwrap_or_default();
+    while let Some((id, depth)) = stack.pop() {
+        ordered.push((id, depth));
+        if let Some(replies) = children.get(&Some(id)) {
+            stack.extend(replies.iter().rev().map(|(reply, _)| (*reply, depth + 1)));
+        }
+    }
+    ordered
+}
+
+/// `id` and every reply below it.
+pub fn with_replies(id: CommentId, comments: &HashMap<CommentId, Comment>) -> Vec<CommentId> {
+    let mut removed = vec![id];
+    let mut index = 0;
+    while index < removed.len() {
+        let parent = removed[index];
+        removed.extend(
+            comments
+                .iter()
+                .filter(|(_, comment)| comment.parent == Some(parent))
+                .map(|(reply, _)| *reply),
+        );
+        index += 1;
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn comment(post: PostId, parent: Option<CommentId>, minutes: i64) -> Comment {
+        Comment {
+            post,
+            parent,
+            author: UserId::new(),
+            content: String::new(),
+            created: DateTime::<Utc>::UNIX_EPOCH + Duration::minutes(minutes),
+            edited: None,
+        }
+    }
+
+    #[test]
+    fn threads_replies_below_their_parent() {
+        let post = PostId::new();
+        let (first, second, reply, nested) = (
+            CommentId::new(),
+            CommentId::new(),
+            CommentId::new(),
+            CommentId::new(),
+        );
+        let comments = HashMap::from([
+            (second, comment(post, None, 2)),
+            (first, comment(post, None, 1)),
+            (reply, comment(post, Some(first), 3)),
+            (nested, comment(post, Some(reply), 4)),
+            (CommentId::new(), comment(PostId::new(), None, 0)),
+        ]);
+
+        assert_eq!(
+            thread(post, &comments),
+            vec![(first, 0), (reply, 1), (nested, 2), (second, 0)]
+        );
+
+        let mut removed = with_replies(first, &comments);
+        removed.sort_by_key(|id| id.to_string());
+        let mut expected = vec![first, reply, nested];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(removed, expected);
+    }
+}
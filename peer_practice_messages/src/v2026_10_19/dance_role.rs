@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DanceRole {
+    Lead,
+    Follow,
+    #[default]
+    Either,
+}
+
+impl DanceRole {
+    pub const fn all() -> &'static [DanceRole] {
+        &[DanceRole::Lead, DanceRole::Follow, DanceRole::Either]
+    }
+    pub const ALL: &'static [DanceRole] = Self::all();
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DanceRole::Lead => "Lead",
+            DanceRole::Follow => "Follow",
+            DanceRole::Either => "Either",
+        }
+    }
+}
+
+impl std::fmt::Display for DanceRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for DanceRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Lead" => Ok(DanceRole::Lead),
+            "Follow" => Ok(DanceRole::Follow),
+            "Either" => Ok(DanceRole::Either),
+            _ => Err(format!("Unknown dance role {s}.")),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A private message from one user to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DirectMessage {
+    pub from: UserId,
+    pub to: UserId,
+    pub content: String,
+    pub sent: DateTime<Utc>,
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl DirectMessage {
+    pub const MAX_LENGTH: usize = 4000;
+
+    /// The other participant of the conversation, if `user` takes part in it.
+    pub fn partner_of(&self, user: UserId) -> Option<UserId> {
+        if self.from == user {
+            Some(self.to)
+        } else if self.to == user {
+            Some(self.from)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DirectMessageId {
+    id: Uuid,
+}
+
+impl std::fmt::Display for DirectMessageId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for DirectMessageId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectMessageId {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+}
+
+/// One entry of a user's inbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversation {
+    pub partner: UserId,
+    pub last: DirectMessageId,
+    pub unread: usize,
+}
+
+/// The conversations `user` takes part in, most recent first.
+pub fn conversations(
+    user: UserId,
+    messages: &HashMap<DirectMessageId, DirectMessage>,
+) -> Vec<Conversation> {
+    let mut by_partner: HashMap<UserId, (Conversation, DateTime<Utc>)> = HashMap::new();
+    for (id, message) in messages {
+        let Some(partner) = message.partner_of(user) else {
+            continue;
+        };
+        let unread = usize::from(message.to == user && !message.read);
+        let (conversation, latest) = by_partner.entry(partner).or_insert((
+            Conversation {
+                partner,
+                last: *id,
+                unread: 0,
+            },
+            message.sent,
+        ));
+        conversation.unread += unread;
+        if message.sent > *latest {
+            conversation.last = *id;
+            *latest = message.sent;
+        }
+    }
+
+    let mut conversations = by_partner.into_values().collect::<Vec<_>>();
+    conversations.sort_by_key(|(_, latest)| std::cmp::Reverse(*latest));
+    conversations
+        .into_iter()
+        .map(|(conversation, _)| conversation)
+        .collect()
+}
+
+/// Messages `user` has received but not read yet.
+pub fn unread_count(user: UserId, messages: &HashMap<DirectMessageId, DirectMessage>) -> usize {
+    messages
+        .values()
+        .filter(|message| message.to == user && !message.read)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn message(from: UserId, to: UserId, minutes: i64, read: bool) -> DirectMessage {
+        DirectMessage {
+            from,
+            to,
+            content: String::new(),
+            sent: DateTime::<Utc>::UNIX_EPOCH + Duration::minutes(minutes),
+            read,
+        }
+    }
+
+    #[test]
+    fn conversations_are_grouped_by_partner() {
+        let (me, anna, ben) = (UserId::new(), UserId::new(), UserId::new());
+        let latest_with_anna = DirectMessageId::new();
+        let messages = HashMap::from([
+            (DirectMessageId::new(), message(anna, me, 1, true)),
+            (latest_with_anna, message(me, anna, 5, false)),
+            (DirectMessageId::new(), message(ben, me, 3, false)),
+            (DirectMessageId::new(), message(ben, me, 4, false)),
+            (DirectMessageId::new(), message(anna, ben, 9, false)),
+        ]);
+
+        let inbox = conversations(me, &messages);
+
+        assert_eq!(inbox.len(), 2);
+        assert_eq!(
+            inbox[0],
+            Conversation {
+                partner: anna,
+                last: latest_with_anna,
+                unread: 0,
+            }
+        );
+        assert_eq!((inbox[1].partner, inbox[1].unread), (ben, 2));
+        assert_eq!(unread_count(me, &messages), 2);
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+#[derive(Hash, Eq, PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct Email {
+    #[cfg(target_arch = "wasm32")]
+    value: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    value: lettre::message::Mailbox,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Email {
+    pub fn new(value: &str) -> Option<Email> {
+        use lettre::message::Mailbox;
+        match value.parse::<Mailbox>() {
+            Ok(mailbox) => Some(mailbox.into()),
+            Err(_) => None,
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.value.email.to_string()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod all {
+    use crate::v2026_10_19::email::Email;
+    use lettre::message::Mailbox;
+
+    impl From<Email> for Mailbox {
+        fn from(value: Email) -> Self {
+            value.value().parse::<Mailbox>().unwrap()
+        }
+    }
+
+    impl From<Mailbox> for Email {
+        fn from(value: Mailbox) -> Self {
+            Email { value }
+        }
+    }
+}
+#[cfg(target_arch = "wasm32")]
+impl Email {
+    pub fn new(value: &str) -> Option<Email> {
+        use regex::Regex;
+        let r = Regex::new(
+            r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$",
+        ).unwrap();
+        if r.is_match(value) {
+            Some(Email {
+                value: value.into(),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn value(&self) -> String {
+        self.value.to_string()
+    }
+}
+
+/// An email in the server's delivery queue.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryId {
+    id: uuid::Uuid,
+}
+
+impl std::fmt::Display for DeliveryId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for DeliveryId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeliveryId {
+    pub fn new() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+        }
+    }
+}
+
+/// An email that could not be delivered on the first attempt, shown to
+/// organizers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndeliveredEmail {
+    pub id: DeliveryId,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub attempts: u32,
+    pub last_error: String,
+    pub status: DeliveryStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Retrying {
+        next_attempt: chrono::DateTime<chrono::Utc>,
+    },
+    /// Given up on, kept in the dead-letter log.
+    Failed { at: chrono::DateTime<chrono::Utc> },
+}
@@ -0,0 +1,166 @@
+use super::convert_utc_to_local_date;
+use super::dance_role::DanceRole;
+use super::post::{Post, PostId};
+use super::topic::{Topic, TopicId, topic_names};
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A post as it was saved by its owner at some point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostVersion {
+    pub at: DateTime<Utc>,
+    pub by: UserId,
+    pub post: Post,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Created,
+    Edited,
+    Joined(DanceRole),
+    Waitlisted(DanceRole),
+    Left,
+    /// The user moved up from the waitlist after someone left.
+    Promoted(UserId),
+    Deleted,
+    Expired,
+    Restored,
+}
+
+/// One mutation of a post.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// `None` for changes the server made on its own.
+    pub actor: Option<UserId>,
+    pub post: PostId,
+    pub action: AuditAction,
+}
+
+/// A field of a post that differs between two versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineDiff {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// The fields that changed from `before` to `after`, except the content, which
+/// is better shown line by line with [`diff_lines`].
+pub fn changed_fields(
+    before: &Post,
+    after: &Post,
+    topics: &HashMap<TopicId, Topic>,
+) -> Vec<FieldChange> {
+    let limit = |capacity: Option<u32>| {
+        capacity
+            .map(|capacity| capacity.to_string())
+            .unwrap_or_else(|| "unlimited".to_string())
+    };
+    let targets = |post: &Post| {
+        post.role_targets
+            .map(|targets| format!("{} leads, {} follows", targets.leads, targets.follows))
+            .unwrap_or_else(|| "none".to_string())
+    };
+    [
+        (
+            "Topics",
+            topic_names(&before.topics, topics),
+            topic_names(&after.topics, topics),
+        ),
+        (
+            "Date",
+            convert_utc_to_local_date(before.date).to_string(),
+            convert_utc_to_local_date(after.date).to_string(),
+        ),
+        ("Level", before.level.to_string(), after.level.to_string()),
+        ("Capacity", limit(before.capacity), limit(after.capacity)),
+        ("Role targets", targets(before), targets(after)),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before != after)
+    .map(|(field, before, after)| FieldChange {
+        field,
+        before,
+        after,
+    })
+    .collect()
+}
+
+/// Line based diff of two texts, using their longest common subsequence.
+pub fn diff_lines(before: &str, after: &str) -> Vec<LineDiff> {
+    let old = before.lines().collect::<Vec<_>>();
+    let new = after.lines().collect::<Vec<_>>();
+
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(LineDiff::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            diff.push(LineDiff::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(LineDiff::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(
+        old[i..]
+            .iter()
+            .map(|line| LineDiff::Removed(line.to_string())),
+    );
+    diff.extend(
+        new[j..]
+            .iter()
+            .map(|line| LineDiff::Added(line.to_string())),
+    );
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_lines_by_common_subsequence() {
+        let diff = diff_lines(
+            "Sugar push\nWhip\nAnchor",
+            "Sugar push\nTuck turn\nAnchor\nStyling",
+        );
+
+        assert_eq!(
+            diff,
+            vec![
+                LineDiff::Same("Sugar push".to_string()),
+                LineDiff::Removed("Whip".to_string()),
+                LineDiff::Added("Tuck turn".to_string()),
+                LineDiff::Same("Anchor".to_string()),
+                LineDiff::Added("Styling".to_string()),
+            ]
+        );
+    }
+}
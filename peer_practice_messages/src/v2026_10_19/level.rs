@@ -0,0 +1,197 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A skill level by name, as configured for the deployment.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Level(String);
+
+impl Level {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The levels of a deployment, ordered from lowest to highest.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Levels(Vec<Level>);
+
+impl Levels {
+    pub fn new(levels: Vec<Level>) -> Self {
+        Self(levels)
+    }
+
+    pub fn all(&self) -> &[Level] {
+        &self.0
+    }
+
+    pub fn rank(&self, level: &Level) -> Option<usize> {
+        self.0.iter().position(|known| known == level)
+    }
+
+    /// Orders the bounds of `range`, `None` if either bound is not a known level.
+    pub fn normalize(&self, range: LevelRange) -> Option<LevelRange> {
+        let from = self.rank(&range.from)?;
+        let to = self.rank(&range.to)?;
+        Some(if from <= to {
+            range
+        } else {
+            LevelRange {
+                from: range.to,
+                to: range.from,
+            }
+        })
+    }
+
+    /// Whether `level` lies within `range`, inclusive on both ends.
+    pub fn contains(&self, range: &LevelRange, level: &Level) -> bool {
+        match (
+            self.rank(&range.from),
+            self.rank(&range.to),
+            self.rank(level),
+        ) {
+            (Some(from), Some(to), Some(level)) => from <= level && level <= to,
+            _ => range.from == *level || range.to == *level,
+        }
+    }
+}
+
+/// The levels the fixed `Level` enum of older versions offered.
+impl Default for Levels {
+    fn default() -> Self {
+        Self(
+            LegacyLevel::ALL
+                .iter()
+                .map(|level| Level::new(level.name()))
+                .collect(),
+        )
+    }
+}
+
+/// Levels a post is meant for, e.g. "Novice–Intermediate".
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelRange {
+    pub from: Level,
+    pub to: Level,
+}
+
+impl LevelRange {
+    pub fn single(level: Level) -> Self {
+        Self {
+            from: level.clone(),
+            to: level,
+        }
+    }
+}
+
+impl std::fmt::Display for LevelRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)
+        } else {
+            write!(f, "{}–{}", self.from, self.to)
+        }
+    }
+}
+
+/// The fixed levels posts used before levels became configurable.
+#[derive(Clone, Copy, Debug, Deserialize)]
+enum LegacyLevel {
+    Beginner1,
+    Beginner2,
+    Beginner3,
+    Club,
+}
+
+impl LegacyLevel {
+    const ALL: &'static [LegacyLevel] = &[
+        LegacyLevel::Beginner1,
+        LegacyLevel::Beginner2,
+        LegacyLevel::Beginner3,
+        LegacyLevel::Club,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            LegacyLevel::Beginner1 => "Level 1",
+            LegacyLevel::Beginner2 => "Level 2",
+            LegacyLevel::Beginner3 => "Level 3",
+            LegacyLevel::Club => "Club",
+        }
+    }
+}
+
+/// Reads a post's level range, accepting the single legacy level stored by older versions.
+pub(crate) fn deserialize_level_range<'de, D>(deserializer: D) -> Result<LevelRange, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Range(LevelRange),
+        Legacy(LegacyLevel),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Range(range) => range,
+        Stored::Legacy(level) => LevelRange::single(Level::new(level.name())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wcs() -> Levels {
+        Levels::new(
+            ["Newcomer", "Novice", "Intermediate", "Advanced", "All-Star"]
+                .into_iter()
+                .map(Level::new)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn ranges_are_ordered_and_inclusive() {
+        let levels = wcs();
+        let range = levels
+            .normalize(LevelRange {
+                from: Level::new("Intermediate"),
+                to: Level::new("Novice"),
+            })
+            .unwrap();
+
+        assert_eq!(range.to_string(), "Novice–Intermediate");
+        assert!(levels.contains(&range, &Level::new("Novice")));
+        assert!(levels.contains(&range, &Level::new("Intermediate")));
+        assert!(!levels.contains(&range, &Level::new("Advanced")));
+        assert!(
+            levels
+                .normalize(LevelRange::single(Level::new("Pro")))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn legacy_level_migrates_to_single_level_range() {
+        #[derive(Deserialize)]
+        struct Stored {
+            #[serde(deserialize_with = "deserialize_level_range")]
+            level: LevelRange,
+        }
+
+        let stored = serde_json::from_str::<Stored>(r#"{"level":"Beginner2"}"#).unwrap();
+
+        assert_eq!(stored.level, LevelRange::single(Level::new("Level 2")));
+        assert_eq!(Levels::default().rank(&stored.level.from), Some(1));
+    }
+}
@@ -0,0 +1,134 @@
+use super::archive::ArchivedPost;
+use super::comment::{Comment, CommentId};
+use super::dance_role::DanceRole;
+use super::direct_message::{DirectMessage, DirectMessageId};
+use super::email::{DeliveryId, UndeliveredEmail};
+use super::history::{AuditEntry, PostVersion};
+use super::level::Levels;
+use super::notification::{Notification, NotificationId, PushSubscription};
+use super::post::{Post, PostId};
+use super::reaction::{Reaction, ReactionCounts};
+use super::request::{ErrorCode, RequestId};
+use super::rotation::{RotationSchedule, RotationSettings};
+use super::search::PostQuery;
+use super::stats::PracticeStats;
+use super::sync::{PostsSync, SyncCursor};
+use super::topic::{Topic, TopicId};
+use super::user::UserId;
+use super::user::calendar_token::CalendarToken;
+use super::user::display_user::UserDisplay;
+use super::user::user_config::UserConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerToClient {
+    User(UserId, UserDisplay),
+    Post(PostId, Post),
+    RemovedPost(PostId),
+    YouAre(UserId),
+    CalendarToken(CalendarToken),
+    UserConfig(UserConfig),
+    RotationSchedule(RotationSchedule),
+    Topic(TopicId, Topic),
+    /// Whether the connected user may manage topics.
+    Organizer(bool),
+    /// The levels configured for this deployment, sent after connecting.
+    Levels(Levels),
+    Comment(CommentId, Comment),
+    RemovedComment(CommentId),
+    /// How many comments posts have, sent to everyone whenever they change.
+    CommentCounts(Vec<(PostId, usize)>),
+    /// A message sent or received by the connected user, also after it was read.
+    DirectMessage(DirectMessageId, DirectMessage),
+    /// Reaction counts of a post, sent to everyone whenever they change.
+    Reactions(PostId, ReactionCounts),
+    /// The reactions the connected user gave a post.
+    MyReactions(PostId, Vec<Reaction>),
+    /// Posts matching the last `SearchPosts`, best match first.
+    SearchResults(PostQuery, Vec<PostId>),
+    PostsSync(PostsSync),
+    /// Saved versions of a post, oldest first.
+    PostHistory(PostId, Vec<PostVersion>),
+    /// Recent mutations of all posts, newest first, only sent to organizers.
+    AuditLog(Vec<AuditEntry>),
+    /// Expired posts and the deleted posts the connected user may restore.
+    Archive(Vec<(PostId, ArchivedPost)>),
+    PracticeStats(PracticeStats),
+    Notification(NotificationId, Notification),
+    /// Application server key the browser subscribes to Web Push with.
+    VapidPublicKey(String),
+    /// Emails waiting for a retry and the dead-letter log, newest first, only
+    /// sent to organizers.
+    UndeliveredEmails(Vec<UndeliveredEmail>),
+    /// The request was carried out.
+    Ack(RequestId),
+    /// The request was not carried out.
+    Error {
+        request: RequestId,
+        code: ErrorCode,
+        message: String,
+    },
+}
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientToServer {
+    GetUser(UserId),
+    UpdateUser(UserDisplay),
+    GetPosts,
+    Join(PostId),
+    /// Joins with a dance role, or changes the role if already joined.
+    JoinAs(PostId, DanceRole),
+    Leave(PostId),
+    UpdatePost(PostId, Post),
+    NewPost(Post),
+    DeletePost(PostId),
+    GetCalendarToken,
+    RenewCalendarToken,
+    GetUserConfig,
+    UpdateUserConfig(UserConfig),
+    GetRotationSchedule(RotationSettings),
+    GetTopics,
+    /// Creates a topic, only accepted from organizers.
+    NewTopic(Topic),
+    /// Renames, recolors or (un)archives a topic, only accepted from organizers.
+    UpdateTopic(TopicId, Topic),
+    /// The comments on a post, loaded once its thread is opened.
+    GetComments(PostId),
+    /// How many comments each live post has.
+    GetCommentCounts,
+    /// Comments on a post, or replies to a comment when a parent is given.
+    NewComment(PostId, Option<CommentId>, String),
+    /// Replaces the content of a comment, only accepted from its author.
+    EditComment(CommentId, String),
+    /// Removes a comment with its replies, accepted from its author and the post owner.
+    DeleteComment(CommentId),
+    /// All messages the connected user sent or received.
+    GetDirectMessages,
+    SendDirectMessage(UserId, String),
+    /// Marks every message received from this user as read.
+    MarkConversationRead(UserId),
+    /// Adds the reaction to a post, or takes it back if already given.
+    ToggleReaction(PostId, Reaction),
+    SearchPosts(PostQuery),
+    /// Asks for the changes to posts since the cursor, or for all posts without one.
+    SyncPosts(Option<SyncCursor>),
+    GetPostHistory(PostId),
+    /// Only answered for organizers.
+    GetAuditLog,
+    GetArchive,
+    /// Brings back a deleted post, accepted from whoever deleted it and organizers.
+    RestorePost(PostId),
+    /// Statistics over the past sessions of the connected user.
+    GetPracticeStats,
+    /// All notifications of the connected user.
+    GetNotifications,
+    MarkNotificationsRead,
+    GetVapidPublicKey,
+    /// Sends Web Push notifications to this browser.
+    SubscribePush(PushSubscription),
+    /// Stops Web Push to the subscription with this endpoint.
+    UnsubscribePush(String),
+    /// Only answered for organizers.
+    GetUndeliveredEmails,
+    /// Queues a failed email again, only accepted from organizers.
+    RetryEmail(DeliveryId),
+}
@@ -0,0 +1,168 @@
+use super::convert_utc_to_local_date;
+use super::post::PostId;
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Something that happened to a post the recipient cares about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+    /// Someone joined a post of the recipient.
+    Joined(UserId),
+    /// A post the recipient joined moved to another date.
+    Rescheduled { from: DateTime<Utc> },
+    /// A post the recipient joined was deleted.
+    Deleted,
+    /// A new post suits the recipient's level.
+    NewPostForLevel,
+    /// A post the recipient joined starts soon.
+    Reminder,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub to: UserId,
+    pub at: DateTime<Utc>,
+    pub post: PostId,
+    /// Topics and date of the post when it happened, as the post may be gone.
+    pub summary: String,
+    pub kind: NotificationKind,
+    #[serde(default)]
+    pub read: bool,
+}
+
+impl Notification {
+    /// One line describing what happened, naming users with `name`.
+    pub fn text(&self, name: impl Fn(UserId) -> String) -> String {
+        match &self.kind {
+            NotificationKind::Joined(user) => format!("{} joined {}", name(*user), self.summary),
+            NotificationKind::Rescheduled { from } => format!(
+                "{} was moved from {}",
+                self.summary,
+                convert_utc_to_local_date(*from)
+            ),
+            NotificationKind::Deleted => format!("{} was deleted", self.summary),
+            NotificationKind::NewPostForLevel => {
+                format!("New post for your level: {}", self.summary)
+            }
+            NotificationKind::Reminder => format!("Reminder: {} is coming up", self.summary),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NotificationId {
+    id: Uuid,
+}
+
+impl std::fmt::Display for NotificationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for NotificationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationId {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+}
+
+/// Where notifications of one kind go.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Channels {
+    #[serde(default)]
+    pub in_app: bool,
+    #[serde(default)]
+    pub email: bool,
+    /// Web Push to every device the user subscribed.
+    #[serde(default)]
+    pub push: bool,
+}
+
+impl Channels {
+    pub const IN_APP: Self = Self {
+        in_app: true,
+        email: false,
+        push: false,
+    };
+
+    /// Whether notifications of this kind go anywhere.
+    pub fn any(&self) -> bool {
+        self.in_app || self.email || self.push
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    #[serde(default = "in_app")]
+    pub joined_my_post: Channels,
+    #[serde(default = "in_app")]
+    pub post_changed: Channels,
+    #[serde(default = "in_app")]
+    pub post_deleted: Channels,
+    #[serde(default)]
+    pub new_post_for_level: Channels,
+    #[serde(default)]
+    pub reminder: Channels,
+    /// How long before a joined practice starts the reminder goes out.
+    #[serde(default = "reminder_hours_before")]
+    pub reminder_hours_before: u32,
+}
+
+fn in_app() -> Channels {
+    Channels::IN_APP
+}
+
+fn reminder_hours_before() -> u32 {
+    24
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            joined_my_post: Channels::IN_APP,
+            post_changed: Channels::IN_APP,
+            post_deleted: Channels::IN_APP,
+            new_post_for_level: Channels::default(),
+            reminder: Channels::default(),
+            reminder_hours_before: reminder_hours_before(),
+        }
+    }
+}
+
+impl NotificationPreferences {
+    /// Reminders go out at most a week ahead.
+    pub const REMINDER_HOURS_BEFORE: std::ops::RangeInclusive<u32> = 1..=168;
+
+    pub fn channels(&self, kind: &NotificationKind) -> Channels {
+        match kind {
+            NotificationKind::Joined(_) => self.joined_my_post,
+            NotificationKind::Rescheduled { .. } => self.post_changed,
+            NotificationKind::Deleted => self.post_deleted,
+            NotificationKind::NewPostForLevel => self.new_post_for_level,
+            NotificationKind::Reminder => self.reminder,
+        }
+    }
+}
+
+/// A browser's Web Push subscription, as returned by `PushSubscription.toJSON()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushSubscription {
+    pub endpoint: String,
+    pub keys: PushKeys,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushKeys {
+    /// The browser's P-256 public key, base64url encoded.
+    pub p256dh: String,
+    /// The shared authentication secret, base64url encoded.
+    pub auth: String,
+}
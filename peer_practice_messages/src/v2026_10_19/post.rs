@@ -0,0 +1,195 @@
+use super::dance_role::DanceRole;
+use super::level::{LevelRange, deserialize_level_range};
+use super::topic::{Topic, TopicId, deserialize_topic_ids, topic_names};
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+pub use roles::{Pairing, RoleCounts, RoleTargets};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+pub mod roles;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Post {
+    /// Older versions stored a single fixed topic as `title`.
+    #[serde(alias = "title", deserialize_with = "deserialize_topic_ids")]
+    pub topics: Vec<TopicId>,
+    pub content: String,
+    /// Older versions stored a single fixed level.
+    #[serde(deserialize_with = "deserialize_level_range")]
+    pub level: LevelRange,
+    pub owner: UserId,
+    pub date: DateTime<Utc>,
+    pub partaking_users: HashSet<UserId>,
+    /// Maximum number of partaking users including the owner, unlimited if `None`.
+    #[serde(default)]
+    pub capacity: Option<u32>,
+    /// Users waiting for a free spot, in order of arrival.
+    #[serde(default)]
+    pub waitlist: Vec<UserId>,
+    /// Role of each joined or waiting user, users without an entry dance either role.
+    #[serde(default)]
+    pub roles: HashMap<UserId, DanceRole>,
+    #[serde(default)]
+    pub role_targets: Option<RoleTargets>,
+}
+
+impl Post {
+    /// A post by `owner` without topics or a limit, for tests to adjust.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn sample(owner: UserId, date: DateTime<Utc>) -> Self {
+        Self {
+            topics: Vec::new(),
+            content: String::new(),
+            level: LevelRange::single(super::level::Level::new("Level 1")),
+            owner,
+            date,
+            partaking_users: HashSet::from([owner]),
+            capacity: None,
+            waitlist: Vec::new(),
+            roles: HashMap::new(),
+            role_targets: None,
+        }
+    }
+
+    /// Names of the post's topics, used wherever the post needs a heading.
+    pub fn title(&self, topics: &HashMap<TopicId, Topic>) -> String {
+        topic_names(&self.topics, topics)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.partaking_users.len() >= capacity as usize)
+    }
+
+    pub fn is_waiting(&self, user: &UserId) -> bool {
+        self.waitlist.contains(user)
+    }
+
+    /// Adds `user` to the participants, or to the waitlist if the post is full.
+    /// Returns whether `user` is partaking afterwards.
+    pub fn join(&mut self, user: UserId) -> bool {
+        if self.partaking_users.contains(&user) {
+            return true;
+        }
+        if self.is_full() {
+            if !self.is_waiting(&user) {
+                self.waitlist.push(user);
+            }
+            false
+        } else {
+            self.waitlist.retain(|waiting| *waiting != user);
+            self.partaking_users.insert(user);
+            true
+        }
+    }
+
+    /// Removes `user` from the participants and the waitlist and returns the
+    /// users promoted from the waitlist into the freed spots.
+    pub fn leave(&mut self, user: &UserId) -> Vec<UserId> {
+        self.roles.remove(user);
+        self.waitlist.retain(|waiting| waiting != user);
+        if self.partaking_users.remove(user) {
+            self.promote_waitlist()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Moves waiting users into free spots, e.g. after the capacity was raised.
+    pub fn promote_waitlist(&mut self) -> Vec<UserId> {
+        let mut promoted = Vec::new();
+        while !self.is_full() && !self.waitlist.is_empty() {
+            let user = self.waitlist.remove(0);
+            self.partaking_users.insert(user);
+            promoted.push(user);
+        }
+        promoted
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PostId {
+    id: Uuid,
+}
+
+impl std::fmt::Display for PostId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for PostId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostId {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+
+    pub const NULL: Self = Self { id: Uuid::nil() };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v2026_10_19::topic::LegacyTopic;
+
+    fn post_with_capacity(capacity: u32) -> (Post, UserId) {
+        let owner = UserId::new();
+        let mut post = Post::sample(owner, Utc::now());
+        post.capacity = Some(capacity);
+        (post, owner)
+    }
+
+    #[test]
+    fn joins_beyond_capacity_are_waitlisted() {
+        let (mut post, _owner) = post_with_capacity(2);
+        let partner = UserId::new();
+        let late = UserId::new();
+
+        assert!(post.join(partner));
+        assert!(!post.join(late));
+        assert!(!post.join(late));
+        assert_eq!(post.partaking_users.len(), 2);
+        assert_eq!(post.waitlist, vec![late]);
+    }
+
+    #[test]
+    fn leaving_promotes_first_waiting_user() {
+        let (mut post, _owner) = post_with_capacity(2);
+        let partner = UserId::new();
+        let first = UserId::new();
+        let second = UserId::new();
+        post.join(partner);
+        post.join(first);
+        post.join(second);
+
+        assert_eq!(post.leave(&partner), vec![first]);
+        assert!(post.partaking_users.contains(&first));
+        assert_eq!(post.waitlist, vec![second]);
+        assert!(post.leave(&second).is_empty());
+        assert!(post.waitlist.is_empty());
+    }
+
+    #[test]
+    fn legacy_title_migrates_to_topic_id() {
+        let (post, _owner) = post_with_capacity(2);
+        let mut stored = serde_json::to_value(&post).unwrap();
+        let fields = stored.as_object_mut().unwrap();
+        fields.remove("topics");
+        fields.insert("title".to_string(), "Spins".into());
+
+        let migrated = serde_json::from_value::<Post>(stored).unwrap();
+
+        assert_eq!(migrated.topics, vec![TopicId::from(LegacyTopic::Spins)]);
+        assert_eq!(
+            migrated.title(&crate::v2026_10_19::topic::legacy_topics()),
+            "Spins"
+        );
+    }
+}
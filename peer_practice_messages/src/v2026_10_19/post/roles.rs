@@ -0,0 +1,146 @@
+use super::Post;
+use crate::v2026_10_19::dance_role::DanceRole;
+use crate::v2026_10_19::user::UserId;
+use serde::{Deserialize, Serialize};
+
+/// Number of leads and follows a post owner would like to have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleTargets {
+    pub leads: u32,
+    pub follows: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoleCounts {
+    pub leads: usize,
+    pub follows: usize,
+    pub either: usize,
+}
+
+/// Partners for one round of a rotation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pairing {
+    /// `(lead, follow)` pairs.
+    pub pairs: Vec<(UserId, UserId)>,
+    pub sitting_out: Vec<UserId>,
+}
+
+impl Post {
+    pub fn role_of(&self, user: &UserId) -> DanceRole {
+        self.roles.get(user).copied().unwrap_or_default()
+    }
+
+    pub fn role_counts(&self) -> RoleCounts {
+        let mut counts = RoleCounts::default();
+        for user in &self.partaking_users {
+            match self.role_of(user) {
+                DanceRole::Lead => counts.leads += 1,
+                DanceRole::Follow => counts.follows += 1,
+                DanceRole::Either => counts.either += 1,
+            }
+        }
+        counts
+    }
+
+    /// Splits the partaking users into leads and follows, assigning users who
+    /// dance either role to whichever side is shorter.
+    pub fn balanced_roles(&self) -> (Vec<UserId>, Vec<UserId>) {
+        let mut users = self.partaking_users.iter().copied().collect::<Vec<_>>();
+        users.sort();
+        balance(users.into_iter().map(|user| (user, self.role_of(&user))))
+    }
+
+    /// Pairs leads with follows for the given round; each round shifts the
+    /// follows by one so that consecutive rounds produce new partners.
+    pub fn rotation(&self, round: usize) -> Pairing {
+        let (leads, follows) = self.balanced_roles();
+        rotate(&leads, &follows, round)
+    }
+}
+
+pub fn balance(users: impl IntoIterator<Item = (UserId, DanceRole)>) -> (Vec<UserId>, Vec<UserId>) {
+    let mut leads = Vec::new();
+    let mut follows = Vec::new();
+    let mut either = Vec::new();
+    for (user, role) in users {
+        match role {
+            DanceRole::Lead => leads.push(user),
+            DanceRole::Follow => follows.push(user),
+            DanceRole::Either => either.push(user),
+        }
+    }
+    for user in either {
+        if leads.len() <= follows.len() {
+            leads.push(user);
+        } else {
+            follows.push(user);
+        }
+    }
+    (leads, follows)
+}
+
+pub fn rotate(leads: &[UserId], follows: &[UserId], round: usize) -> Pairing {
+    let leads_are_shorter = leads.len() <= follows.len();
+    let (short, long) = if leads_are_shorter {
+        (leads, follows)
+    } else {
+        (follows, leads)
+    };
+    if short.is_empty() {
+        return Pairing {
+            pairs: Vec::new(),
+            sitting_out: long.to_vec(),
+        };
+    }
+
+    let offset = round % long.len();
+    let rotated = long[offset..].iter().chain(&long[..offset]).copied();
+    let mut pairs = Vec::with_capacity(short.len());
+    let mut sitting_out = Vec::new();
+    for (index, partner) in rotated.enumerate() {
+        match short.get(index) {
+            Some(user) if leads_are_shorter => pairs.push((*user, partner)),
+            Some(user) => pairs.push((partner, *user)),
+            None => sitting_out.push(partner),
+        }
+    }
+    Pairing { pairs, sitting_out }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn either_fills_the_shorter_side() {
+        let lead = UserId::new();
+        let follows = [UserId::new(), UserId::new()];
+        let flexible = UserId::new();
+        let (leads, follows) = balance([
+            (lead, DanceRole::Lead),
+            (follows[0], DanceRole::Follow),
+            (follows[1], DanceRole::Follow),
+            (flexible, DanceRole::Either),
+        ]);
+
+        assert_eq!(leads, vec![lead, flexible]);
+        assert_eq!(follows.len(), 2);
+    }
+
+    #[test]
+    fn rounds_rotate_partners_without_repeats() {
+        let leads = [UserId::new(), UserId::new()];
+        let follows = [UserId::new(), UserId::new(), UserId::new()];
+
+        let mut seen = HashSet::new();
+        for round in 0..follows.len() {
+            let pairing = rotate(&leads, &follows, round);
+            assert_eq!(pairing.pairs.len(), 2);
+            assert_eq!(pairing.sitting_out.len(), 1);
+            for pair in pairing.pairs {
+                assert!(seen.insert(pair), "pair {pair:?} repeated in round {round}");
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::user::UserId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A lightweight signal on a post that does not commit to joining it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Reaction {
+    Interested,
+    Like,
+    Love,
+    Fire,
+    Laugh,
+}
+
+impl Reaction {
+    pub const ALL: &'static [Reaction] = &[
+        Reaction::Interested,
+        Reaction::Like,
+        Reaction::Love,
+        Reaction::Fire,
+        Reaction::Laugh,
+    ];
+
+    pub const fn emoji(self) -> &'static str {
+        match self {
+            Reaction::Interested => "🙋",
+            Reaction::Like => "👍",
+            Reaction::Love => "❤️",
+            Reaction::Fire => "🔥",
+            Reaction::Laugh => "😂",
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Reaction::Interested => "Interested",
+            Reaction::Like => "Like",
+            Reaction::Love => "Love",
+            Reaction::Fire => "Fire",
+            Reaction::Laugh => "Laugh",
+        }
+    }
+}
+
+/// How many users reacted with each reaction, without revealing who.
+pub type ReactionCounts = BTreeMap<Reaction, usize>;
+
+/// Who reacted how to a single post.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostReactions(HashMap<Reaction, HashSet<UserId>>);
+
+impl PostReactions {
+    /// Adds the reaction, or takes it back if the user already reacted that way.
+    /// Returns whether the user reacts that way afterwards.
+    pub fn toggle(&mut self, user: UserId, reaction: Reaction) -> bool {
+        let users = self.0.entry(reaction).or_default();
+        let reacted = users.insert(user) || !users.remove(&user);
+        if users.is_empty() {
+            self.0.remove(&reaction);
+        }
+        reacted
+    }
+
+    pub fn counts(&self) -> ReactionCounts {
+        self.0
+            .iter()
+            .map(|(reaction, users)| (*reaction, users.len()))
+            .collect()
+    }
+
+    /// The reactions of `user`, in display order.
+    pub fn of(&self, user: &UserId) -> Vec<Reaction> {
+        let mut reactions = self
+            .0
+            .iter()
+            .filter(|(_, users)| users.contains(user))
+            .map(|(reaction, _)| *reaction)
+            .collect::<Vec<_>>();
+        reactions.sort();
+        reactions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggling_twice_takes_the_reaction_back() {
+        let (anna, ben) = (UserId::new(), UserId::new());
+        let mut reactions = PostReactions::default();
+
+        assert!(reactions.toggle(anna, Reaction::Fire));
+        assert!(reactions.toggle(ben, Reaction::Fire));
+        assert!(reactions.toggle(anna, Reaction::Interested));
+        assert_eq!(
+            reactions.counts(),
+            ReactionCounts::from([(Reaction::Interested, 1), (Reaction::Fire, 2)])
+        );
+        assert_eq!(
+            reactions.of(&anna),
+            vec![Reaction::Interested, Reaction::Fire]
+        );
+
+        assert!(!reactions.toggle(anna, Reaction::Interested));
+        assert!(!reactions.toggle(anna, Reaction::Fire));
+        assert!(!reactions.toggle(ben, Reaction::Fire));
+        assert!(reactions.is_empty());
+    }
+}
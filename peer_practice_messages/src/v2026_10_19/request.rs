@@ -0,0 +1,89 @@
+use super::messages::ClientToServer;
+use serde::{Deserialize, Serialize};
+
+/// Chosen by the client to match the server's `Ack` or `Error` to a message.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RequestId(pub u64);
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A client message with the id its answer refers to.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClientRequest {
+    pub id: RequestId,
+    pub message: ClientToServer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// The message could not be read or its content is not acceptable.
+    Invalid,
+    NotFound,
+    /// The connected user may not do this.
+    Forbidden,
+    Internal,
+}
+
+/// Why the server did not carry out a client message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl RequestError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Invalid, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_wrap_the_message_with_its_id() {
+        let request = ClientRequest {
+            id: RequestId(7),
+            message: ClientToServer::GetTopics,
+        };
+
+        let json = serde_json::to_string(&request).unwrap();
+
+        assert_eq!(json, r#"{"id":7,"message":"GetTopics"}"#);
+        let parsed = serde_json::from_str::<ClientRequest>(&json).unwrap();
+        assert_eq!(parsed.id, RequestId(7));
+        assert!(matches!(parsed.message, ClientToServer::GetTopics));
+    }
+}
@@ -0,0 +1,51 @@
+use super::post::PostId;
+use super::topic::TopicId;
+use super::user::UserId;
+use super::ymd::practice_start;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Parameters for planning the rotation of one practice evening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationSettings {
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub round_minutes: u32,
+    pub rounds: u32,
+}
+
+impl RotationSettings {
+    pub const ROUNDS: RangeInclusive<u32> = 1..=12;
+    pub const ROUND_MINUTES: RangeInclusive<u32> = 5..=60;
+
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            start: practice_start(),
+            round_minutes: 15,
+            rounds: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationSchedule {
+    pub settings: RotationSettings,
+    pub rounds: Vec<RotationRound>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RotationRound {
+    pub start: NaiveTime,
+    pub pairs: Vec<PracticePair>,
+    pub sitting_out: Vec<UserId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PracticePair {
+    pub post: PostId,
+    pub topics: Vec<TopicId>,
+    pub lead: UserId,
+    pub follow: UserId,
+}
@@ -0,0 +1,29 @@
+use super::level::Level;
+use super::topic::TopicId;
+use super::user::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A search over posts, empty fields do not restrict the result.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostQuery {
+    /// Words to find in the ideas and topic names of a post.
+    pub text: String,
+    /// Only posts whose level range includes this level.
+    pub level: Option<Level>,
+    /// Only posts with at least one of these topics.
+    pub topics: Vec<TopicId>,
+    /// Only posts at or after this instant.
+    pub from: Option<DateTime<Utc>>,
+    /// Only posts before this instant.
+    pub until: Option<DateTime<Utc>>,
+    pub owner: Option<UserId>,
+    /// Only posts the searching user joined or waits for.
+    pub joined_by_me: bool,
+}
+
+impl PostQuery {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
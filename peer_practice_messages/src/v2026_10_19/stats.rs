@@ -0,0 +1,46 @@
+use super::post::PostId;
+use super::topic::TopicId;
+use super::user::UserId;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A past session the user took part in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PastSession {
+    pub post: PostId,
+    pub date: DateTime<Utc>,
+    pub topics: Vec<TopicId>,
+    pub hosted: bool,
+    pub participants: usize,
+}
+
+/// How often the user practiced with someone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartnerCount {
+    pub partner: UserId,
+    pub sessions: usize,
+}
+
+/// How often a topic was practiced in one month.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicMonth {
+    /// First day of the month.
+    pub month: NaiveDate,
+    pub topic: TopicId,
+    pub sessions: usize,
+}
+
+/// Personal statistics over the past sessions a user attended.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PracticeStats {
+    /// Newest first.
+    pub sessions: Vec<PastSession>,
+    pub hosted: usize,
+    /// Ordered by month, then by topic.
+    pub topics_by_month: Vec<TopicMonth>,
+    /// Most frequent partners first.
+    pub partners: Vec<PartnerCount>,
+    /// Weeks in a row with at least one session, up to this or last week.
+    pub current_streak_weeks: u32,
+    pub longest_streak_weeks: u32,
+}
@@ -0,0 +1,23 @@
+use super::post::{Post, PostId};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// How far a client has caught up with the posts on the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// Changes when the server loses its change log, revisions of another epoch are meaningless.
+    pub epoch: Uuid,
+    pub revision: u64,
+}
+
+/// One page of changes to posts, answering `ClientToServer::SyncPosts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostsSync {
+    /// The client's posts are outdated beyond repair and have to be replaced by
+    /// the posts of this and the following pages. Only set on the first page.
+    pub full_resync: bool,
+    pub posts: Vec<(PostId, Post)>,
+    pub removed: Vec<PostId>,
+    /// Set on the last page, to be sent with the next sync.
+    pub cursor: Option<SyncCursor>,
+}
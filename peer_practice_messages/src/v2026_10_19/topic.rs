@@ -0,0 +1,101 @@
+use super::accent_colors::AccentColor;
+pub use legacy::LegacyTopic;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+mod legacy;
+
+/// A practice topic managed by the organizers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Topic {
+    pub name: String,
+    pub color: AccentColor,
+    /// Archived topics still show on existing posts but cannot be picked for new ones.
+    #[serde(default)]
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TopicId {
+    id: Uuid,
+}
+
+impl std::fmt::Display for TopicId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.id)
+    }
+}
+
+impl Default for TopicId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TopicId {
+    pub fn new() -> Self {
+        Self { id: Uuid::new_v4() }
+    }
+}
+
+impl std::str::FromStr for TopicId {
+    type Err = uuid::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self { id: s.parse()? })
+    }
+}
+
+/// Legacy topics map to fixed ids so stored posts and the seeded topics agree.
+impl From<LegacyTopic> for TopicId {
+    fn from(topic: LegacyTopic) -> Self {
+        Self {
+            id: Uuid::from_u128(topic as u128 + 1),
+        }
+    }
+}
+
+/// Topics a fresh deployment starts with, one per legacy topic.
+pub fn legacy_topics() -> HashMap<TopicId, Topic> {
+    LegacyTopic::ALL
+        .iter()
+        .map(|topic| {
+            (
+                TopicId::from(*topic),
+                Topic {
+                    name: topic.name().to_string(),
+                    color: topic.color(),
+                    archived: false,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Joins the names of `ids`, skipping topics that are not known (yet).
+pub fn topic_names(ids: &[TopicId], topics: &HashMap<TopicId, Topic>) -> String {
+    ids.iter()
+        .filter_map(|id| topics.get(id))
+        .map(|topic| topic.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reads a post's topic ids, accepting the single legacy topic stored by older versions.
+pub(crate) fn deserialize_topic_ids<'de, D>(deserializer: D) -> Result<Vec<TopicId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Stored {
+        Ids(Vec<TopicId>),
+        Legacy(LegacyTopic),
+    }
+
+    Ok(match Stored::deserialize(deserializer)? {
+        Stored::Ids(ids) => ids,
+        Stored::Legacy(topic) => vec![topic.into()],
+    })
+}
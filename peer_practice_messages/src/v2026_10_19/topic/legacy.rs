@@ -0,0 +1,62 @@
+use crate::v2026_10_19::accent_colors::AccentColor;
+use serde::{Deserialize, Serialize};
+
+/// The fixed topics posts used before topics became data, kept to migrate stored posts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegacyTopic {
+    Basics,
+    Swing,
+    Spins,
+    Connection,
+    Timing,
+    RockAndGo,
+    Anchor,
+    FootWork,
+    Pattern,
+    Blues,
+}
+
+impl LegacyTopic {
+    pub const ALL: &'static [LegacyTopic] = &[
+        LegacyTopic::Basics,
+        LegacyTopic::Swing,
+        LegacyTopic::Spins,
+        LegacyTopic::Connection,
+        LegacyTopic::Timing,
+        LegacyTopic::RockAndGo,
+        LegacyTopic::Anchor,
+        LegacyTopic::FootWork,
+        LegacyTopic::Pattern,
+        LegacyTopic::Blues,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            LegacyTopic::Basics => "Basics",
+            LegacyTopic::Swing => "Swing",
+            LegacyTopic::Spins => "Spins",
+            LegacyTopic::Connection => "Connection",
+            LegacyTopic::Timing => "Timing",
+            LegacyTopic::RockAndGo => "Rock & Go",
+            LegacyTopic::Anchor => "Anchor",
+            LegacyTopic::FootWork => "Footwork",
+            LegacyTopic::Pattern => "Pattern",
+            LegacyTopic::Blues => "Blues",
+        }
+    }
+
+    pub const fn color(self) -> AccentColor {
+        match self {
+            LegacyTopic::Basics => AccentColor::Teal,
+            LegacyTopic::Swing => AccentColor::Peach,
+            LegacyTopic::Spins => AccentColor::Mauve,
+            LegacyTopic::Connection => AccentColor::Pink,
+            LegacyTopic::Timing => AccentColor::Yellow,
+            LegacyTopic::RockAndGo => AccentColor::Red,
+            LegacyTopic::Anchor => AccentColor::Blue,
+            LegacyTopic::FootWork => AccentColor::Green,
+            LegacyTopic::Pattern => AccentColor::Sapphire,
+            LegacyTopic::Blues => AccentColor::Lavender,
+        }
+    }
+}
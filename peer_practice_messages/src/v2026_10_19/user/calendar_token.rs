@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Secret used in the URL of a user's personal calendar feed.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CalendarToken {
+    token: Uuid,
+}
+
+impl Default for CalendarToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CalendarToken {
+    pub fn new() -> Self {
+        Self {
+            token: Uuid::new_v4(),
+        }
+    }
+}
+
+impl Display for CalendarToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.token.simple())
+    }
+}
+
+impl FromStr for CalendarToken {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(s)
+            .map(|token| Self { token })
+            .map_err(|_| ())
+    }
+}
@@ -0,0 +1,31 @@
+use super::super::level::Level;
+use super::super::user::{User, UserId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDisplay {
+    pub display_name: Option<String>,
+    pub id: UserId,
+    #[serde(default)]
+    pub level: Option<Level>,
+}
+
+impl From<User> for UserDisplay {
+    fn from(user: User) -> Self {
+        UserDisplay {
+            display_name: user.display_name,
+            id: user.id,
+            level: user.level,
+        }
+    }
+}
+
+impl From<&User> for UserDisplay {
+    fn from(user: &User) -> Self {
+        UserDisplay {
+            display_name: user.display_name.clone(),
+            id: user.id,
+            level: user.level.clone(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use super::email::Email;
+use super::level::Level;
+use calendar_token::CalendarToken;
+use serde::{Deserialize, Serialize};
+use user_config::UserConfig;
+use uuid::Uuid;
+
+pub mod calendar_token;
+pub mod display_user;
+pub mod user_config;
+mod user_id;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub email: Email,
+    pub display_name: Option<String>,
+    pub id: UserId,
+    #[serde(default)]
+    pub calendar_token: Option<CalendarToken>,
+    #[serde(default)]
+    pub config: UserConfig,
+    /// The user's own level, used to find posts that suit them.
+    #[serde(default)]
+    pub level: Option<Level>,
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct UserId {
+    id: Uuid,
+}
+impl Default for UserId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
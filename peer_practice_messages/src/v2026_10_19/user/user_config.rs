@@ -0,0 +1,19 @@
+use super::super::language::Language;
+use super::super::notification::NotificationPreferences;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserConfig {
+    /// Email a calendar invite when joining a post and a cancellation when leaving it.
+    #[serde(default)]
+    pub calendar_invites: bool,
+    /// Which notifications the user gets, and how.
+    #[serde(default)]
+    pub notifications: NotificationPreferences,
+    /// Email the posts of each practice evening a few days before it.
+    #[serde(default)]
+    pub practice_digest: bool,
+    /// Language of emails, `None` to follow the browser's.
+    #[serde(default)]
+    pub language: Option<Language>,
+}
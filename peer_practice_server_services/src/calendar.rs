@@ -0,0 +1,326 @@
+use chrono::{DateTime, Days, NaiveDate, TimeZone, Utc};
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::topic::{Topic, TopicId};
+use peer_practice_messages::current::user::UserId;
+use std::collections::HashMap;
+
+const PRODUCT_ID: &str = "-//peer_practice//calendar//EN";
+const UID_DOMAIN: &str = "peer-practice";
+const SESSION_SUMMARY: &str = "Peer Practice";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarMethod {
+    Publish,
+    Request,
+    Cancel,
+}
+
+impl CalendarMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalendarMethod::Publish => "PUBLISH",
+            CalendarMethod::Request => "REQUEST",
+            CalendarMethod::Cancel => "CANCEL",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: Option<String>,
+    pub sequence: u32,
+    pub cancelled: bool,
+    pub organizer: Option<String>,
+    pub attendees: Vec<String>,
+}
+
+impl CalendarEvent {
+    pub fn from_post(id: PostId, post: &Post, topics: &HashMap<TopicId, Topic>) -> Self {
+        Self {
+            uid: post_uid(id),
+            date: convert_utc_to_local_date(post.date),
+            summary: format!("{} ({})", post.title(topics), post.level),
+            description: (!post.content.trim().is_empty()).then(|| post.content.clone()),
+            sequence: 0,
+            cancelled: false,
+            organizer: None,
+            attendees: Vec::new(),
+        }
+    }
+
+    pub fn session(date: NaiveDate) -> Self {
+        Self {
+            uid: format!("session-{}@{UID_DOMAIN}", date.format("%Y%m%d")),
+            date,
+            summary: SESSION_SUMMARY.to_string(),
+            description: None,
+            sequence: 0,
+            cancelled: false,
+            organizer: None,
+            attendees: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    pub name: String,
+    pub method: CalendarMethod,
+    pub events: Vec<CalendarEvent>,
+}
+
+impl Calendar {
+    /// Feed with every upcoming session date. It needs no login, so it must
+    /// not tell anything about the posts.
+    pub fn public(session_dates: &[NaiveDate]) -> Self {
+        let events = session_dates.iter().copied().map(CalendarEvent::session);
+        Self::published(SESSION_SUMMARY.to_string(), events)
+    }
+
+    /// Feed with the posts `user` joined and every upcoming session date.
+    pub fn personal(
+        user: UserId,
+        posts: &[(PostId, Post)],
+        topics: &HashMap<TopicId, Topic>,
+        session_dates: &[NaiveDate],
+    ) -> Self {
+        let events = posts
+            .iter()
+            .filter(|(_, post)| post.partaking_users.contains(&user))
+            .map(|(id, post)| CalendarEvent::from_post(*id, post, topics))
+            .chain(session_dates.iter().copied().map(CalendarEvent::session));
+        Self::published(format!("{SESSION_SUMMARY} (joined)"), events)
+    }
+
+    /// Invitation (or its cancellation) for a single attendee of a post.
+    ///
+    /// The sequence grows with `now`, so a later invite always supersedes an
+    /// earlier cancellation of the same post and vice versa.
+    pub fn invite(
+        method: CalendarMethod,
+        id: PostId,
+        post: &Post,
+        topics: &HashMap<TopicId, Topic>,
+        attendee: String,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut event = CalendarEvent::from_post(id, post, topics);
+        event.sequence = sequence_at(now);
+        event.cancelled = method == CalendarMethod::Cancel;
+        event.attendees.push(attendee);
+        Self {
+            name: SESSION_SUMMARY.to_string(),
+            method,
+            events: vec![event],
+        }
+    }
+
+    fn published(name: String, events: impl Iterator<Item = CalendarEvent>) -> Self {
+        let mut events = events.collect::<Vec<_>>();
+        events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.uid.cmp(&b.uid)));
+        Self {
+            name,
+            method: CalendarMethod::Publish,
+            events,
+        }
+    }
+
+    pub fn to_ics(&self, now: DateTime<Utc>) -> String {
+        let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let mut out = String::new();
+        push_line(&mut out, "BEGIN:VCALENDAR");
+        push_line(&mut out, "VERSION:2.0");
+        push_line(&mut out, &format!("PRODID:{PRODUCT_ID}"));
+        push_line(&mut out, "CALSCALE:GREGORIAN");
+        push_line(&mut out, &format!("METHOD:{}", self.method.as_str()));
+        push_line(
+            &mut out,
+            &format!("X-WR-CALNAME:{}", escape_text(&self.name)),
+        );
+        for event in &self.events {
+            let end = event
+                .date
+                .checked_add_days(Days::new(1))
+                .unwrap_or(event.date);
+            push_line(&mut out, "BEGIN:VEVENT");
+            push_line(&mut out, &format!("UID:{}", event.uid));
+            push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+            push_line(&mut out, &format!("SEQUENCE:{}", event.sequence));
+            push_line(
+                &mut out,
+                &format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")),
+            );
+            push_line(
+                &mut out,
+                &format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+            );
+            push_line(
+                &mut out,
+                &format!("SUMMARY:{}", escape_text(&event.summary)),
+            );
+            if let Some(description) = &event.description {
+                push_line(
+                    &mut out,
+                    &format!("DESCRIPTION:{}", escape_text(description)),
+                );
+            }
+            if let Some(organizer) = &event.organizer {
+                push_line(&mut out, &format!("ORGANIZER:mailto:{organizer}"));
+            }
+            for attendee in &event.attendees {
+                push_line(
+                    &mut out,
+                    &format!("ATTENDEE;ROLE=REQ-PARTICIPANT;RSVP=FALSE:mailto:{attendee}"),
+                );
+            }
+            if event.cancelled {
+                push_line(&mut out, "STATUS:CANCELLED");
+            }
+            push_line(&mut out, "END:VEVENT");
+        }
+        push_line(&mut out, "END:VCALENDAR");
+        out
+    }
+}
+
+pub fn post_uid(id: PostId) -> String {
+    format!("{id}@{UID_DOMAIN}")
+}
+
+fn sequence_at(now: DateTime<Utc>) -> u32 {
+    let epoch = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    (now - epoch).num_minutes().clamp(0, i64::from(i32::MAX)) as u32
+}
+
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line folded at 75 octets as required by RFC 5545.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use peer_practice_messages::current::level::{Level, LevelRange};
+    use peer_practice_messages::current::topic::{LegacyTopic, legacy_topics};
+
+    fn post(owner: UserId, content: &str) -> Post {
+        Post {
+            topics: vec![LegacyTopic::Anchor.into()],
+            content: content.to_string(),
+            level: LevelRange::single(Level::new("Club")),
+            ..Post::sample(owner, Utc.with_ymd_and_hms(2026, 3, 13, 12, 0, 0).unwrap())
+        }
+    }
+
+    #[test]
+    fn personal_feed_only_contains_joined_posts() {
+        let me = UserId::new();
+        let joined = (PostId::new(), post(me, ""));
+        let other = (PostId::new(), post(UserId::new(), ""));
+        let calendar =
+            Calendar::personal(me, &[joined.clone(), other.clone()], &legacy_topics(), &[]);
+
+        assert_eq!(calendar.events.len(), 1);
+        assert_eq!(calendar.events[0].uid, post_uid(joined.0));
+    }
+
+    #[test]
+    fn uids_are_stable_between_renders() {
+        let me = UserId::new();
+        let posts = [(PostId::new(), post(me, "Anchor variations"))];
+        let session = NaiveDate::from_ymd_opt(2026, 3, 13).unwrap();
+        let first = Calendar::personal(me, &posts, &legacy_topics(), &[session]).to_ics(Utc::now());
+        let second =
+            Calendar::personal(me, &posts, &legacy_topics(), &[session]).to_ics(Utc::now());
+
+        let uids = |ics: &str| {
+            ics.lines()
+                .filter(|l| l.starts_with("UID:"))
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uids(&first), uids(&second));
+        assert!(first.contains("UID:session-20260313@peer-practice\r\n"));
+    }
+
+    #[test]
+    fn public_feed_only_contains_sessions() {
+        let session = NaiveDate::from_ymd_opt(2026, 3, 13).unwrap();
+        let calendar = Calendar::public(&[session]);
+
+        assert_eq!(calendar.events.len(), 1);
+        assert_eq!(calendar.events[0].summary, SESSION_SUMMARY);
+        assert_eq!(calendar.events[0].description, None);
+    }
+
+    #[test]
+    fn escapes_and_folds_long_lines() {
+        let me = UserId::new();
+        let content = "Rock, step; triple\nanchor ".repeat(5);
+        let posts = [(PostId::new(), post(me, &content))];
+        let ics = Calendar::personal(me, &posts, &legacy_topics(), &[]).to_ics(Utc::now());
+
+        assert!(ics.contains("SUMMARY:Anchor ("));
+
+        assert!(ics.contains("Rock\\, step\\; triple\\nanchor"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+
+    #[test]
+    fn cancellation_supersedes_earlier_invite() {
+        let me = UserId::new();
+        let id = PostId::new();
+        let now = Utc::now();
+        let invite = Calendar::invite(
+            CalendarMethod::Request,
+            id,
+            &post(me, ""),
+            &legacy_topics(),
+            "me@example.com".to_string(),
+            now,
+        );
+        let cancel = Calendar::invite(
+            CalendarMethod::Cancel,
+            id,
+            &post(me, ""),
+            &legacy_topics(),
+            "me@example.com".to_string(),
+            now + Duration::minutes(5),
+        );
+
+        assert!(cancel.events[0].sequence > invite.events[0].sequence);
+        let ics = cancel.to_ics(now);
+        assert!(ics.contains("METHOD:CANCEL\r\n"));
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+        assert!(ics.contains(&format!("UID:{}\r\n", post_uid(id))));
+    }
+}
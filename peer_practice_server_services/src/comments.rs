@@ -0,0 +1,229 @@
+use crate::storage::StorageMsg;
+use crate::ws_hub::WsHubMsg;
+use peer_practice_messages::current::comment::{Comment, CommentId, with_replies};
+use peer_practice_messages::current::messages::ServerToClient;
+use peer_practice_messages::current::post::PostId;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+
+#[derive(Debug)]
+pub enum CommentsMsg {
+    New(Comment, oneshot::Sender<CommentId>),
+    Update(CommentId, Comment),
+    /// Removes the comment together with all replies below it.
+    Remove(CommentId),
+    /// Removes every comment on a post, e.g. after the post was deleted.
+    RemoveForPost(PostId),
+    Get(CommentId, oneshot::Sender<Option<Comment>>),
+    ForPost(PostId, oneshot::Sender<Vec<(CommentId, Comment)>>),
+    Counts(oneshot::Sender<HashMap<PostId, usize>>),
+}
+
+pub fn spawn_comments_actor(
+    storage: Sender<StorageMsg>,
+    ws_hub: Sender<WsHubMsg>,
+) -> Sender<CommentsMsg> {
+    let (tx, mut rx) = mpsc::channel::<CommentsMsg>(100);
+
+    tokio::spawn(async move {
+        let mut comments = setup(&storage).await;
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                CommentsMsg::New(comment, respond_to) => {
+                    let id = CommentId::new();
+                    let post = comment.post;
+                    comments.insert(id, comment.clone());
+                    let _ = respond_to.send(id);
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Comment(id, comment)))
+                        .await;
+                    broadcast_count(&comments, post, &ws_hub).await;
+                    let _ = storage
+                        .send(StorageMsg::SaveComments(comments.clone()))
+                        .await;
+                }
+                CommentsMsg::Update(id, comment) => {
+                    let Some(existing) = comments.get_mut(&id) else {
+                        continue;
+                    };
+                    *existing = comment.clone();
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Comment(id, comment)))
+                        .await;
+                    let _ = storage
+                        .send(StorageMsg::SaveComments(comments.clone()))
+                        .await;
+                }
+                CommentsMsg::Remove(id) => {
+                    if comments.contains_key(&id) {
+                        let removed = with_replies(id, &comments);
+                        remove(&mut comments, removed, &storage, &ws_hub).await;
+                    }
+                }
+                CommentsMsg::RemoveForPost(post) => {
+                    let removed = comments
+                        .iter()
+                        .filter(|(_, comment)| comment.post == post)
+                        .map(|(id, _)| *id)
+                        .collect::<Vec<_>>();
+                    if !removed.is_empty() {
+                        remove(&mut comments, removed, &storage, &ws_hub).await;
+                    }
+                }
+                CommentsMsg::Get(id, respond_to) => {
+                    let _ = respond_to.send(comments.get(&id).cloned());
+                }
+                CommentsMsg::ForPost(post, respond_to) => {
+                    let list = comments
+                        .iter()
+                        .filter(|(_, comment)| comment.post == post)
+                        .map(|(id, comment)| (*id, comment.clone()))
+                        .collect();
+                    let _ = respond_to.send(list);
+                }
+                CommentsMsg::Counts(respond_to) => {
+                    let mut counts = HashMap::new();
+                    for comment in comments.values() {
+                        *counts.entry(comment.post).or_default() += 1;
+                    }
+                    let _ = respond_to.send(counts);
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+async fn remove(
+    comments: &mut HashMap<CommentId, Comment>,
+    removed: Vec<CommentId>,
+    storage: &Sender<StorageMsg>,
+    ws_hub: &Sender<WsHubMsg>,
+) {
+    let mut posts = HashSet::new();
+    for id in removed {
+        if let Some(comment) = comments.remove(&id) {
+            posts.insert(comment.post);
+        }
+        let _ = ws_hub
+            .send(WsHubMsg::BroadcastAll(ServerToClient::RemovedComment(id)))
+            .await;
+    }
+    for post in posts {
+        broadcast_count(comments, post, ws_hub).await;
+    }
+    let _ = storage
+        .send(StorageMsg::SaveComments(comments.clone()))
+        .await;
+}
+
+async fn broadcast_count(
+    comments: &HashMap<CommentId, Comment>,
+    post: PostId,
+    ws_hub: &Sender<WsHubMsg>,
+) {
+    let count = comments
+        .values()
+        .filter(|comment| comment.post == post)
+        .count();
+    let _ = ws_hub
+        .send(WsHubMsg::BroadcastAll(ServerToClient::CommentCounts(vec![
+            (post, count),
+        ])))
+        .await;
+}
+
+async fn setup(storage: &Sender<StorageMsg>) -> HashMap<CommentId, Comment> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveComments { respond_to })
+        .await;
+    recv.await.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::spawn_storage_actor;
+    use chrono::Utc;
+    use peer_practice_messages::current::user::UserId;
+
+    fn comment(post: PostId, parent: Option<CommentId>) -> Comment {
+        Comment {
+            post,
+            parent,
+            author: UserId::new(),
+            content: "Nice".to_string(),
+            created: Utc::now(),
+            edited: None,
+        }
+    }
+
+    async fn add(comments: &Sender<CommentsMsg>, comment: Comment) -> CommentId {
+        let (respond_to, recv) = oneshot::channel();
+        comments
+            .send(CommentsMsg::New(comment, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap()
+    }
+
+    async fn for_post(comments: &Sender<CommentsMsg>, post: PostId) -> HashSet<CommentId> {
+        let (respond_to, recv) = oneshot::channel();
+        comments
+            .send(CommentsMsg::ForPost(post, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap().into_iter().map(|(id, _)| id).collect()
+    }
+
+    #[tokio::test]
+    async fn removing_a_comment_takes_its_replies_along() {
+        let dir = std::env::temp_dir().join(format!("comments-{}", uuid::Uuid::new_v4()));
+        let storage = spawn_storage_actor(dir.clone());
+        let (ws_hub, mut hub) = mpsc::channel(100);
+        let comments = spawn_comments_actor(storage.clone(), ws_hub.clone());
+        let (post, other_post) = (PostId::new(), PostId::new());
+
+        let root = add(&comments, comment(post, None)).await;
+        let reply = add(&comments, comment(post, Some(root))).await;
+        let nested = add(&comments, comment(post, Some(reply))).await;
+        let kept = add(&comments, comment(post, None)).await;
+        let elsewhere = add(&comments, comment(other_post, None)).await;
+        while hub.try_recv().is_ok() {}
+
+        comments.send(CommentsMsg::Remove(root)).await.unwrap();
+        assert_eq!(for_post(&comments, post).await, HashSet::from([kept]));
+        assert_eq!(
+            for_post(&comments, other_post).await,
+            HashSet::from([elsewhere])
+        );
+
+        let mut removed = HashSet::new();
+        let mut counts = Vec::new();
+        while let Ok(WsHubMsg::BroadcastAll(msg)) = hub.try_recv() {
+            match msg {
+                ServerToClient::RemovedComment(id) => {
+                    removed.insert(id);
+                }
+                ServerToClient::CommentCounts(changed) => counts.extend(changed),
+                msg => panic!("unexpected broadcast {msg:?}"),
+            }
+        }
+        assert_eq!(removed, HashSet::from([root, reply, nested]));
+        assert_eq!(counts, [(post, 1)]);
+
+        let restarted = spawn_comments_actor(storage, ws_hub);
+        let (respond_to, recv) = oneshot::channel();
+        restarted
+            .send(CommentsMsg::Counts(respond_to))
+            .await
+            .unwrap();
+        let reloaded = recv.await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloaded, HashMap::from([(post, 1), (other_post, 1)]));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::storage::StorageMsg;
+use crate::ws_hub::WsHubMsg;
+use peer_practice_messages::current::direct_message::{DirectMessage, DirectMessageId};
+use peer_practice_messages::current::messages::ServerToClient;
+use peer_practice_messages::current::user::UserId;
+use std::collections::HashMap;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+
+#[derive(Debug)]
+pub enum ConversationsMsg {
+    Send(DirectMessage, oneshot::Sender<DirectMessageId>),
+    /// Every message the user sent or received.
+    ListFor(
+        UserId,
+        oneshot::Sender<Vec<(DirectMessageId, DirectMessage)>>,
+    ),
+    /// Marks the messages `reader` received from `partner` as read.
+    MarkRead {
+        reader: UserId,
+        partner: UserId,
+    },
+}
+
+pub fn spawn_conversations_actor(
+    storage: Sender<StorageMsg>,
+    ws_hub: Sender<WsHubMsg>,
+) -> Sender<ConversationsMsg> {
+    let (tx, mut rx) = mpsc::channel::<ConversationsMsg>(100);
+
+    tokio::spawn(async move {
+        let mut messages = setup(&storage).await;
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                ConversationsMsg::Send(message, respond_to) => {
+                    let id = DirectMessageId::new();
+                    messages.insert(id, message.clone());
+                    let _ = respond_to.send(id);
+                    notify(&ws_hub, id, &message).await;
+                    let _ = storage
+                        .send(StorageMsg::SaveDirectMessages(messages.clone()))
+                        .await;
+                }
+                ConversationsMsg::ListFor(user, respond_to) => {
+                    let list = messages
+                        .iter()
+                        .filter(|(_, message)| message.partner_of(user).is_some())
+                        .map(|(id, message)| (*id, message.clone()))
+                        .collect();
+                    let _ = respond_to.send(list);
+                }
+                ConversationsMsg::MarkRead { reader, partner } => {
+                    let mut changed = false;
+                    for (id, message) in messages.iter_mut() {
+                        if message.to == reader && message.from == partner && !message.read {
+                            message.read = true;
+                            changed = true;
+                            notify(&ws_hub, *id, message).await;
+                        }
+                    }
+                    if changed {
+                        let _ = storage
+                            .send(StorageMsg::SaveDirectMessages(messages.clone()))
+                            .await;
+                    }
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+/// Sends the message to both participants, and only to them.
+async fn notify(ws_hub: &Sender<WsHubMsg>, id: DirectMessageId, message: &DirectMessage) {
+    let mut participants = vec![message.from];
+    if message.to != message.from {
+        participants.push(message.to);
+    }
+    for user_id in participants {
+        let _ = ws_hub
+            .send(WsHubMsg::BroadcastUser {
+                user_id,
+                msg: ServerToClient::DirectMessage(id, message.clone()),
+            })
+            .await;
+    }
+}
+
+async fn setup(storage: &Sender<StorageMsg>) -> HashMap<DirectMessageId, DirectMessage> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveDirectMessages { respond_to })
+        .await;
+    recv.await.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::spawn_storage_actor;
+    use chrono::Utc;
+
+    fn message(from: UserId, to: UserId) -> DirectMessage {
+        DirectMessage {
+            from,
+            to,
+            content: "Hi".to_string(),
+            sent: Utc::now(),
+            read: false,
+        }
+    }
+
+    async fn list_for(
+        conversations: &Sender<ConversationsMsg>,
+        user: UserId,
+    ) -> HashMap<DirectMessageId, DirectMessage> {
+        let (respond_to, recv) = oneshot::channel();
+        conversations
+            .send(ConversationsMsg::ListFor(user, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap().into_iter().collect()
+    }
+
+    #[tokio::test]
+    async fn marks_only_received_messages_read_and_keeps_them() {
+        let dir = std::env::temp_dir().join(format!("conversations-{}", uuid::Uuid::new_v4()));
+        let storage = spawn_storage_actor(dir.clone());
+        let (ws_hub, mut hub) = mpsc::channel(100);
+        let conversations = spawn_conversations_actor(storage.clone(), ws_hub.clone());
+        let (alice, bob, carol) = (UserId::new(), UserId::new(), UserId::new());
+
+        let mut ids = Vec::new();
+        for sent in [
+            message(alice, bob),
+            message(bob, alice),
+            message(carol, bob),
+        ] {
+            let (respond_to, recv) = oneshot::channel();
+            conversations
+                .send(ConversationsMsg::Send(sent, respond_to))
+                .await
+                .unwrap();
+            ids.push(recv.await.unwrap());
+        }
+        let [to_bob, to_alice, from_carol] = ids[..] else {
+            unreachable!()
+        };
+        conversations
+            .send(ConversationsMsg::MarkRead {
+                reader: bob,
+                partner: alice,
+            })
+            .await
+            .unwrap();
+
+        let listed = list_for(&conversations, bob).await;
+        assert_eq!(listed.len(), 3);
+        assert!(listed[&to_bob].read);
+        assert!(!listed[&to_alice].read);
+        assert!(!listed[&from_carol].read);
+        assert!(
+            !list_for(&conversations, alice)
+                .await
+                .contains_key(&from_carol)
+        );
+
+        let mut recipients = Vec::new();
+        while let Ok(WsHubMsg::BroadcastUser { user_id, msg }) = hub.try_recv() {
+            let ServerToClient::DirectMessage(id, _) = msg else {
+                panic!("unexpected broadcast {msg:?}");
+            };
+            recipients.push((id, user_id));
+        }
+        assert_eq!(recipients.len(), 8);
+        assert!(
+            recipients
+                .iter()
+                .all(|(id, user_id)| *id == from_carol || *user_id != carol)
+        );
+
+        let restarted = spawn_conversations_actor(storage, ws_hub);
+        let reloaded = list_for(&restarted, bob).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(reloaded[&to_bob].read);
+        assert!(!reloaded[&to_alice].read);
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::NaiveDate;
+use peer_practice_messages::current::level::Levels;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::topic::{Topic, TopicId};
+use peer_practice_messages::current::user::UserId;
+use std::collections::{BTreeMap, HashMap};
+
+/// Heading for posts without a topic.
+const NO_TOPIC: &str = "Other ideas";
+
+/// Posts keyed by level rank and name, so levels sort from lowest to highest.
+type ByLevel = BTreeMap<(Option<usize>, String), Vec<DigestPost>>;
+
+/// The posts of one practice evening, as emailed to one user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub date: NaiveDate,
+    /// Ordered by topic name.
+    pub topics: Vec<DigestTopic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestTopic {
+    pub name: String,
+    /// Ordered from lowest to highest level.
+    pub levels: Vec<DigestLevel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestLevel {
+    pub level: String,
+    pub posts: Vec<DigestPost>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestPost {
+    pub owner: String,
+    pub content: String,
+    pub partaking: usize,
+    /// `None` if the user already joined.
+    pub join_link: Option<String>,
+}
+
+impl Digest {
+    /// Groups `posts` by topic and level, linking every post `user` has not
+    /// joined yet to `join_link`. A post with several topics shows under each.
+    pub fn new(
+        date: NaiveDate,
+        user: UserId,
+        posts: &[(PostId, Post)],
+        topics: &HashMap<TopicId, Topic>,
+        levels: &Levels,
+        owner_name: impl Fn(UserId) -> String,
+        join_link: impl Fn(PostId) -> String,
+    ) -> Self {
+        let mut grouped: BTreeMap<String, ByLevel> = BTreeMap::new();
+        for (id, post) in posts {
+            let mut names = post
+                .topics
+                .iter()
+                .filter_map(|topic| topics.get(topic))
+                .map(|topic| topic.name.clone())
+                .collect::<Vec<_>>();
+            if names.is_empty() {
+                names.push(NO_TOPIC.to_string());
+            }
+            let level = (levels.rank(&post.level.from), post.level.to_string());
+            let entry = DigestPost {
+                owner: owner_name(post.owner),
+                content: post.content.clone(),
+                partaking: post.partaking_users.len(),
+                join_link: (!post.partaking_users.contains(&user) && !post.is_waiting(&user))
+                    .then(|| join_link(*id)),
+            };
+            for name in names {
+                grouped
+                    .entry(name)
+                    .or_default()
+                    .entry(level.clone())
+                    .or_default()
+                    .push(entry.clone());
+            }
+        }
+
+        Self {
+            date,
+            topics: grouped
+                .into_iter()
+                .map(|(name, levels)| DigestTopic {
+                    name,
+                    levels: levels
+                        .into_iter()
+                        .map(|((_, level), posts)| DigestLevel { level, posts })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use peer_practice_messages::current::accent_colors::AccentColor;
+    use peer_practice_messages::current::level::{Level, LevelRange};
+
+    fn post(owner: UserId, topics: Vec<TopicId>, level: &str) -> Post {
+        Post {
+            topics,
+            content: level.to_string(),
+            level: LevelRange::single(Level::new(level)),
+            ..Post::sample(owner, Utc::now())
+        }
+    }
+
+    #[test]
+    fn groups_by_topic_then_level() {
+        let (me, other) = (UserId::new(), UserId::new());
+        let whips = TopicId::new();
+        let topics = HashMap::from([(
+            whips,
+            Topic {
+                name: "Whips".to_string(),
+                color: AccentColor::Teal,
+                archived: false,
+            },
+        )]);
+        let posts = vec![
+            (PostId::new(), post(other, vec![whips], "Level 3")),
+            (PostId::new(), post(me, vec![whips], "Level 1")),
+            (PostId::new(), post(other, vec![], "Level 2")),
+        ];
+
+        let digest = Digest::new(
+            NaiveDate::from_ymd_opt(2025, 3, 14).unwrap(),
+            me,
+            &posts,
+            &topics,
+            &Levels::default(),
+            |_| "Someone".to_string(),
+            |id| format!("https://example.com/join/{id}"),
+        );
+
+        let names = digest
+            .topics
+            .iter()
+            .map(|topic| topic.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![NO_TOPIC, "Whips"]);
+        let whips = &digest.topics[1];
+        let levels = whips
+            .levels
+            .iter()
+            .map(|level| level.level.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(levels, vec!["Level 1", "Level 3"]);
+        assert_eq!(whips.levels[0].posts[0].join_link, None);
+        assert!(whips.levels[1].posts[0].join_link.is_some());
+    }
+}
@@ -0,0 +1,89 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use eyre::WrapErr;
+use lettre::message::dkim::{DkimConfig, DkimSigningAlgorithm, DkimSigningKey};
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey};
+use rsa::pkcs8::{DecodePrivateKey, EncodePublicKey, LineEnding};
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use std::path::Path;
+
+/// Name of the key in DNS unless configured otherwise.
+pub const DEFAULT_SELECTOR: &str = "peer-practice";
+
+/// Size of generated keys, what most receivers expect today.
+pub const KEY_BITS: usize = 2048;
+
+/// Reads the RSA key at `path`, in PKCS#1 or PKCS#8 PEM, for signing as
+/// `selector._domainkey.domain`.
+pub fn load(path: &Path, selector: String, domain: String) -> eyre::Result<DkimConfig> {
+    let pem = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read DKIM key {}", path.display()))?;
+    config(&pem, selector, domain).wrap_err_with(|| format!("Invalid DKIM key {}", path.display()))
+}
+
+fn config(pem: &str, selector: String, domain: String) -> eyre::Result<DkimConfig> {
+    let key = RsaPrivateKey::from_pkcs1_pem(pem)
+        .or_else(|_| RsaPrivateKey::from_pkcs8_pem(pem))
+        .map_err(|err| eyre::eyre!("{err}"))?;
+    // lettre only takes PKCS#1.
+    let pkcs1 = key
+        .to_pkcs1_pem(LineEnding::LF)
+        .map_err(|err| eyre::eyre!("{err}"))?;
+    let key = DkimSigningKey::new(&pkcs1, DkimSigningAlgorithm::Rsa)?;
+    Ok(DkimConfig::default_config(selector, domain, key))
+}
+
+/// A new private key in PKCS#1 PEM and the value of its DNS TXT record.
+pub fn generate() -> eyre::Result<(String, String)> {
+    let key = RsaPrivateKey::new(&mut rand_core::OsRng, KEY_BITS)?;
+    let pem = key
+        .to_pkcs1_pem(LineEnding::LF)
+        .map_err(|err| eyre::eyre!("Could not encode DKIM key: {err}"))?;
+    let public = RsaPublicKey::from(&key)
+        .to_public_key_der()
+        .map_err(|err| eyre::eyre!("Could not encode DKIM public key: {err}"))?;
+    let value = format!("v=DKIM1; k=rsa; p={}", STANDARD.encode(public.as_bytes()));
+    Ok((pem.to_string(), value))
+}
+
+/// The TXT record publishing `value` in zone file syntax. DNS strings hold at
+/// most 255 characters, so longer values are split.
+pub fn dns_record(selector: &str, domain: &str, value: &str) -> String {
+    let strings = value
+        .as_bytes()
+        .chunks(255)
+        .map(|chunk| format!("\"{}\"", String::from_utf8_lossy(chunk)))
+        .collect::<Vec<_>>();
+    format!(
+        "{selector}._domainkey.{domain}. IN TXT ( {} )",
+        strings.join(" ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lettre::Message;
+
+    #[test]
+    fn signs_with_a_generated_key() {
+        let (pem, value) = generate().unwrap();
+        let config = config(&pem, "pp".to_string(), "example.com".to_string()).unwrap();
+        let mut email = Message::builder()
+            .from("from@example.com".parse().unwrap())
+            .to("to@example.com".parse().unwrap())
+            .subject("Hello")
+            .body("Hi there".to_string())
+            .unwrap();
+
+        email.sign(&config);
+
+        let raw = String::from_utf8(email.formatted()).unwrap();
+        assert!(raw.contains("DKIM-Signature: "));
+        assert!(raw.contains("d=example.com; s=pp;"));
+
+        let record = dns_record("pp", "example.com", &value);
+        assert!(record.starts_with("pp._domainkey.example.com. IN TXT ( \"v=DKIM1; k=rsa; p="));
+        assert!(record.contains("\" \""));
+    }
+}
@@ -1,84 +1,480 @@
+use crate::calendar::{Calendar, CalendarMethod};
+use crate::digest::Digest;
+use crate::email_queue::{Delivery, EmailQueue, QueuedEmail, attempt};
+use crate::storage::StorageMsg;
+use crate::transport::{Mailer, SmtpSecurity, Transport};
+use askama::Template;
+use chrono::{DateTime, Local, Utc};
 use eyre::WrapErr;
-use lettre::message::Mailbox;
+use lettre::Message;
+use lettre::message::dkim::DkimConfig;
 use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::transport::smtp::response::Response;
-use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use peer_practice_messages::current::email::{DeliveryId, UndeliveredEmail};
+use peer_practice_messages::current::language::Language;
 use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
 use tokio::sync::{mpsc, oneshot};
+use tokio::time::MissedTickBehavior;
+
+/// How often the queue is checked for emails due for a retry.
+const RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Answered once the first attempt is over. Errors mean the email could not
+/// be built or was rejected for good.
+pub type DeliveryResponder = oneshot::Sender<Result<Delivery, eyre::Error>>;
 
 pub enum EmailMsg {
     SendLoginMail {
         target: Mailbox,
         validation_code: u32,
-        respond_to: oneshot::Sender<Result<Response, eyre::Error>>,
+        language: Language,
+        expires_at: DateTime<Utc>,
+        respond_to: DeliveryResponder,
+    },
+    SendMail {
+        mail: OutgoingMail,
+        respond_to: DeliveryResponder,
+    },
+    /// Sends `calendar` as an iTIP message organized by the configured sender.
+    SendCalendarInvite {
+        target: Mailbox,
+        calendar: Calendar,
+        respond_to: DeliveryResponder,
     },
+    /// Renders the posts of a practice evening as an HTML email.
+    SendDigest {
+        target: Mailbox,
+        digest: Digest,
+        respond_to: DeliveryResponder,
+    },
+    /// Emails waiting for a retry and the dead-letter log, newest first.
+    Undelivered {
+        respond_to: oneshot::Sender<Vec<UndeliveredEmail>>,
+    },
+    /// Queues a dead letter again, replies whether there was one.
+    Retry {
+        id: DeliveryId,
+        respond_to: oneshot::Sender<bool>,
+    },
+}
+
+#[derive(Template)]
+#[template(path = "digest.html")]
+struct DigestHtml<'a> {
+    digest: &'a Digest,
+}
+
+#[derive(Template)]
+#[template(path = "digest.txt")]
+struct DigestText<'a> {
+    digest: &'a Digest,
+}
+
+#[derive(Template)]
+#[template(path = "login.html")]
+struct LoginHtml<'a> {
+    lang: &'a str,
+    branding: &'a Branding,
+    text: &'a LoginText,
+    code: u32,
+}
+
+#[derive(Template)]
+#[template(path = "login.txt")]
+struct LoginPlain<'a> {
+    branding: &'a Branding,
+    text: &'a LoginText,
+    code: u32,
+}
+
+/// The sentences of a login email in the recipient's language.
+struct LoginText {
+    subject: String,
+    purpose: String,
+    expiry: String,
+    ignore: String,
+}
+
+impl LoginText {
+    fn new(
+        language: Language,
+        site_name: &str,
+        code: u32,
+        expires_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let minutes = (expires_at - now).num_minutes().max(1);
+        let until = expires_at.with_timezone(&Local).format("%H:%M");
+        match language {
+            Language::English => Self {
+                subject: format!("{code} is your {site_name} login code"),
+                purpose: format!("Use this code to log in to {site_name}:"),
+                expiry: format!("The code is valid for {minutes} minutes, until {until}."),
+                ignore: "If you did not try to log in, you can ignore this email. \
+                    Nobody can log in without the code."
+                    .to_string(),
+            },
+            Language::German => Self {
+                subject: format!("{code} ist dein Anmeldecode für {site_name}"),
+                purpose: format!("Mit diesem Code meldest du dich bei {site_name} an:"),
+                expiry: format!("Der Code ist {minutes} Minuten gültig, bis {until} Uhr."),
+                ignore: "Falls du dich nicht anmelden wolltest, kannst du diese E-Mail \
+                    ignorieren. Ohne den Code kann sich niemand anmelden."
+                    .to_string(),
+            },
+        }
+    }
+}
+
+/// How the site presents itself in emails.
+#[derive(Debug, Clone)]
+pub struct Branding {
+    pub site_name: String,
+    /// CSS color of the header.
+    pub accent_color: String,
+    pub logo_url: Option<String>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self {
+            site_name: "Peer Practice".to_string(),
+            accent_color: "#0d9488".to_string(),
+            logo_url: None,
+        }
+    }
+}
+
+pub struct OutgoingMail {
+    pub target: Mailbox,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+    pub attachments: Vec<MailAttachment>,
+}
+
+pub struct MailAttachment {
+    pub filename: String,
+    pub content_type: ContentType,
+    pub body: Vec<u8>,
 }
 
 pub struct EmailConfiguration {
-    pub credentials: Credentials,
     pub from: Mailbox,
     pub reply_to: Mailbox,
-    pub tls_relay: String,
+    pub transport: Transport,
+    pub branding: Branding,
+    /// Signs outgoing emails if set.
+    pub dkim: Option<DkimConfig>,
 }
 
 impl EmailConfiguration {
+    /// Sends through `tls_relay` with STARTTLS.
     pub fn new(
         tls_relay: String,
         credentials: String,
         password: String,
         from: &str,
         reply_to: &str,
+    ) -> Result<Self, eyre::Error> {
+        let transport = Transport::Smtp {
+            security: SmtpSecurity::Starttls,
+            relay: tls_relay,
+            credentials: Some(Credentials::new(credentials, password)),
+        };
+        Self::with_transport(transport, from, reply_to)
+    }
+
+    pub fn with_transport(
+        transport: Transport,
+        from: &str,
+        reply_to: &str,
     ) -> Result<Self, eyre::Error> {
         Ok(Self {
             reply_to: Mailbox::from_str(reply_to)
                 .wrap_err("Could not parse \"reply_to\" email address.")?,
             from: Mailbox::from_str(from).wrap_err("Could not parse \"from\" email address.")?,
-            credentials: Credentials::new(credentials, password),
-            tls_relay,
+            transport,
+            branding: Branding::default(),
+            dkim: None,
         })
     }
 }
 
-pub fn spawn_email_actor(config: EmailConfiguration) -> mpsc::Sender<EmailMsg> {
+/// Sends emails, keeping the ones that fail for retries. Every email is sent
+/// in its own task, so a slow attempt does not hold up the others.
+pub fn spawn_email_actor(
+    config: EmailConfiguration,
+    storage: Sender<StorageMsg>,
+) -> eyre::Result<Sender<EmailMsg>> {
+    let mailer =
+        Arc::new(Mailer::new(&config.transport).wrap_err("Could not set up email transport.")?);
     let (tx, mut rx) = mpsc::channel::<EmailMsg>(64);
 
+    // Attempts report back here, so only the actor touches the queue.
+    let (attempted_tx, mut attempted_rx) = mpsc::channel::<(QueuedEmail, Option<bool>)>(64);
+
     tokio::spawn(async move {
-        while let Some(msg) = rx.recv().await {
-            match msg {
+        let mut queue = setup(&storage).await;
+        let mut retries = tokio::time::interval(RETRY_INTERVAL);
+        retries.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            let msg = tokio::select! {
+                msg = rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                Some((email, outcome)) = attempted_rx.recv() => {
+                    if queue.attempted(email, outcome) {
+                        save(&storage, &queue).await;
+                    }
+                    continue;
+                }
+                _ = retries.tick() => {
+                    let due = queue.take_due(Utc::now());
+                    if !due.is_empty() {
+                        let mailer = mailer.clone();
+                        let attempted = attempted_tx.clone();
+                        tokio::spawn(async move {
+                            for mut email in due {
+                                let outcome = attempt(&mailer, &mut email, Utc::now()).await;
+                                let _ = attempted.send((email, outcome)).await;
+                            }
+                        });
+                    }
+                    continue;
+                }
+            };
+
+            let (mail, expires_at, respond_to) = match msg {
                 EmailMsg::SendLoginMail {
                     target,
                     validation_code,
+                    language,
+                    expires_at,
+                    respond_to,
+                } => (
+                    login_mail(&config, target, validation_code, language, expires_at),
+                    Some(expires_at),
+                    respond_to,
+                ),
+                EmailMsg::SendMail { mail, respond_to } => (Ok(mail), None, respond_to),
+                EmailMsg::SendCalendarInvite {
+                    target,
+                    calendar,
+                    respond_to,
+                } => (
+                    calendar_invite_mail(&config, target, calendar),
+                    None,
                     respond_to,
-                } => {
-                    let res = send_login_mail(&config, target, validation_code).await;
-                    let _ = respond_to.send(res);
+                ),
+                EmailMsg::SendDigest {
+                    target,
+                    digest,
+                    respond_to,
+                } => (digest_mail(target, &digest), None, respond_to),
+                EmailMsg::Undelivered { respond_to } => {
+                    let _ = respond_to.send(queue.undelivered());
+                    continue;
                 }
-            }
+                EmailMsg::Retry { id, respond_to } => {
+                    let retried = queue.retry(id, Utc::now());
+                    if retried {
+                        save(&storage, &queue).await;
+                        retries.reset_immediately();
+                    }
+                    let _ = respond_to.send(retried);
+                    continue;
+                }
+            };
+
+            let mut email = match mail.and_then(|mail| queued_email(&config, mail, expires_at)) {
+                Ok(email) => email,
+                Err(err) => {
+                    let _ = respond_to.send(Err(err));
+                    continue;
+                }
+            };
+            let mailer = mailer.clone();
+            let attempted = attempted_tx.clone();
+            tokio::spawn(async move {
+                let outcome = attempt(&mailer, &mut email, Utc::now()).await;
+                let _ = respond_to.send(Delivery::of(&email, outcome));
+                if outcome.is_some() {
+                    let _ = attempted.send((email, outcome)).await;
+                }
+            });
         }
     });
 
-    tx
+    Ok(tx)
+}
+
+async fn setup(storage: &Sender<StorageMsg>) -> EmailQueue {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveEmailQueue { respond_to })
+        .await;
+    EmailQueue::new(recv.await.unwrap_or_default(), Utc::now())
+}
+
+async fn save(storage: &Sender<StorageMsg>, queue: &EmailQueue) {
+    let _ = storage
+        .send(StorageMsg::SaveEmailQueue(queue.persisted()))
+        .await;
 }
 
-async fn send_login_mail(
+fn login_mail(
     config: &EmailConfiguration,
-    target: impl Into<Mailbox>,
-    validation_code: u32,
-) -> Result<Response, eyre::Error> {
-    let email = Message::builder()
+    target: Mailbox,
+    code: u32,
+    language: Language,
+    expires_at: DateTime<Utc>,
+) -> Result<OutgoingMail, eyre::Error> {
+    let branding = &config.branding;
+    let text = LoginText::new(language, &branding.site_name, code, expires_at, Utc::now());
+    Ok(OutgoingMail {
+        target,
+        subject: text.subject.clone(),
+        text: LoginPlain {
+            branding,
+            text: &text,
+            code,
+        }
+        .render()
+        .wrap_err("Could not render login text.")?,
+        html: Some(
+            LoginHtml {
+                lang: language.tag(),
+                branding,
+                text: &text,
+                code,
+            }
+            .render()
+            .wrap_err("Could not render login HTML.")?,
+        ),
+        attachments: Vec::new(),
+    })
+}
+
+fn calendar_invite_mail(
+    config: &EmailConfiguration,
+    target: Mailbox,
+    mut calendar: Calendar,
+) -> Result<OutgoingMail, eyre::Error> {
+    for event in &mut calendar.events {
+        event.organizer = Some(config.from.email.to_string());
+    }
+    let summary = calendar
+        .events
+        .first()
+        .map(|event| format!("{} on {}", event.summary, event.date.format("%Y-%m-%d")))
+        .unwrap_or_default();
+    let (subject, text) = match calendar.method {
+        CalendarMethod::Cancel => (
+            format!("Cancelled: {summary}"),
+            format!("You are no longer part of {summary}."),
+        ),
+        _ => (
+            format!("Invitation: {summary}"),
+            format!("You joined {summary}. The attached invite adds it to your calendar."),
+        ),
+    };
+    let content_type = ContentType::parse(&format!(
+        "text/calendar; charset=utf-8; method={}",
+        calendar.method.as_str()
+    ))?;
+
+    Ok(OutgoingMail {
+        target,
+        subject,
+        text,
+        html: None,
+        attachments: vec![MailAttachment {
+            filename: "invite.ics".to_string(),
+            content_type,
+            body: calendar.to_ics(Utc::now()).into_bytes(),
+        }],
+    })
+}
+
+fn digest_mail(target: Mailbox, digest: &Digest) -> Result<OutgoingMail, eyre::Error> {
+    Ok(OutgoingMail {
+        target,
+        subject: format!("Practice ideas for {}", digest.date.format("%A, %B %-d")),
+        text: DigestText { digest }
+            .render()
+            .wrap_err("Could not render digest text.")?,
+        html: Some(
+            DigestHtml { digest }
+                .render()
+                .wrap_err("Could not render digest HTML.")?,
+        ),
+        attachments: Vec::new(),
+    })
+}
+
+fn queued_email(
+    config: &EmailConfiguration,
+    mail: OutgoingMail,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<QueuedEmail, eyre::Error> {
+    let mut body = match mail.html {
+        Some(html) => {
+            MultiPart::mixed().multipart(MultiPart::alternative_plain_html(mail.text, html))
+        }
+        None => MultiPart::mixed().singlepart(SinglePart::plain(mail.text)),
+    };
+    for attachment in mail.attachments {
+        body = body.singlepart(
+            Attachment::new(attachment.filename).body(attachment.body, attachment.content_type),
+        );
+    }
+
+    let mut email = Message::builder()
         .from(config.from.clone())
         .reply_to(config.reply_to.clone())
-        .to(target.into())
-        .subject(format!("Login Code {validation_code}"))
-        .header(ContentType::TEXT_PLAIN)
-        .body(format!("{validation_code}"))
+        .to(mail.target)
+        .subject(mail.subject.clone())
+        .multipart(body)
         .with_context(|| "Could not create email.")?;
+    if let Some(dkim) = &config.dkim {
+        email.sign(dkim);
+    }
 
-    let mailer = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.tls_relay)?
-        .credentials(config.credentials.clone())
-        .build();
+    QueuedEmail::new(&email, mail.subject, expires_at, Utc::now())
+}
 
-    // Send the email
-    mailer.send(email).await.wrap_err("Failed to send email.")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn login_mail_explains_the_code_in_the_users_language() {
+        let mut config = EmailConfiguration::new(
+            "smtp.example.com:587".to_string(),
+            "account".to_string(),
+            "password".to_string(),
+            "from@example.com",
+            "reply@example.com",
+        )
+        .unwrap();
+        config.branding.site_name = "Salsa Lab".to_string();
+        let target = Mailbox::from_str("dancer@example.com").unwrap();
+        let expires_at = Utc::now() + Duration::minutes(15);
+
+        let mail = login_mail(&config, target, 123456, Language::German, expires_at).unwrap();
+
+        assert_eq!(mail.subject, "123456 ist dein Anmeldecode für Salsa Lab");
+        assert!(mail.text.contains("123456"));
+        assert!(mail.text.contains("Minuten gültig"));
+        let html = mail.html.unwrap();
+        assert!(html.contains(r#"<html lang="de">"#));
+        assert!(html.contains("Salsa Lab"));
+    }
 }
@@ -0,0 +1,369 @@
+use crate::transport::{Mailer, Rejected};
+use chrono::{DateTime, Duration, Utc};
+use eyre::WrapErr;
+use lettre::Message;
+use lettre::address::{Address, Envelope};
+use peer_practice_messages::current::email::{DeliveryId, DeliveryStatus, UndeliveredEmail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::{error, info};
+
+/// Dead letters kept for organizers to look at.
+const DEAD_LETTERS_KEPT: usize = 200;
+
+/// A formatted email with what is needed to send it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEmail {
+    pub id: DeliveryId,
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub subject: String,
+    /// The whole MIME message. Empty for expiring emails read back from
+    /// disk, see [`EmailQueue::persisted`].
+    pub raw: String,
+    pub created: DateTime<Utc>,
+    /// Not retried past this, e.g. when a login code runs out. Such emails
+    /// carry secrets.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub attempts: u32,
+    pub next_attempt: DateTime<Utc>,
+    pub last_error: Option<String>,
+    /// Set once the email was given up on.
+    pub failed_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedEmail {
+    pub fn new(
+        message: &Message,
+        subject: String,
+        expires_at: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> eyre::Result<Self> {
+        let envelope = message.envelope();
+        Ok(Self {
+            id: DeliveryId::new(),
+            from: envelope.from().map(|from| from.to_string()),
+            to: envelope.to().iter().map(|to| to.to_string()).collect(),
+            subject,
+            raw: String::from_utf8(message.formatted()).wrap_err("Email is not UTF-8.")?,
+            created: now,
+            expires_at,
+            attempts: 0,
+            next_attempt: now,
+            last_error: None,
+            failed_at: None,
+        })
+    }
+
+    fn envelope(&self) -> eyre::Result<Envelope> {
+        let from = self
+            .from
+            .as_deref()
+            .map(str::parse::<Address>)
+            .transpose()?;
+        let to = self
+            .to
+            .iter()
+            .map(|to| to.parse::<Address>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Envelope::new(from, to)?)
+    }
+
+    /// Records a failed attempt and schedules the next one. Returns `false`
+    /// once the email is given up on.
+    fn failed(&mut self, error: String, permanent: bool, now: DateTime<Utc>) -> bool {
+        self.last_error = Some(error);
+        let next_attempt = retry_delay(self.attempts)
+            .filter(|_| !permanent)
+            .map(|delay| now + delay)
+            .filter(|next| self.expires_at.is_none_or(|expires_at| *next < expires_at));
+        match next_attempt {
+            Some(next_attempt) => {
+                self.next_attempt = next_attempt;
+                true
+            }
+            None => {
+                self.failed_at = Some(now);
+                false
+            }
+        }
+    }
+
+    fn undelivered(&self) -> UndeliveredEmail {
+        UndeliveredEmail {
+            id: self.id,
+            to: self.to.clone(),
+            subject: self.subject.clone(),
+            created: self.created,
+            attempts: self.attempts,
+            last_error: self.last_error.clone().unwrap_or_default(),
+            status: match self.failed_at {
+                Some(at) => DeliveryStatus::Failed { at },
+                None => DeliveryStatus::Retrying {
+                    next_attempt: self.next_attempt,
+                },
+            },
+        }
+    }
+}
+
+/// How long to wait after `attempts` failed attempts, `None` once it is time
+/// to give up.
+pub fn retry_delay(attempts: u32) -> Option<Duration> {
+    match attempts {
+        1 => Some(Duration::minutes(1)),
+        2 => Some(Duration::minutes(5)),
+        3 => Some(Duration::minutes(15)),
+        4 => Some(Duration::hours(1)),
+        5 => Some(Duration::hours(4)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    /// The first attempt failed, the email is retried later.
+    Queued(DeliveryId),
+}
+
+impl Delivery {
+    /// What the first [`attempt`] at `email` means for its sender. Errors once
+    /// the email is given up on.
+    pub fn of(email: &QueuedEmail, outcome: Option<bool>) -> eyre::Result<Self> {
+        match outcome {
+            None => Ok(Delivery::Sent),
+            Some(true) => Ok(Delivery::Queued(email.id)),
+            Some(false) => Err(Rejected(email.last_error.clone().unwrap_or_default()).into()),
+        }
+    }
+}
+
+/// Emails waiting for a retry and the dead-letter log.
+#[derive(Debug, Default)]
+pub struct EmailQueue {
+    emails: Vec<QueuedEmail>,
+    /// Emails handed out by [`take_due`](Self::take_due) and still being sent.
+    in_flight: HashSet<DeliveryId>,
+}
+
+impl EmailQueue {
+    /// Takes the emails [`persisted`](Self::persisted) before. Those whose
+    /// body was not kept are given up on.
+    pub fn new(mut emails: Vec<QueuedEmail>, now: DateTime<Utc>) -> Self {
+        for email in &mut emails {
+            if email.raw.is_empty() && email.failed_at.is_none() {
+                email.last_error = Some("Not kept across restarts.".to_string());
+                email.failed_at = Some(now);
+            }
+        }
+        Self {
+            emails,
+            in_flight: HashSet::new(),
+        }
+    }
+
+    /// The emails to write to disk. Expiring emails, like login codes, are
+    /// only kept for the dead-letter log, without their body.
+    pub fn persisted(&self) -> Vec<QueuedEmail> {
+        self.emails
+            .iter()
+            .cloned()
+            .map(|mut email| {
+                if email.expires_at.is_some() {
+                    email.raw.clear();
+                }
+                email
+            })
+            .collect()
+    }
+
+    /// The emails whose next attempt is due. They stay queued but are not
+    /// handed out again until their attempt is [recorded](Self::attempted).
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<QueuedEmail> {
+        let due = self
+            .emails
+            .iter()
+            .filter(|email| {
+                email.failed_at.is_none()
+                    && email.next_attempt <= now
+                    && !self.in_flight.contains(&email.id)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        self.in_flight.extend(due.iter().map(|email| email.id));
+        due
+    }
+
+    /// Records an [`attempt`] at `email`, keeping it for retries or the
+    /// dead-letter log unless it went out. Returns whether anything changed.
+    pub fn attempted(&mut self, email: QueuedEmail, outcome: Option<bool>) -> bool {
+        self.in_flight.remove(&email.id);
+        let before = self.emails.len();
+        self.emails.retain(|queued| queued.id != email.id);
+        let removed = self.emails.len() != before;
+        match outcome {
+            Some(_) => {
+                self.keep(email);
+                true
+            }
+            None => removed,
+        }
+    }
+
+    /// Queues a dead letter again, with a fresh set of attempts. Expired
+    /// emails and those whose body was not kept cannot be sent again.
+    pub fn retry(&mut self, id: DeliveryId, now: DateTime<Utc>) -> bool {
+        let Some(email) = self.emails.iter_mut().find(|email| {
+            email.id == id
+                && email.failed_at.is_some()
+                && !email.raw.is_empty()
+                && email.expires_at.is_none_or(|expires_at| now < expires_at)
+        }) else {
+            return false;
+        };
+        email.attempts = 0;
+        email.failed_at = None;
+        email.next_attempt = now;
+        true
+    }
+
+    /// Newest first.
+    pub fn undelivered(&self) -> Vec<UndeliveredEmail> {
+        let mut undelivered = self
+            .emails
+            .iter()
+            .map(QueuedEmail::undelivered)
+            .collect::<Vec<_>>();
+        undelivered.sort_by_key(|email| std::cmp::Reverse(email.created));
+        undelivered
+    }
+
+    fn keep(&mut self, email: QueuedEmail) {
+        self.emails.push(email);
+        let mut dead = self
+            .emails
+            .iter()
+            .filter_map(|email| email.failed_at)
+            .collect::<Vec<_>>();
+        if dead.len() > DEAD_LETTERS_KEPT {
+            dead.sort_unstable_by_key(|at| std::cmp::Reverse(*at));
+            let oldest_kept = dead[DEAD_LETTERS_KEPT - 1];
+            self.emails
+                .retain(|email| email.failed_at.is_none_or(|at| at >= oldest_kept));
+        }
+    }
+}
+
+/// Sends `email` once. Returns `None` when it went out, otherwise whether it
+/// will be retried.
+pub async fn attempt(mailer: &Mailer, email: &mut QueuedEmail, now: DateTime<Utc>) -> Option<bool> {
+    email.attempts += 1;
+    let result = match email.envelope() {
+        Ok(envelope) => mailer.send_raw(&envelope, email.raw.as_bytes()).await,
+        Err(err) => Err(Rejected(err.to_string()).into()),
+    };
+    let err = match result {
+        Ok(()) => {
+            if email.attempts > 1 {
+                info!(id = %email.id, attempts = email.attempts, "delivered queued email");
+            }
+            return None;
+        }
+        Err(err) => err,
+    };
+    let permanent = err.downcast_ref::<Rejected>().is_some();
+    let retry = email.failed(format!("{err:#}"), permanent, now);
+    if retry {
+        info!(id = %email.id, attempts = email.attempts, "email delivery failed, retrying later: {err:#}");
+    } else {
+        error!(id = %email.id, to = ?email.to, "gave up on email: {err:#}");
+    }
+    Some(retry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> QueuedEmail {
+        let message = Message::builder()
+            .from("from@example.com".parse().unwrap())
+            .to("to@example.com".parse().unwrap())
+            .subject("Hello")
+            .body("Hi there".to_string())
+            .unwrap();
+        QueuedEmail::new(&message, "Hello".to_string(), expires_at, now).unwrap()
+    }
+
+    #[test]
+    fn retries_with_growing_delays_until_giving_up() {
+        let now = Utc::now();
+        let mut queued = email(None, now);
+        assert_eq!(queued.envelope().unwrap().to().len(), 1);
+
+        let mut delays = Vec::new();
+        loop {
+            queued.attempts += 1;
+            if !queued.failed("timeout".to_string(), false, now) {
+                break;
+            }
+            delays.push(queued.next_attempt - now);
+        }
+        assert_eq!(queued.attempts, 6);
+        assert_eq!(queued.failed_at, Some(now));
+        assert!(delays.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut rejected = email(None, now);
+        rejected.attempts = 1;
+        assert!(!rejected.failed("550 no such user".to_string(), true, now));
+
+        // A login code that runs out before the next attempt is not retried.
+        let mut login = email(Some(now + Duration::minutes(10)), now);
+        login.attempts = 3;
+        assert!(!login.failed("timeout".to_string(), false, now));
+    }
+
+    #[test]
+    fn login_codes_are_neither_stored_nor_resent_once_expired() {
+        let now = Utc::now();
+        let mut login = email(Some(now + Duration::minutes(10)), now);
+        login.failed_at = Some(now);
+        let id = login.id;
+        let mut queue = EmailQueue::new(vec![login, email(None, now)], now);
+
+        let persisted = queue.persisted();
+        assert!(persisted[0].raw.is_empty());
+        assert!(!persisted[1].raw.is_empty());
+
+        assert!(!queue.retry(id, now + Duration::minutes(11)));
+        assert!(queue.retry(id, now + Duration::minutes(1)));
+        assert_eq!(
+            queue.emails[0].expires_at,
+            Some(now + Duration::minutes(10))
+        );
+
+        let mut restarted = EmailQueue::new(persisted, now);
+        assert!(!restarted.retry(id, now + Duration::minutes(1)));
+    }
+
+    #[test]
+    fn due_emails_are_handed_out_once_per_attempt() {
+        let now = Utc::now();
+        let (mut sent, mut failing) = (email(None, now), email(None, now));
+        let mut queue = EmailQueue::new(vec![sent.clone(), failing.clone()], now);
+
+        assert_eq!(queue.take_due(now).len(), 2);
+        assert!(queue.take_due(now).is_empty());
+        assert_eq!(queue.undelivered().len(), 2);
+
+        sent.attempts += 1;
+        assert!(queue.attempted(sent, None));
+        failing.attempts += 1;
+        assert!(failing.failed("timeout".to_string(), false, now));
+        assert!(queue.attempted(failing.clone(), Some(true)));
+        assert_eq!(queue.emails.len(), 1);
+        assert!(queue.take_due(now).is_empty());
+        assert_eq!(queue.take_due(failing.next_attempt)[0].id, failing.id);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::storage::StorageMsg;
+use peer_practice_messages::current::history::{AuditEntry, PostVersion};
+use peer_practice_messages::current::post::PostId;
+use std::collections::HashMap;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+
+/// Oldest audit entries are dropped beyond this many.
+const MAX_AUDIT_ENTRIES: usize = 10_000;
+
+#[derive(Debug)]
+pub enum HistoryMsg {
+    Record(AuditEntry),
+    /// Remembers a saved state of a post.
+    Version(PostId, Box<PostVersion>),
+    Versions(PostId, oneshot::Sender<Vec<PostVersion>>),
+    /// Drops the versions of posts that were purged for good.
+    Forget(Vec<PostId>),
+    /// Replies with the most recent entries, newest first.
+    AuditLog(usize, oneshot::Sender<Vec<AuditEntry>>),
+}
+
+pub fn spawn_history_actor(storage: Sender<StorageMsg>) -> Sender<HistoryMsg> {
+    let (tx, mut rx) = mpsc::channel::<HistoryMsg>(100);
+
+    tokio::spawn(async move {
+        let (mut versions, mut audit_log) = setup(&storage).await;
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                HistoryMsg::Record(entry) => {
+                    audit_log.push(entry);
+                    if audit_log.len() > MAX_AUDIT_ENTRIES {
+                        let excess = audit_log.len() - MAX_AUDIT_ENTRIES;
+                        audit_log.drain(..excess);
+                    }
+                    let _ = storage
+                        .send(StorageMsg::SaveAuditLog(audit_log.clone()))
+                        .await;
+                }
+                HistoryMsg::Version(id, version) => {
+                    versions.entry(id).or_default().push(*version);
+                    let _ = storage
+                        .send(StorageMsg::SavePostVersions(versions.clone()))
+                        .await;
+                }
+                HistoryMsg::Versions(id, respond_to) => {
+                    let _ = respond_to.send(versions.get(&id).cloned().unwrap_or_default());
+                }
+                HistoryMsg::Forget(ids) => {
+                    let before = versions.len();
+                    for id in &ids {
+                        versions.remove(id);
+                    }
+                    if versions.len() != before {
+                        let _ = storage
+                            .send(StorageMsg::SavePostVersions(versions.clone()))
+                            .await;
+                    }
+                }
+                HistoryMsg::AuditLog(limit, respond_to) => {
+                    let recent = audit_log.iter().rev().take(limit).cloned().collect();
+                    let _ = respond_to.send(recent);
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+async fn setup(
+    storage: &Sender<StorageMsg>,
+) -> (HashMap<PostId, Vec<PostVersion>>, Vec<AuditEntry>) {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrievePostVersions { respond_to })
+        .await;
+    let versions = recv.await.unwrap_or_default();
+
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveAuditLog { respond_to })
+        .await;
+    let audit_log = recv.await.unwrap_or_default();
+
+    (versions, audit_log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::spawn_storage_actor;
+    use chrono::{Duration, Utc};
+    use peer_practice_messages::current::history::AuditAction;
+    use peer_practice_messages::current::post::Post;
+    use peer_practice_messages::current::user::UserId;
+
+    async fn versions(history: &Sender<HistoryMsg>, post: PostId) -> usize {
+        let (respond_to, recv) = oneshot::channel();
+        history
+            .send(HistoryMsg::Versions(post, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap().len()
+    }
+
+    async fn audit_log(history: &Sender<HistoryMsg>, limit: usize) -> Vec<AuditEntry> {
+        let (respond_to, recv) = oneshot::channel();
+        history
+            .send(HistoryMsg::AuditLog(limit, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn forgets_purged_posts_and_lists_the_newest_entries_first() {
+        let dir = std::env::temp_dir().join(format!("history-{}", uuid::Uuid::new_v4()));
+        let storage = spawn_storage_actor(dir.clone());
+        let history = spawn_history_actor(storage.clone());
+        let (owner, now) = (UserId::new(), Utc::now());
+        let (purged, kept) = (PostId::new(), PostId::new());
+
+        let entries = [
+            (purged, AuditAction::Created),
+            (kept, AuditAction::Created),
+            (kept, AuditAction::Edited),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(minutes, (post, action))| AuditEntry {
+            at: now + Duration::minutes(minutes as i64),
+            actor: Some(owner),
+            post,
+            action,
+        })
+        .collect::<Vec<_>>();
+        for entry in &entries {
+            history
+                .send(HistoryMsg::Record(entry.clone()))
+                .await
+                .unwrap();
+            let version = PostVersion {
+                at: entry.at,
+                by: owner,
+                post: Post::sample(owner, now),
+            };
+            history
+                .send(HistoryMsg::Version(entry.post, Box::new(version)))
+                .await
+                .unwrap();
+        }
+        history
+            .send(HistoryMsg::Forget(vec![purged]))
+            .await
+            .unwrap();
+
+        assert_eq!(versions(&history, purged).await, 0);
+        assert_eq!(versions(&history, kept).await, 2);
+        assert_eq!(
+            audit_log(&history, 2).await,
+            [entries[2].clone(), entries[1].clone()]
+        );
+
+        let restarted = spawn_history_actor(storage);
+        let (reloaded_versions, reloaded_log) = (
+            versions(&restarted, kept).await,
+            audit_log(&restarted, 10).await,
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloaded_versions, 2);
+        assert_eq!(reloaded_log.len(), entries.len());
+        assert_eq!(reloaded_log[0], entries[2]);
+    }
+}
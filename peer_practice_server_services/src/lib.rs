@@ -1,6 +1,22 @@
+pub mod calendar;
+pub mod comments;
+pub mod conversations;
+pub mod digest;
+pub mod dkim;
 pub mod email;
+pub mod email_queue;
+pub mod history;
+pub mod notifications;
 pub mod pending_logins;
 pub mod posts;
+pub mod push;
+pub mod reminders;
+pub mod rotation;
+pub mod search;
+pub mod stats;
 pub mod storage;
+pub mod sync;
+pub mod topics;
+pub mod transport;
 pub mod users;
 pub mod ws_hub;
@@ -0,0 +1,424 @@
+use crate::email::{EmailMsg, OutgoingMail};
+use crate::push::{PushMessage, PushMsg};
+use crate::reminders::SentReminder;
+use crate::storage::StorageMsg;
+use crate::topics::TopicsMsg;
+use crate::users::UsersMsg;
+use crate::ws_hub::WsHubMsg;
+use chrono::{DateTime, Duration, Utc};
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::level::Levels;
+use peer_practice_messages::current::messages::ServerToClient;
+use peer_practice_messages::current::notification::{
+    Notification, NotificationId, NotificationKind,
+};
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::topic::{Topic, TopicId};
+use peer_practice_messages::current::user::{User, UserId};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+use tracing::error;
+
+/// Notifications kept per user, older ones are dropped.
+const MAX_NOTIFICATIONS_PER_USER: usize = 100;
+
+/// A change to a post that users may want to hear about.
+#[derive(Debug)]
+pub enum PostEvent {
+    Created(PostId, Post),
+    Joined(PostId, Post, UserId),
+    Rescheduled {
+        id: PostId,
+        post: Post,
+        from: DateTime<Utc>,
+    },
+    Deleted(PostId, Post, UserId),
+}
+
+#[derive(Debug)]
+pub enum NotificationsMsg {
+    Event(Box<PostEvent>),
+    /// Reminders that are due, each sent only once.
+    Remind(Vec<(SentReminder, Post)>),
+    ListFor(UserId, oneshot::Sender<Vec<(NotificationId, Notification)>>),
+    MarkRead(UserId),
+}
+
+/// Routes post events to the users they concern, in the app, by email or by
+/// Web Push as each user prefers.
+pub fn spawn_notifications_actor(
+    storage: Sender<StorageMsg>,
+    ws_hub: Sender<WsHubMsg>,
+    users: Sender<UsersMsg>,
+    topics: Sender<TopicsMsg>,
+    email: Sender<EmailMsg>,
+    push: Sender<PushMsg>,
+    levels: Levels,
+) -> Sender<NotificationsMsg> {
+    let (tx, mut rx) = mpsc::channel::<NotificationsMsg>(100);
+
+    let outlets = Outlets {
+        ws_hub,
+        email,
+        push,
+    };
+    tokio::spawn(async move {
+        let (mut notifications, mut reminders) = setup(&storage).await;
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                NotificationsMsg::Event(event) => {
+                    let everyone = list_users(&users).await;
+                    let topics = list_topics(&topics).await;
+
+                    let (post_id, post, kind) = describe(&event);
+                    let summary = summary(post, &topics);
+                    let mut stored = false;
+                    for user in recipients(&event, &everyone, &levels) {
+                        let notification = Notification {
+                            to: user.id,
+                            at: Utc::now(),
+                            post: post_id,
+                            summary: summary.clone(),
+                            kind: kind.clone(),
+                            read: false,
+                        };
+                        stored |=
+                            dispatch(&outlets, &mut notifications, user, &everyone, notification)
+                                .await;
+                    }
+                    if stored {
+                        let _ = storage
+                            .send(StorageMsg::SaveNotifications(notifications.clone()))
+                            .await;
+                    }
+                }
+                NotificationsMsg::Remind(due) => {
+                    let before = reminders.len();
+                    let now = Utc::now();
+                    // Reminders of past posts can no longer be due.
+                    reminders.retain(|sent| sent.date > now - Duration::days(2));
+                    let mut changed = reminders.len() != before;
+                    let due = due
+                        .into_iter()
+                        .filter(|(reminder, _)| !reminders.contains(reminder))
+                        .collect::<Vec<_>>();
+                    if !due.is_empty() {
+                        let everyone = list_users(&users).await;
+                        let topics = list_topics(&topics).await;
+                        let mut stored = false;
+                        for (reminder, post) in due {
+                            reminders.insert(reminder);
+                            changed = true;
+                            let Some(user) = everyone.iter().find(|user| user.id == reminder.user)
+                            else {
+                                continue;
+                            };
+                            let notification = Notification {
+                                to: user.id,
+                                at: now,
+                                post: reminder.post,
+                                summary: summary(&post, &topics),
+                                kind: NotificationKind::Reminder,
+                                read: false,
+                            };
+                            stored |= dispatch(
+                                &outlets,
+                                &mut notifications,
+                                user,
+                                &everyone,
+                                notification,
+                            )
+                            .await;
+                        }
+                        if stored {
+                            let _ = storage
+                                .send(StorageMsg::SaveNotifications(notifications.clone()))
+                                .await;
+                        }
+                    }
+                    if changed {
+                        let _ = storage
+                            .send(StorageMsg::SaveReminders(reminders.clone()))
+                            .await;
+                    }
+                }
+                NotificationsMsg::ListFor(user, respond_to) => {
+                    let list = notifications
+                        .iter()
+                        .filter(|(_, notification)| notification.to == user)
+                        .map(|(id, notification)| (*id, notification.clone()))
+                        .collect();
+                    let _ = respond_to.send(list);
+                }
+                NotificationsMsg::MarkRead(user) => {
+                    let mut changed = false;
+                    for (id, notification) in notifications.iter_mut() {
+                        if notification.to == user && !notification.read {
+                            notification.read = true;
+                            changed = true;
+                            let _ = outlets
+                                .ws_hub
+                                .send(WsHubMsg::BroadcastUser {
+                                    user_id: user,
+                                    msg: ServerToClient::Notification(*id, notification.clone()),
+                                })
+                                .await;
+                        }
+                    }
+                    if changed {
+                        let _ = storage
+                            .send(StorageMsg::SaveNotifications(notifications.clone()))
+                            .await;
+                    }
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+fn describe(event: &PostEvent) -> (PostId, &Post, NotificationKind) {
+    match event {
+        PostEvent::Created(id, post) => (*id, post, NotificationKind::NewPostForLevel),
+        PostEvent::Joined(id, post, user) => (*id, post, NotificationKind::Joined(*user)),
+        PostEvent::Rescheduled { id, post, from } => {
+            (*id, post, NotificationKind::Rescheduled { from: *from })
+        }
+        PostEvent::Deleted(id, post, _) => (*id, post, NotificationKind::Deleted),
+    }
+}
+
+/// The users `event` concerns, never the one who caused it.
+fn recipients<'a>(event: &PostEvent, users: &'a [User], levels: &Levels) -> Vec<&'a User> {
+    let attendees = |post: &Post, actor: UserId| {
+        users
+            .iter()
+            .filter(|user| user.id != actor)
+            .filter(|user| post.partaking_users.contains(&user.id) || post.is_waiting(&user.id))
+            .collect()
+    };
+    match event {
+        PostEvent::Created(_, post) => users
+            .iter()
+            .filter(|user| user.id != post.owner)
+            .filter(|user| {
+                user.level
+                    .as_ref()
+                    .is_some_and(|level| levels.contains(&post.level, level))
+            })
+            .collect(),
+        PostEvent::Joined(_, post, joiner) => users
+            .iter()
+            .filter(|user| user.id == post.owner && user.id != *joiner)
+            .collect(),
+        PostEvent::Rescheduled { post, .. } => attendees(post, post.owner),
+        PostEvent::Deleted(_, post, by) => attendees(post, *by),
+    }
+}
+
+/// Where notifications go besides the stored list.
+struct Outlets {
+    ws_hub: Sender<WsHubMsg>,
+    email: Sender<EmailMsg>,
+    push: Sender<PushMsg>,
+}
+
+/// Sends `notification` through the channels `user` picked for its kind.
+/// Returns whether it was stored in `notifications`.
+async fn dispatch(
+    outlets: &Outlets,
+    notifications: &mut HashMap<NotificationId, Notification>,
+    user: &User,
+    everyone: &[User],
+    notification: Notification,
+) -> bool {
+    let channels = user.config.notifications.channels(&notification.kind);
+    let name = |id| display_name(everyone, id);
+    if channels.email {
+        send_email(&outlets.email, user, notification.text(name));
+    }
+    if channels.push {
+        let _ = outlets
+            .push
+            .send(PushMsg::Send {
+                to: user.id,
+                message: PushMessage {
+                    title: "Peer Practice".to_string(),
+                    body: notification.text(name),
+                    url: "/".to_string(),
+                },
+            })
+            .await;
+    }
+    if !channels.in_app {
+        return false;
+    }
+    let id = NotificationId::new();
+    notifications.insert(id, notification.clone());
+    prune(notifications, user.id);
+    let _ = outlets
+        .ws_hub
+        .send(WsHubMsg::BroadcastUser {
+            user_id: user.id,
+            msg: ServerToClient::Notification(id, notification),
+        })
+        .await;
+    true
+}
+
+/// Topics and date of `post`, as notifications describe it.
+fn summary(post: &Post, topics: &HashMap<TopicId, Topic>) -> String {
+    let title = post.title(topics);
+    format!(
+        "{} on {}",
+        if title.is_empty() { "Practice" } else { &title },
+        convert_utc_to_local_date(post.date)
+    )
+}
+
+async fn list_users(users: &Sender<UsersMsg>) -> Vec<User> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = users.send(UsersMsg::List { respond_to }).await;
+    recv.await.unwrap_or_default()
+}
+
+async fn list_topics(topics: &Sender<TopicsMsg>) -> HashMap<TopicId, Topic> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = topics.send(TopicsMsg::List(respond_to)).await;
+    recv.await.unwrap_or_default()
+}
+
+fn display_name(users: &[User], id: UserId) -> String {
+    users
+        .iter()
+        .find(|user| user.id == id)
+        .and_then(|user| user.display_name.clone())
+        .unwrap_or_else(|| "Someone".to_string())
+}
+
+/// Keeps only the newest notifications of `user`.
+fn prune(notifications: &mut HashMap<NotificationId, Notification>, user: UserId) {
+    let mut own = notifications
+        .iter()
+        .filter(|(_, notification)| notification.to == user)
+        .map(|(id, notification)| (*id, notification.at))
+        .collect::<Vec<_>>();
+    if own.len() <= MAX_NOTIFICATIONS_PER_USER {
+        return;
+    }
+    own.sort_by_key(|(_, at)| std::cmp::Reverse(*at));
+    for (id, _) in own.split_off(MAX_NOTIFICATIONS_PER_USER) {
+        notifications.remove(&id);
+    }
+}
+
+/// Emails in the background, so a slow mail server does not hold up other
+/// notifications.
+fn send_email(email: &Sender<EmailMsg>, user: &User, text: String) {
+    let email = email.clone();
+    let user_id = user.id;
+    let mail = OutgoingMail {
+        target: user.email.clone().into(),
+        subject: text.clone(),
+        text,
+        html: None,
+        attachments: Vec::new(),
+    };
+    tokio::spawn(async move {
+        let (respond_to, recv) = oneshot::channel();
+        let _ = email.send(EmailMsg::SendMail { mail, respond_to }).await;
+        if let Ok(Err(err)) = recv.await {
+            error!("Failed to email notification to {:?}: {:?}", user_id, err);
+        }
+    });
+}
+
+async fn setup(
+    storage: &Sender<StorageMsg>,
+) -> (HashMap<NotificationId, Notification>, HashSet<SentReminder>) {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveNotifications { respond_to })
+        .await;
+    let notifications = recv.await.unwrap_or_default();
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveReminders { respond_to })
+        .await;
+    (notifications, recv.await.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peer_practice_messages::current::email::Email;
+    use peer_practice_messages::current::level::{Level, LevelRange};
+    use std::collections::HashSet;
+
+    fn user(name: &str, level: Option<&str>) -> User {
+        User {
+            email: Email::new(&format!("{name}@example.com")).unwrap(),
+            display_name: Some(name.to_string()),
+            id: UserId::new(),
+            calendar_token: None,
+            config: Default::default(),
+            level: level.map(Level::new),
+        }
+    }
+
+    #[test]
+    fn notifies_only_the_users_concerned() {
+        let users = vec![
+            user("owner", Some("Level 1")),
+            user("joiner", Some("Level 2")),
+            user("waiting", Some("Level 3")),
+            user("bystander", None),
+        ];
+        let [owner, joiner, waiting, _] = [0, 1, 2, 3].map(|i| users[i].id);
+        let post = Post {
+            level: LevelRange {
+                from: Level::new("Level 1"),
+                to: Level::new("Level 2"),
+            },
+            partaking_users: HashSet::from([owner, joiner]),
+            capacity: Some(2),
+            waitlist: vec![waiting],
+            ..Post::sample(owner, Utc::now())
+        };
+        let levels = Levels::default();
+        let ids = |event: PostEvent| {
+            recipients(&event, &users, &levels)
+                .into_iter()
+                .map(|user| user.id)
+                .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(
+            ids(PostEvent::Created(PostId::new(), post.clone())),
+            HashSet::from([joiner])
+        );
+        assert_eq!(
+            ids(PostEvent::Joined(PostId::new(), post.clone(), joiner)),
+            HashSet::from([owner])
+        );
+        assert_eq!(
+            ids(PostEvent::Joined(PostId::new(), post.clone(), owner)),
+            HashSet::new()
+        );
+        assert_eq!(
+            ids(PostEvent::Rescheduled {
+                id: PostId::new(),
+                post: post.clone(),
+                from: Utc::now(),
+            }),
+            HashSet::from([joiner, waiting])
+        );
+        assert_eq!(
+            ids(PostEvent::Deleted(PostId::new(), post, owner)),
+            HashSet::from([joiner, waiting])
+        );
+    }
+}
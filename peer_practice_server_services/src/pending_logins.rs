@@ -3,6 +3,9 @@ use peer_practice_messages::current::email::Email;
 use std::collections::HashMap;
 use tokio::sync::{mpsc, oneshot};
 
+/// How long a login code can be used.
+pub const LOGIN_CODE_VALIDITY_MINUTES: i64 = 15;
+
 pub enum PendingLoginsMsg {
     GetByAddress {
         address: Email,
@@ -30,7 +33,7 @@ pub fn spawn_pending_logins_actor() -> mpsc::Sender<PendingLoginsMsg> {
                 } => {
                     let now = Utc::now();
                     let val = if let Some((code, set_at)) = state.get(&address) {
-                        if *set_at + Duration::minutes(15) > now {
+                        if *set_at + Duration::minutes(LOGIN_CODE_VALIDITY_MINUTES) > now {
                             Some(*code)
                         } else {
                             None
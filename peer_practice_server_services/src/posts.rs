@@ -1,49 +1,281 @@
+use crate::history::HistoryMsg;
+use crate::notifications::{NotificationsMsg, PostEvent};
+use crate::search::{Stems, search};
+use crate::stats::practice_stats;
 use crate::storage::StorageMsg;
+use crate::sync::{Changes, PostLog};
 use crate::ws_hub::WsHubMsg;
+use chrono::{DateTime, Utc};
+use peer_practice_messages::current::archive::{ArchivedPost, Removal};
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::dance_role::DanceRole;
+use peer_practice_messages::current::history::{AuditAction, AuditEntry, PostVersion};
+use peer_practice_messages::current::level::Levels;
 use peer_practice_messages::current::messages::ServerToClient;
 use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::reaction::{PostReactions, Reaction, ReactionCounts};
+use peer_practice_messages::current::search::PostQuery;
+use peer_practice_messages::current::stats::PracticeStats;
+use peer_practice_messages::current::sync::{PostsSync, SyncCursor};
+use peer_practice_messages::current::topic::{Topic, TopicId};
 use peer_practice_messages::current::user::UserId;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::mpsc::Sender;
 use tokio::sync::{mpsc, oneshot};
 
 #[derive(Debug)]
 pub enum PostsMsg {
     New(Post, oneshot::Sender<PostId>),
-    Upsert(PostId, Post),
-    UserJoins(PostId, UserId),
-    UserLeaves(PostId, UserId),
-    Remove(PostId),
+    /// Updates an existing post and replies with the users promoted from the
+    /// waitlist, e.g. after the capacity was raised. Replies `None` without
+    /// touching anything if there is no such post.
+    Upsert(PostId, Post, oneshot::Sender<Option<Vec<UserId>>>),
+    /// Joins with a role, or keeps the role of a user who already joined if
+    /// `None`. Replies with where the user stood before, `None` if there is
+    /// no such post.
+    UserJoins(
+        PostId,
+        UserId,
+        Option<DanceRole>,
+        oneshot::Sender<Option<Membership>>,
+    ),
+    /// Replies with where the user stood before and the users promoted from
+    /// the waitlist into the freed spot, `None` if there is no such post.
+    UserLeaves(PostId, UserId, oneshot::Sender<Option<Left>>),
+    /// Moves a post to the archive, as deleted by a user or as expired if `None`.
+    Remove(PostId, Option<UserId>),
+    /// Brings a deleted post back onto the board, replies whether it was found.
+    Restore(PostId, UserId, oneshot::Sender<bool>),
+    /// Forgets posts deleted before the given time and replies with their ids.
+    Purge(DateTime<Utc>, oneshot::Sender<Vec<PostId>>),
+    GetArchived(PostId, oneshot::Sender<Option<ArchivedPost>>),
+    ListArchive(oneshot::Sender<Vec<(PostId, ArchivedPost)>>),
+    /// Statistics over the sessions that took place, on the board or expired.
+    PracticeStats(UserId, oneshot::Sender<PracticeStats>),
     Get(PostId, oneshot::Sender<Option<Post>>),
     List(oneshot::Sender<Vec<(PostId, Post)>>),
+    ToggleReaction(PostId, UserId, Reaction),
+    /// Replies with the reaction counts of every post and the reactions of the user.
+    Reactions(
+        UserId,
+        oneshot::Sender<Vec<(PostId, ReactionCounts, Vec<Reaction>)>>,
+    ),
+    /// Replies with everything that changed since the cursor, as a single page.
+    Sync(Option<SyncCursor>, oneshot::Sender<PostsSync>),
+    /// Replies with the matching posts, best match first.
+    Search {
+        query: PostQuery,
+        user: UserId,
+        levels: Levels,
+        topics: HashMap<TopicId, Topic>,
+        respond_to: oneshot::Sender<Vec<PostId>>,
+    },
+}
+
+/// Where a user stands on a post.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Membership {
+    Outside,
+    Waiting,
+    Partaking,
+}
+
+impl Membership {
+    fn of(post: &Post, user: &UserId) -> Self {
+        if post.partaking_users.contains(user) {
+            Membership::Partaking
+        } else if post.is_waiting(user) {
+            Membership::Waiting
+        } else {
+            Membership::Outside
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Left {
+    pub before: Membership,
+    pub promoted: Vec<UserId>,
 }
 
 pub fn spawn_posts_actor(
     storage: Sender<StorageMsg>,
     ws_hub: Sender<WsHubMsg>,
+    history: Sender<HistoryMsg>,
+    notifications: Sender<NotificationsMsg>,
 ) -> Sender<PostsMsg> {
     let (tx, mut rx) = mpsc::channel::<PostsMsg>(100);
 
     tokio::spawn(async move {
         let mut posts: HashMap<PostId, Post> = HashMap::new();
+        let mut reactions: HashMap<PostId, PostReactions> = HashMap::new();
+        let mut archive: HashMap<PostId, ArchivedPost> = HashMap::new();
+        let stems = Stems::default();
+        let mut log = setup(&storage, &mut posts, &mut archive, &mut reactions).await;
+        let audit = |post: PostId, actor: Option<UserId>, action: AuditAction| {
+            let history = history.clone();
+            async move {
+                let entry = AuditEntry {
+                    at: Utc::now(),
+                    actor,
+                    post,
+                    action,
+                };
+                let _ = history.send(HistoryMsg::Record(entry)).await;
+            }
+        };
+        let version = |id: PostId, post: &Post| {
+            let history = history.clone();
+            let version = PostVersion {
+                at: Utc::now(),
+                by: post.owner,
+                post: post.clone(),
+            };
+            async move {
+                let _ = history
+                    .send(HistoryMsg::Version(id, Box::new(version)))
+                    .await;
+            }
+        };
 
-        setup(&storage, &mut posts).await;
+        let notify = |event: PostEvent| {
+            let notifications = notifications.clone();
+            async move {
+                let _ = notifications
+                    .send(NotificationsMsg::Event(Box::new(event)))
+                    .await;
+            }
+        };
 
         while let Some(msg) = rx.recv().await {
             match msg {
-                PostsMsg::Upsert(id, post) => {
+                PostsMsg::Upsert(id, mut post, promoted_to) => {
+                    // A post deleted in the meantime is not brought back.
+                    let Some(existing) = posts.get(&id) else {
+                        let _ = promoted_to.send(None);
+                        continue;
+                    };
+                    sanitize(&mut post);
+                    // Membership only changes through joins and leaves, so a
+                    // stale update cannot undo them or bypass the capacity.
+                    post.partaking_users = existing.partaking_users.clone();
+                    post.waitlist = existing.waitlist.clone();
+                    post.roles = existing.roles.clone();
+                    let rescheduled_from = Some(existing.date).filter(|date| *date != post.date);
+                    let promoted = post.promote_waitlist();
                     posts.insert(id, post.clone());
+                    log.changed(id);
+                    let _ = promoted_to.send(Some(promoted.clone()));
+                    version(id, &post).await;
+                    audit(id, Some(post.owner), AuditAction::Edited).await;
+                    for user in promoted {
+                        audit(id, None, AuditAction::Promoted(user)).await;
+                    }
+                    if let Some(from) = rescheduled_from {
+                        notify(PostEvent::Rescheduled {
+                            id,
+                            post: post.clone(),
+                            from,
+                        })
+                        .await;
+                    }
                     let _ = ws_hub
                         .send(WsHubMsg::BroadcastAll(ServerToClient::Post(id, post)))
                         .await;
-                    let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+                    save_posts(&storage, &posts, &log).await;
                 }
-                PostsMsg::Remove(id) => {
-                    posts.remove(&id);
+                PostsMsg::Remove(id, by) => {
+                    let Some(post) = posts.remove(&id) else {
+                        continue;
+                    };
+                    if let Some(user) = by {
+                        notify(PostEvent::Deleted(id, post.clone(), user)).await;
+                    }
+                    let (removal, action) = match by {
+                        Some(user) => (Removal::Deleted(user), AuditAction::Deleted),
+                        None => (Removal::Expired, AuditAction::Expired),
+                    };
+                    archive.insert(
+                        id,
+                        ArchivedPost {
+                            post,
+                            removed_at: Utc::now(),
+                            removal,
+                        },
+                    );
+                    audit(id, by, action).await;
+                    let _ = storage.send(StorageMsg::SaveArchive(archive.clone())).await;
+                    log.removed(id);
                     let _ = ws_hub
                         .send(WsHubMsg::BroadcastAll(ServerToClient::RemovedPost(id)))
                         .await;
-                    let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+                    // Archived posts keep their reactions until they are purged.
+                    save_posts(&storage, &posts, &log).await;
+                }
+                PostsMsg::Restore(id, by, restored) => {
+                    let Some(archived) = archive.remove(&id).filter(ArchivedPost::is_deleted)
+                    else {
+                        let _ = restored.send(false);
+                        continue;
+                    };
+                    let post = archived.post;
+                    posts.insert(id, post.clone());
+                    log.changed(id);
+                    let _ = restored.send(true);
+                    audit(id, Some(by), AuditAction::Restored).await;
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Post(id, post)))
+                        .await;
+                    if let Some(post_reactions) = reactions.get(&id) {
+                        let _ = ws_hub
+                            .send(WsHubMsg::BroadcastAll(ServerToClient::Reactions(
+                                id,
+                                post_reactions.counts(),
+                            )))
+                            .await;
+                    }
+                    save_posts(&storage, &posts, &log).await;
+                    let _ = storage.send(StorageMsg::SaveArchive(archive.clone())).await;
+                }
+                PostsMsg::Purge(before, purged) => {
+                    let expired = archive
+                        .iter()
+                        .filter(|(_, archived)| {
+                            archived.is_deleted() && archived.removed_at < before
+                        })
+                        .map(|(id, _)| *id)
+                        .collect::<Vec<_>>();
+                    if !expired.is_empty() {
+                        for id in &expired {
+                            archive.remove(id);
+                            reactions.remove(id);
+                        }
+                        let _ = storage.send(StorageMsg::SaveArchive(archive.clone())).await;
+                        let _ = storage
+                            .send(StorageMsg::SaveReactions(reactions.clone()))
+                            .await;
+                    }
+                    let _ = purged.send(expired);
+                }
+                PostsMsg::GetArchived(id, reply) => {
+                    let _ = reply.send(archive.get(&id).cloned());
+                }
+                PostsMsg::PracticeStats(user, reply) => {
+                    let now = Utc::now();
+                    let held = posts.iter().filter(|(_, post)| post.date < now);
+                    let expired = archive
+                        .iter()
+                        .filter(|(_, archived)| archived.removal == Removal::Expired)
+                        .map(|(id, archived)| (id, &archived.post));
+                    let past = held.chain(expired).map(|(id, post)| (*id, post));
+                    let _ = reply.send(practice_stats(user, past, convert_utc_to_local_date(now)));
+                }
+                PostsMsg::ListArchive(reply) => {
+                    let list = archive
+                        .iter()
+                        .map(|(id, archived)| (*id, archived.clone()))
+                        .collect();
+                    let _ = reply.send(list);
                 }
                 PostsMsg::Get(id, reply) => {
                     let result = posts.get(&id).cloned();
@@ -53,38 +285,150 @@ pub fn spawn_posts_actor(
                     let list = posts.iter().map(|(id, post)| (*id, post.clone())).collect();
                     let _ = reply.send(list);
                 }
-                PostsMsg::New(post, sender) => {
+                PostsMsg::New(mut post, sender) => {
+                    sanitize(&mut post);
                     let id = PostId::new();
                     posts.insert(id, post.clone());
+                    log.changed(id);
                     let _ = sender.send(id);
+                    version(id, &post).await;
+                    audit(id, Some(post.owner), AuditAction::Created).await;
+                    notify(PostEvent::Created(id, post.clone())).await;
                     let _ = ws_hub
                         .send(WsHubMsg::BroadcastAll(ServerToClient::Post(id, post)))
                         .await;
-                    let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+                    save_posts(&storage, &posts, &log).await;
                 }
-                PostsMsg::UserJoins(post_id, user) => {
-                    if let Some(post) = posts.get_mut(&post_id) {
-                        post.partaking_users.insert(user);
-                        let _ = ws_hub
-                            .send(WsHubMsg::BroadcastAll(ServerToClient::Post(
-                                post_id,
-                                post.clone(),
-                            )))
-                            .await;
-                        let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+                PostsMsg::UserJoins(post_id, user, role, before) => {
+                    let Some(post) = posts.get_mut(&post_id) else {
+                        let _ = before.send(None);
+                        continue;
+                    };
+                    let membership = Membership::of(post, &user);
+                    let _ = before.send(Some(membership));
+                    let role = role
+                        .or_else(|| post.roles.get(&user).copied())
+                        .unwrap_or_default();
+                    if membership != Membership::Outside && post.roles.get(&user) == Some(&role) {
+                        continue;
+                    }
+                    post.roles.insert(user, role);
+                    let already_joined = membership == Membership::Partaking;
+                    let joined = post.join(user);
+                    let action = if joined {
+                        AuditAction::Joined(role)
+                    } else {
+                        AuditAction::Waitlisted(role)
+                    };
+                    log.changed(post_id);
+                    audit(post_id, Some(user), action).await;
+                    if joined && !already_joined {
+                        notify(PostEvent::Joined(post_id, post.clone(), user)).await;
                     }
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Post(
+                            post_id,
+                            post.clone(),
+                        )))
+                        .await;
+                    save_posts(&storage, &posts, &log).await;
                 }
-                PostsMsg::UserLeaves(post_id, user) => {
-                    if let Some(post) = posts.get_mut(&post_id) {
-                        post.partaking_users.remove(&user);
-                        let _ = ws_hub
-                            .send(WsHubMsg::BroadcastAll(ServerToClient::Post(
-                                post_id,
-                                post.clone(),
-                            )))
-                            .await;
-                        let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+                PostsMsg::ToggleReaction(post_id, user, reaction) => {
+                    if !posts.contains_key(&post_id) {
+                        continue;
+                    }
+                    let post_reactions = reactions.entry(post_id).or_default();
+                    post_reactions.toggle(user, reaction);
+                    let counts = post_reactions.counts();
+                    let mine = post_reactions.of(&user);
+                    if post_reactions.is_empty() {
+                        reactions.remove(&post_id);
                     }
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Reactions(
+                            post_id, counts,
+                        )))
+                        .await;
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastUser {
+                            user_id: user,
+                            msg: ServerToClient::MyReactions(post_id, mine),
+                        })
+                        .await;
+                    let _ = storage
+                        .send(StorageMsg::SaveReactions(reactions.clone()))
+                        .await;
+                }
+                PostsMsg::Reactions(user, reply) => {
+                    let list = reactions
+                        .iter()
+                        .map(|(id, post_reactions)| {
+                            (*id, post_reactions.counts(), post_reactions.of(&user))
+                        })
+                        .collect();
+                    let _ = reply.send(list);
+                }
+                PostsMsg::Sync(cursor, respond_to) => {
+                    let pick = |ids: Vec<PostId>| {
+                        ids.into_iter()
+                            .filter_map(|id| posts.get(&id).map(|post| (id, post.clone())))
+                            .collect()
+                    };
+                    let sync = match log.since(cursor) {
+                        Changes::Full => PostsSync {
+                            full_resync: true,
+                            posts: pick(posts.keys().copied().collect()),
+                            removed: Vec::new(),
+                            cursor: Some(log.cursor()),
+                        },
+                        Changes::Since { changed, removed } => PostsSync {
+                            full_resync: false,
+                            posts: pick(changed),
+                            removed,
+                            cursor: Some(log.cursor()),
+                        },
+                    };
+                    let _ = respond_to.send(sync);
+                }
+                PostsMsg::Search {
+                    query,
+                    user,
+                    levels,
+                    topics,
+                    respond_to,
+                } => {
+                    let _ = respond_to.send(search(&stems, &posts, &query, user, &levels, &topics));
+                }
+                PostsMsg::UserLeaves(post_id, user, left) => {
+                    let Some(post) = posts.get_mut(&post_id) else {
+                        let _ = left.send(None);
+                        continue;
+                    };
+                    let before = Membership::of(post, &user);
+                    if before == Membership::Outside {
+                        let _ = left.send(Some(Left {
+                            before,
+                            promoted: Vec::new(),
+                        }));
+                        continue;
+                    }
+                    let moved_up = post.leave(&user);
+                    let _ = left.send(Some(Left {
+                        before,
+                        promoted: moved_up.clone(),
+                    }));
+                    log.changed(post_id);
+                    audit(post_id, Some(user), AuditAction::Left).await;
+                    for promoted in moved_up {
+                        audit(post_id, None, AuditAction::Promoted(promoted)).await;
+                    }
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Post(
+                            post_id,
+                            post.clone(),
+                        )))
+                        .await;
+                    save_posts(&storage, &posts, &log).await;
                 }
             }
         }
@@ -93,7 +437,23 @@ pub fn spawn_posts_actor(
     tx
 }
 
-async fn setup(storage: &Sender<StorageMsg>, posts: &mut HashMap<PostId, Post>) {
+async fn save_posts(storage: &Sender<StorageMsg>, posts: &HashMap<PostId, Post>, log: &PostLog) {
+    let _ = storage.send(StorageMsg::SavePostLog(log.clone())).await;
+    let _ = storage.send(StorageMsg::SavePosts(posts.clone())).await;
+}
+
+fn sanitize(post: &mut Post) {
+    post.capacity = post.capacity.map(|capacity| capacity.max(1));
+    let mut seen = HashSet::new();
+    post.topics.retain(|topic| seen.insert(*topic));
+}
+
+async fn setup(
+    storage: &Sender<StorageMsg>,
+    posts: &mut HashMap<PostId, Post>,
+    archive: &mut HashMap<PostId, ArchivedPost>,
+    reactions: &mut HashMap<PostId, PostReactions>,
+) -> PostLog {
     let (respond_to, recv) = oneshot::channel();
     let _ = storage.send(StorageMsg::RetrievePosts { respond_to }).await;
 
@@ -102,4 +462,125 @@ async fn setup(storage: &Sender<StorageMsg>, posts: &mut HashMap<PostId, Post>)
             posts.insert(id, post);
         })
     }
+
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrievePostLog { respond_to })
+        .await;
+    let mut log = recv.await.ok().flatten().unwrap_or_default();
+    // Posts from before the log was saved are new to every client.
+    posts
+        .keys()
+        .filter(|id| !log.knows(id))
+        .copied()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .for_each(|id| log.changed(id));
+
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveArchive { respond_to })
+        .await;
+    if let Ok(snapshot) = recv.await {
+        archive.extend(snapshot);
+    }
+
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveReactions { respond_to })
+        .await;
+    if let Ok(snapshot) = recv.await {
+        reactions.extend(
+            snapshot
+                .into_iter()
+                .filter(|(id, _)| posts.contains_key(id) || archive.contains_key(id)),
+        );
+    }
+
+    log
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::spawn_storage_actor;
+
+    async fn reactions_of(
+        posts: &Sender<PostsMsg>,
+        user: UserId,
+    ) -> Vec<(PostId, ReactionCounts, Vec<Reaction>)> {
+        let (respond_to, recv) = oneshot::channel();
+        posts
+            .send(PostsMsg::Reactions(user, respond_to))
+            .await
+            .unwrap();
+        recv.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn reactions_are_counted_per_post_and_kept() {
+        let dir = std::env::temp_dir().join(format!("posts-{}", uuid::Uuid::new_v4()));
+        let storage = spawn_storage_actor(dir.clone());
+        let (ws_hub, mut hub) = mpsc::channel(100);
+        let (history, _history_rx) = mpsc::channel(100);
+        let (notifications, _notifications_rx) = mpsc::channel(100);
+        let posts = spawn_posts_actor(
+            storage.clone(),
+            ws_hub.clone(),
+            history.clone(),
+            notifications.clone(),
+        );
+        let (owner, fan) = (UserId::new(), UserId::new());
+
+        let (respond_to, recv) = oneshot::channel();
+        posts
+            .send(PostsMsg::New(Post::sample(owner, Utc::now()), respond_to))
+            .await
+            .unwrap();
+        let post = recv.await.unwrap();
+        for (user, reaction) in [
+            (owner, Reaction::Interested),
+            (fan, Reaction::Interested),
+            (fan, Reaction::Fire),
+            (fan, Reaction::Fire),
+        ] {
+            posts
+                .send(PostsMsg::ToggleReaction(post, user, reaction))
+                .await
+                .unwrap();
+        }
+        posts
+            .send(PostsMsg::ToggleReaction(PostId::new(), fan, Reaction::Love))
+            .await
+            .unwrap();
+
+        let expected = ReactionCounts::from([(Reaction::Interested, 2)]);
+        assert_eq!(
+            reactions_of(&posts, fan).await,
+            [(post, expected.clone(), vec![Reaction::Interested])]
+        );
+
+        let mut mine = Vec::new();
+        let mut last_counts = None;
+        while let Ok(msg) = hub.try_recv() {
+            match msg {
+                WsHubMsg::BroadcastAll(ServerToClient::Reactions(id, counts)) => {
+                    last_counts = Some((id, counts));
+                }
+                WsHubMsg::BroadcastUser {
+                    user_id,
+                    msg: ServerToClient::MyReactions(id, reactions),
+                } => mine.push((user_id, id, reactions)),
+                _ => {}
+            }
+        }
+        assert_eq!(last_counts, Some((post, expected.clone())));
+        assert_eq!(mine.len(), 4);
+        assert_eq!(mine[3], (fan, post, vec![Reaction::Interested]));
+
+        let restarted = spawn_posts_actor(storage, ws_hub, history, notifications);
+        let reloaded = reactions_of(&restarted, owner).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloaded, [(post, expected, vec![Reaction::Interested])]);
+    }
 }
@@ -0,0 +1,493 @@
+use crate::storage::StorageMsg;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{Duration, Utc};
+use eyre::{WrapErr, bail};
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use p256::SecretKey;
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey, LineEnding};
+use peer_practice_messages::current::notification::PushSubscription;
+use peer_practice_messages::current::user::UserId;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{StatusCode, Url};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+/// How long a push service keeps a message for an offline browser.
+const TTL_SECONDS: u32 = 24 * 60 * 60;
+
+/// Browsers kept per user; the oldest subscription goes first.
+const MAX_SUBSCRIPTIONS_PER_USER: usize = 10;
+
+/// The server's VAPID key pair, identifying it to push services.
+#[derive(Clone)]
+pub struct VapidKey {
+    secret: SecretKey,
+}
+
+impl VapidKey {
+    pub fn generate() -> Self {
+        Self {
+            secret: SecretKey::random(&mut rand_core::OsRng),
+        }
+    }
+
+    /// Reads the PKCS#8 PEM key at `path`, creating it first if missing.
+    pub fn load_or_create(path: &Path) -> eyre::Result<Self> {
+        if path.exists() {
+            let pem = std::fs::read_to_string(path)
+                .wrap_err_with(|| format!("Could not read VAPID key {}", path.display()))?;
+            let secret = SecretKey::from_pkcs8_pem(&pem)
+                .map_err(|err| eyre::eyre!("Invalid VAPID key {}: {err}", path.display()))?;
+            return Ok(Self { secret });
+        }
+
+        let key = Self::generate();
+        let pem = key
+            .secret
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|err| eyre::eyre!("Could not encode VAPID key: {err}"))?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(path)
+            .and_then(|mut file| file.write_all(pem.as_bytes()))
+            .wrap_err_with(|| format!("Could not write VAPID key {}", path.display()))?;
+        info!("Created VAPID key {}", path.display());
+        Ok(key)
+    }
+
+    /// The uncompressed public key as browsers expect it for
+    /// `applicationServerKey`.
+    pub fn public_key(&self) -> String {
+        let point = self.secret.public_key().to_encoded_point(false);
+        URL_SAFE_NO_PAD.encode(point.as_bytes())
+    }
+
+    /// The `Authorization` header for a push to `endpoint`.
+    fn authorization(&self, endpoint: &str, subject: &str) -> eyre::Result<String> {
+        #[derive(Serialize)]
+        struct Claims<'a> {
+            aud: String,
+            exp: i64,
+            sub: &'a str,
+        }
+
+        let audience = reqwest::Url::parse(endpoint)?
+            .origin()
+            .ascii_serialization();
+        let claims = Claims {
+            aud: audience,
+            exp: (Utc::now() + Duration::hours(12)).timestamp(),
+            sub: subject,
+        };
+        let der = self
+            .secret
+            .to_pkcs8_der()
+            .map_err(|err| eyre::eyre!("Could not encode VAPID key: {err}"))?;
+        let token = encode(
+            &Header::new(Algorithm::ES256),
+            &claims,
+            &EncodingKey::from_ec_der(der.as_bytes()),
+        )?;
+        Ok(format!("vapid t={token}, k={}", self.public_key()))
+    }
+}
+
+/// What the service worker shows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushMessage {
+    pub title: String,
+    pub body: String,
+    /// Opened when the notification is clicked.
+    pub url: String,
+}
+
+#[derive(Debug)]
+pub enum PushMsg {
+    Subscribe(UserId, PushSubscription),
+    /// Drops the subscription with this endpoint.
+    Unsubscribe(UserId, String),
+    Send {
+        to: UserId,
+        message: PushMessage,
+    },
+}
+
+/// Keeps the Web Push subscriptions of each user and pushes to all of them.
+pub fn spawn_push_actor(
+    storage: Sender<StorageMsg>,
+    key: VapidKey,
+    subject: String,
+) -> Sender<PushMsg> {
+    let (tx, mut rx) = mpsc::channel::<PushMsg>(100);
+    let unsubscribe = tx.clone();
+
+    tokio::spawn(async move {
+        let mut subscriptions = setup(&storage).await;
+        let client = match reqwest::Client::builder()
+            .dns_resolver(std::sync::Arc::new(PublicResolver))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                error!("Could not build push client: {:?}", err);
+                return;
+            }
+        };
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                PushMsg::Subscribe(user, subscription) => {
+                    if let Err(err) = check_endpoint(&subscription.endpoint) {
+                        error!(user_id = ?user, "refused push subscription: {err:#}");
+                        continue;
+                    }
+                    let own = subscriptions.entry(user).or_default();
+                    own.retain(|existing| existing.endpoint != subscription.endpoint);
+                    own.push(subscription);
+                    if own.len() > MAX_SUBSCRIPTIONS_PER_USER {
+                        let excess = own.len() - MAX_SUBSCRIPTIONS_PER_USER;
+                        own.drain(..excess);
+                    }
+                    let _ = storage
+                        .send(StorageMsg::SavePushSubscriptions(subscriptions.clone()))
+                        .await;
+                }
+                PushMsg::Unsubscribe(user, endpoint) => {
+                    let Some(own) = subscriptions.get_mut(&user) else {
+                        continue;
+                    };
+                    let before = own.len();
+                    own.retain(|existing| existing.endpoint != endpoint);
+                    if own.len() == before {
+                        continue;
+                    }
+                    if own.is_empty() {
+                        subscriptions.remove(&user);
+                    }
+                    let _ = storage
+                        .send(StorageMsg::SavePushSubscriptions(subscriptions.clone()))
+                        .await;
+                }
+                PushMsg::Send { to, message } => {
+                    let payload = match serde_json::to_vec(&message) {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            error!("Could not serialize push message: {:?}", err);
+                            continue;
+                        }
+                    };
+                    // Pushes go out in the background, so a slow push service
+                    // does not hold up subscriptions.
+                    for subscription in subscriptions.get(&to).cloned().unwrap_or_default() {
+                        let client = client.clone();
+                        let key = key.clone();
+                        let subject = subject.clone();
+                        let payload = payload.clone();
+                        let unsubscribe = unsubscribe.clone();
+                        tokio::spawn(async move {
+                            let delivery = deliver(
+                                check_endpoint,
+                                &client,
+                                &key,
+                                &subject,
+                                &subscription,
+                                &payload,
+                            );
+                            match delivery.await {
+                                Ok(Delivery::Sent) => {}
+                                Ok(Delivery::Gone) => {
+                                    info!(user_id = ?to, "push subscription expired");
+                                    let _ = unsubscribe
+                                        .send(PushMsg::Unsubscribe(to, subscription.endpoint))
+                                        .await;
+                                }
+                                Err(err) => error!("Failed to push to {:?}: {:?}", to, err),
+                            }
+                        });
+                    }
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+/// Decides which endpoints pushes may go to.
+type EndpointCheck = fn(&str) -> eyre::Result<()>;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Delivery {
+    Sent,
+    /// The browser unsubscribed, the subscription should be dropped.
+    Gone,
+}
+
+/// Encrypts `payload` for `subscription` and posts it to its push service.
+async fn deliver(
+    check: EndpointCheck,
+    client: &reqwest::Client,
+    key: &VapidKey,
+    subject: &str,
+    subscription: &PushSubscription,
+    payload: &[u8],
+) -> eyre::Result<Delivery> {
+    check(&subscription.endpoint)?;
+    let decode = |value: &str| URL_SAFE_NO_PAD.decode(value.trim_end_matches('='));
+    let p256dh = decode(&subscription.keys.p256dh).wrap_err("Invalid p256dh key")?;
+    let auth = decode(&subscription.keys.auth).wrap_err("Invalid auth secret")?;
+    let body = ece::encrypt(&p256dh, &auth, payload)?;
+
+    let response = client
+        .post(&subscription.endpoint)
+        .header("TTL", TTL_SECONDS.to_string())
+        .header("Content-Encoding", "aes128gcm")
+        .header("Content-Type", "application/octet-stream")
+        .header(
+            "Authorization",
+            key.authorization(&subscription.endpoint, subject)?,
+        )
+        .body(body)
+        .send()
+        .await?;
+
+    match response.status() {
+        status if status.is_success() => Ok(Delivery::Sent),
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(Delivery::Gone),
+        status => bail!(
+            "Push service answered {status}: {}",
+            response.text().await.unwrap_or_default()
+        ),
+    }
+}
+
+/// Push services are public HTTPS endpoints. Anything else would let clients
+/// make the server send requests into its own network.
+pub fn check_endpoint(endpoint: &str) -> eyre::Result<()> {
+    let url = Url::parse(endpoint).wrap_err("Invalid push endpoint")?;
+    if url.scheme() != "https" {
+        bail!("Push endpoints must use https.");
+    }
+    let Some(host) = url.host_str() else {
+        bail!("Push endpoints need a host.");
+    };
+    let public = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_public(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    };
+    if !public {
+        bail!("Push endpoints must be public hosts.");
+    }
+    Ok(())
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // Carrier-grade NAT, 100.64.0.0/10.
+        || (a == 100 && (64..128).contains(&b))
+        || a == 0)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local, fc00::/7.
+        || (first & 0xfe00) == 0xfc00
+        // Link local, fe80::/10.
+        || (first & 0xffc0) == 0xfe80)
+}
+
+/// Resolves push service hosts, refusing names that point into a private
+/// network so a public-looking endpoint cannot reach internal hosts either.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .collect::<Vec<_>>();
+            if addrs.iter().any(|addr| !is_public(addr.ip())) {
+                return Err(format!("{} resolves to a private address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+async fn setup(storage: &Sender<StorageMsg>) -> HashMap<UserId, Vec<PushSubscription>> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrievePushSubscriptions { respond_to })
+        .await;
+    recv.await.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peer_practice_messages::current::notification::PushKeys;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Accepts one request, answers with `status` and returns the request's
+    /// head and body.
+    async fn mock_push_service(
+        status: &'static str,
+    ) -> (String, tokio::task::JoinHandle<(String, Vec<u8>)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/push/abc", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let head_end = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                if let Some(at) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                    break at + 4;
+                }
+            };
+            let head = String::from_utf8_lossy(&request[..head_end]).to_lowercase();
+            let length = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .and_then(|length| length.trim().parse::<usize>().ok())
+                .unwrap_or_default();
+            while request.len() < head_end + length {
+                let read = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            socket
+                .write_all(format!("HTTP/1.1 {status}\r\ncontent-length: 0\r\n\r\n").as_bytes())
+                .await
+                .unwrap();
+            (head, request[head_end..].to_vec())
+        });
+        (endpoint, handle)
+    }
+
+    /// Lets pushes reach the mock push service on localhost.
+    fn mock_only(endpoint: &str) -> eyre::Result<()> {
+        match Url::parse(endpoint)?.host_str() {
+            Some("127.0.0.1") => Ok(()),
+            _ => bail!("Not the mock push service."),
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_encrypted_payload_and_notices_expired_subscriptions() {
+        let (browser, auth) = ece::generate_keypair_and_auth_secret().unwrap();
+        let subscription = |endpoint: String| PushSubscription {
+            endpoint,
+            keys: PushKeys {
+                p256dh: URL_SAFE_NO_PAD.encode(browser.pub_as_raw().unwrap()),
+                auth: URL_SAFE_NO_PAD.encode(auth),
+            },
+        };
+        let key = VapidKey::generate();
+        let client = reqwest::Client::new();
+
+        let (endpoint, service) = mock_push_service("201 Created").await;
+        let delivery = deliver(
+            mock_only,
+            &client,
+            &key,
+            "mailto:admin@example.com",
+            &subscription(endpoint),
+            b"hello",
+        )
+        .await
+        .unwrap();
+        let (head, body) = service.await.unwrap();
+        assert_eq!(delivery, Delivery::Sent);
+        assert!(head.contains("content-encoding: aes128gcm"));
+        assert!(head.contains(&format!("k={}", key.public_key()).to_lowercase()));
+        let components = browser.raw_components().unwrap();
+        assert_eq!(ece::decrypt(&components, &auth, &body).unwrap(), b"hello");
+
+        let (endpoint, service) = mock_push_service("410 Gone").await;
+        let delivery = deliver(
+            mock_only,
+            &client,
+            &key,
+            "mailto:admin@example.com",
+            &subscription(endpoint),
+            b"hello",
+        )
+        .await
+        .unwrap();
+        service.await.unwrap();
+        assert_eq!(delivery, Delivery::Gone);
+
+        let (endpoint, service) = mock_push_service("201 Created").await;
+        let refused = deliver(
+            check_endpoint,
+            &client,
+            &key,
+            "mailto:admin@example.com",
+            &subscription(endpoint),
+            b"hello",
+        )
+        .await;
+        assert!(refused.is_err());
+        service.abort();
+    }
+
+    #[test]
+    fn only_public_https_endpoints_are_accepted() {
+        assert!(check_endpoint("https://fcm.googleapis.com/fcm/send/abc").is_ok());
+        assert!(check_endpoint("https://[2a00:1450::1]/push").is_ok());
+        for endpoint in [
+            "http://fcm.googleapis.com/fcm/send/abc",
+            "https://127.0.0.1:8080/push",
+            "https://localhost/push",
+            "https://10.0.0.5/push",
+            "https://192.168.1.1/push",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/push",
+            "https://[::ffff:127.0.0.1]/push",
+            "https://[fd00::1]/push",
+            "not a url",
+        ] {
+            assert!(check_endpoint(endpoint).is_err(), "{endpoint}");
+        }
+    }
+}
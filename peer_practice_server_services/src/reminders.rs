@@ -0,0 +1,126 @@
+use chrono::{DateTime, Duration, Local, Utc};
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::user::{User, UserId};
+use peer_practice_messages::current::ymd::practice_start;
+use serde::{Deserialize, Serialize};
+
+/// A reminder that went out, so it is not sent again. The post's date is part
+/// of it, so a rescheduled post is reminded of anew.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SentReminder {
+    pub post: PostId,
+    pub user: UserId,
+    pub date: DateTime<Utc>,
+}
+
+/// When the practice evening of `post` starts.
+pub fn starts_at(post: &Post) -> DateTime<Utc> {
+    convert_utc_to_local_date(post.date)
+        .and_time(practice_start())
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or(post.date)
+}
+
+/// The reminders due at `now`: every user who joined a post that starts
+/// within their chosen reminder time and has not started yet.
+pub fn due_reminders(
+    posts: &[(PostId, Post)],
+    users: &[User],
+    now: DateTime<Utc>,
+) -> Vec<SentReminder> {
+    posts
+        .iter()
+        .flat_map(|(id, post)| {
+            let start = starts_at(post);
+            users
+                .iter()
+                .filter(move |user| post.partaking_users.contains(&user.id))
+                .filter(move |user| {
+                    let preferences = &user.config.notifications;
+                    // Reminder times past chrono's range remind right away.
+                    let from = Duration::try_hours(preferences.reminder_hours_before.into())
+                        .and_then(|before| start.checked_sub_signed(before));
+                    preferences.reminder.any() && from.is_none_or(|from| from <= now) && now < start
+                })
+                .map(move |user| SentReminder {
+                    post: *id,
+                    user: user.id,
+                    date: post.date,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use peer_practice_messages::current::email::Email;
+    use peer_practice_messages::current::notification::Channels;
+    use std::collections::HashSet;
+
+    fn user(name: &str, hours_before: u32, reminder: Channels) -> User {
+        let mut user = User {
+            email: Email::new(&format!("{name}@example.com")).unwrap(),
+            display_name: Some(name.to_string()),
+            id: UserId::new(),
+            calendar_token: None,
+            config: Default::default(),
+            level: None,
+        };
+        user.config.notifications.reminder = reminder;
+        user.config.notifications.reminder_hours_before = hours_before;
+        user
+    }
+
+    #[test]
+    fn reminds_joined_users_within_their_reminder_time() {
+        let push = Channels {
+            push: true,
+            ..Channels::default()
+        };
+        let early = user("early", 48, push);
+        let late = user("late", 2, push);
+        let silent = user("silent", 48, Channels::default());
+        let bystander = user("bystander", 48, push);
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let post = Post {
+            partaking_users: HashSet::from([early.id, late.id, silent.id]),
+            ..Post::sample(
+                early.id,
+                peer_practice_messages::current::convert_to_utc(date),
+            )
+        };
+        let posts = vec![(PostId::new(), post.clone())];
+        let users = vec![early.clone(), late.clone(), silent, bystander];
+        let due = |hours_before_start: i64| {
+            due_reminders(
+                &posts,
+                &users,
+                starts_at(&post) - Duration::hours(hours_before_start),
+            )
+            .into_iter()
+            .map(|reminder| reminder.user)
+            .collect::<HashSet<_>>()
+        };
+
+        assert_eq!(due(72), HashSet::new());
+        assert_eq!(due(24), HashSet::from([early.id]));
+        assert_eq!(due(1), HashSet::from([early.id, late.id]));
+        assert_eq!(due(-1), HashSet::new());
+
+        // Reminder times beyond chrono's range must not panic the job.
+        let eager = user("eager", u32::MAX, push);
+        let mut far_ahead = post.clone();
+        far_ahead.partaking_users = HashSet::from([eager.id]);
+        let reminders = due_reminders(
+            &[(PostId::new(), far_ahead)],
+            &[eager],
+            starts_at(&post) - Duration::days(365),
+        );
+        assert_eq!(reminders.len(), 1);
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::Duration;
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::dance_role::DanceRole;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::rotation::{
+    PracticePair, RotationRound, RotationSchedule, RotationSettings,
+};
+use peer_practice_messages::current::user::UserId;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Plans who practices with whom in each round of an evening.
+///
+/// Partners are only paired within a post they both joined, so every pair
+/// shares the post's topic and level. Each user dances at most once per round,
+/// roles are honored (users dancing either role fill whichever side is
+/// needed), and pairs that already met are only chosen when no new pairing is
+/// possible.
+pub fn plan_rotation(posts: &[(PostId, Post)], settings: RotationSettings) -> RotationSchedule {
+    let mut evening = posts
+        .iter()
+        .filter(|(_, post)| convert_utc_to_local_date(post.date) == settings.date)
+        .collect::<Vec<_>>();
+    evening.sort_by(|(a_id, a), (b_id, b)| {
+        a.topics
+            .cmp(&b.topics)
+            .then_with(|| a_id.to_string().cmp(&b_id.to_string()))
+    });
+
+    let attendees = evening
+        .iter()
+        .flat_map(|(_, post)| post.partaking_users.iter().copied())
+        .collect::<BTreeSet<_>>();
+
+    let mut met: HashMap<(UserId, UserId), u32> = HashMap::new();
+    let mut danced: HashMap<UserId, u32> = HashMap::new();
+    let mut rounds = Vec::with_capacity(settings.rounds as usize);
+
+    for round in 0..settings.rounds {
+        let mut busy = HashSet::new();
+        let mut pairs = Vec::new();
+
+        // Start with a different post every round so no topic always gets
+        // first pick of the shared attendees.
+        let offset = if evening.is_empty() {
+            0
+        } else {
+            round as usize % evening.len()
+        };
+        for (post_id, post) in evening[offset..].iter().chain(&evening[..offset]) {
+            let mut candidates = candidate_pairs(post);
+            candidates.sort_by_key(|(lead, follow)| {
+                (
+                    met.get(&pair_key(*lead, *follow)).copied().unwrap_or(0),
+                    danced.get(lead).copied().unwrap_or(0)
+                        + danced.get(follow).copied().unwrap_or(0),
+                    *lead,
+                    *follow,
+                )
+            });
+
+            for (lead, follow) in candidates {
+                if busy.contains(&lead) || busy.contains(&follow) {
+                    continue;
+                }
+                busy.insert(lead);
+                busy.insert(follow);
+                *met.entry(pair_key(lead, follow)).or_default() += 1;
+                *danced.entry(lead).or_default() += 1;
+                *danced.entry(follow).or_default() += 1;
+                pairs.push(PracticePair {
+                    post: *post_id,
+                    topics: post.topics.clone(),
+                    lead,
+                    follow,
+                });
+            }
+        }
+
+        rounds.push(RotationRound {
+            start: settings.start
+                + Duration::minutes(i64::from(round) * i64::from(settings.round_minutes)),
+            pairs,
+            sitting_out: attendees
+                .iter()
+                .filter(|user| !busy.contains(*user))
+                .copied()
+                .collect(),
+        });
+    }
+
+    RotationSchedule { settings, rounds }
+}
+
+fn candidate_pairs(post: &Post) -> Vec<(UserId, UserId)> {
+    let can_lead = |user: &UserId| post.role_of(user) != DanceRole::Follow;
+    let can_follow = |user: &UserId| post.role_of(user) != DanceRole::Lead;
+
+    let mut candidates = Vec::new();
+    for lead in post.partaking_users.iter().filter(|user| can_lead(user)) {
+        for follow in post.partaking_users.iter().filter(|user| can_follow(user)) {
+            if lead != follow {
+                candidates.push((*lead, *follow));
+            }
+        }
+    }
+    candidates
+}
+
+fn pair_key(a: UserId, b: UserId) -> (UserId, UserId) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+    use peer_practice_messages::current::convert_to_utc;
+    use peer_practice_messages::current::level::{Level, LevelRange};
+    use peer_practice_messages::current::topic::LegacyTopic;
+
+    fn settings() -> RotationSettings {
+        RotationSettings::new(NaiveDate::from_ymd_opt(2026, 3, 13).unwrap())
+    }
+
+    fn post(topic: LegacyTopic, users: &[(UserId, DanceRole)]) -> (PostId, Post) {
+        let post = Post {
+            topics: vec![topic.into()],
+            level: LevelRange::single(Level::new("Club")),
+            partaking_users: users.iter().map(|(user, _)| *user).collect(),
+            roles: users.iter().copied().collect(),
+            ..Post::sample(users[0].0, convert_to_utc(settings().date))
+        };
+        (PostId::new(), post)
+    }
+
+    #[test]
+    fn avoids_repeat_pairings_and_double_booking() {
+        let leads = [UserId::new(), UserId::new()];
+        let follows = [UserId::new(), UserId::new()];
+        let users = leads
+            .iter()
+            .map(|user| (*user, DanceRole::Lead))
+            .chain(follows.iter().map(|user| (*user, DanceRole::Follow)))
+            .collect::<Vec<_>>();
+        let posts = [
+            post(LegacyTopic::Anchor, &users),
+            post(LegacyTopic::Spins, &users),
+        ];
+
+        let schedule = plan_rotation(&posts, settings());
+
+        let mut seen = HashSet::new();
+        for round in schedule.rounds.iter().take(2) {
+            assert_eq!(round.pairs.len(), 2);
+            assert!(round.sitting_out.is_empty());
+            let mut dancing = HashSet::new();
+            for pair in &round.pairs {
+                assert!(leads.contains(&pair.lead) && follows.contains(&pair.follow));
+                assert!(dancing.insert(pair.lead) && dancing.insert(pair.follow));
+                assert!(seen.insert((pair.lead, pair.follow)), "{pair:?} repeated");
+            }
+        }
+    }
+
+    #[test]
+    fn ignores_other_evenings_and_assigns_time_slots() {
+        let lead = UserId::new();
+        let follow = UserId::new();
+        let (id, mut other_evening) = post(
+            LegacyTopic::Blues,
+            &[(lead, DanceRole::Lead), (follow, DanceRole::Follow)],
+        );
+        other_evening.date = Utc::now() + Duration::days(400);
+
+        let schedule = plan_rotation(&[(id, other_evening)], settings());
+
+        assert_eq!(schedule.rounds.len(), 4);
+        assert!(schedule.rounds.iter().all(|round| round.pairs.is_empty()));
+        assert_eq!(
+            schedule.rounds[1].start,
+            settings().start + Duration::minutes(15)
+        );
+    }
+}
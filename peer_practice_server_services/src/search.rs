@@ -0,0 +1,178 @@
+use peer_practice_messages::current::level::Levels;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::search::PostQuery;
+use peer_practice_messages::current::topic::{Topic, TopicId};
+use peer_practice_messages::current::user::UserId;
+use rust_stemmers::{Algorithm, Stemmer};
+use std::collections::{HashMap, HashSet};
+
+/// Topic names weigh more than words in the ideas of a post.
+const TOPIC_WEIGHT: usize = 3;
+
+/// Reduces words to their German and English stems, so "Drehungen" finds
+/// "Drehung" and "spinning" finds "spins".
+pub struct Stems {
+    german: Stemmer,
+    english: Stemmer,
+}
+
+impl Default for Stems {
+    fn default() -> Self {
+        Self {
+            german: Stemmer::create(Algorithm::German),
+            english: Stemmer::create(Algorithm::English),
+        }
+    }
+}
+
+impl Stems {
+    /// The stems of every word in `text`, one set per word.
+    pub fn words(&self, text: &str) -> Vec<HashSet<String>> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let word = word.to_lowercase();
+                HashSet::from([
+                    self.german.stem(&word).into_owned(),
+                    self.english.stem(&word).into_owned(),
+                    word,
+                ])
+            })
+            .collect()
+    }
+}
+
+/// The posts matching `query` as seen by `user`, best match first and
+/// otherwise ordered by date.
+pub fn search(
+    stems: &Stems,
+    posts: &HashMap<PostId, Post>,
+    query: &PostQuery,
+    user: UserId,
+    levels: &Levels,
+    topics: &HashMap<TopicId, Topic>,
+) -> Vec<PostId> {
+    let terms = stems.words(&query.text);
+    let mut hits = posts
+        .iter()
+        .filter(|(_, post)| matches_filters(post, query, user, levels))
+        .filter_map(|(id, post)| {
+            let score = score(stems, post, &terms, topics)?;
+            Some((*id, post.date, score))
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.cmp(&b.1)));
+    hits.into_iter().map(|(id, _, _)| id).collect()
+}
+
+fn matches_filters(post: &Post, query: &PostQuery, user: UserId, levels: &Levels) -> bool {
+    query
+        .level
+        .as_ref()
+        .is_none_or(|level| levels.contains(&post.level, level))
+        && (query.topics.is_empty() || post.topics.iter().any(|t| query.topics.contains(t)))
+        && query.from.is_none_or(|from| post.date >= from)
+        && query.until.is_none_or(|until| post.date < until)
+        && query.owner.is_none_or(|owner| post.owner == owner)
+        && (!query.joined_by_me || post.partaking_users.contains(&user) || post.is_waiting(&user))
+}
+
+/// How well `post` matches all of `terms`, `None` if any term is missing.
+fn score(
+    stems: &Stems,
+    post: &Post,
+    terms: &[HashSet<String>],
+    topics: &HashMap<TopicId, Topic>,
+) -> Option<usize> {
+    if terms.is_empty() {
+        return Some(0);
+    }
+    let content = stems.words(&post.content);
+    let titles = stems.words(&post.title(topics));
+    let occurrences = |words: &[HashSet<String>], term: &HashSet<String>| {
+        words.iter().filter(|word| !word.is_disjoint(term)).count()
+    };
+
+    terms.iter().try_fold(0, |total, term| {
+        let found = occurrences(&titles, term) * TOPIC_WEIGHT + occurrences(&content, term);
+        (found > 0).then_some(total + found)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+    use peer_practice_messages::current::accent_colors::AccentColor;
+    use peer_practice_messages::current::level::{Level, LevelRange};
+
+    fn post(content: &str, topics: Vec<TopicId>, level: &str, days: i64) -> Post {
+        Post {
+            topics,
+            content: content.to_string(),
+            level: LevelRange::single(Level::new(level)),
+            partaking_users: HashSet::new(),
+            ..Post::sample(
+                UserId::new(),
+                DateTime::<Utc>::UNIX_EPOCH + Duration::days(days),
+            )
+        }
+    }
+
+    #[test]
+    fn finds_stemmed_words_in_both_languages() {
+        let spins = TopicId::new();
+        let topics = HashMap::from([(
+            spins,
+            Topic {
+                name: "Spins".to_string(),
+                color: AccentColor::Teal,
+                archived: false,
+            },
+        )]);
+        let (german, english, topic, other) =
+            (PostId::new(), PostId::new(), PostId::new(), PostId::new());
+        let posts = HashMap::from([
+            (
+                german,
+                post("Wir üben Drehungen im Sugar Push", vec![], "Level 1", 1),
+            ),
+            (english, post("Spinning and a turn", vec![], "Level 2", 2)),
+            (topic, post("Anchors", vec![spins], "Level 1", 3)),
+            (other, post("Whip", vec![], "Level 1", 0)),
+        ]);
+        let stems = Stems::default();
+        let levels = Levels::default();
+        let run =
+            |query: PostQuery| search(&stems, &posts, &query, UserId::new(), &levels, &topics);
+
+        assert_eq!(
+            run(PostQuery {
+                text: "Drehung".to_string(),
+                ..PostQuery::default()
+            }),
+            vec![german]
+        );
+        assert_eq!(
+            run(PostQuery {
+                text: "spin".to_string(),
+                ..PostQuery::default()
+            }),
+            vec![topic, english]
+        );
+        assert_eq!(
+            run(PostQuery {
+                level: Some(Level::new("Level 1")),
+                ..PostQuery::default()
+            }),
+            vec![other, german, topic]
+        );
+        assert!(
+            run(PostQuery {
+                text: "spin whip".to_string(),
+                ..PostQuery::default()
+            })
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{Datelike, Days, NaiveDate};
+use peer_practice_messages::current::convert_utc_to_local_date;
+use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::stats::{
+    PartnerCount, PastSession, PracticeStats, TopicMonth,
+};
+use peer_practice_messages::current::topic::TopicId;
+use peer_practice_messages::current::user::UserId;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Partners beyond this are left out of the statistics.
+const MAX_PARTNERS: usize = 10;
+
+/// Statistics of `user` over past sessions, counting those they partook in.
+pub fn practice_stats<'a>(
+    user: UserId,
+    past: impl IntoIterator<Item = (PostId, &'a Post)>,
+    today: NaiveDate,
+) -> PracticeStats {
+    let mut sessions = past
+        .into_iter()
+        .filter(|(_, post)| post.partaking_users.contains(&user))
+        .collect::<Vec<_>>();
+    sessions.sort_by_key(|(_, post)| std::cmp::Reverse(post.date));
+
+    let mut topics: BTreeMap<(NaiveDate, TopicId), usize> = BTreeMap::new();
+    let mut partners: HashMap<UserId, usize> = HashMap::new();
+    let mut weeks = BTreeSet::new();
+    for (_, post) in &sessions {
+        let day = convert_utc_to_local_date(post.date);
+        for topic in &post.topics {
+            *topics
+                .entry((day.with_day(1).unwrap(), *topic))
+                .or_default() += 1;
+        }
+        for partner in post.partaking_users.iter().filter(|other| **other != user) {
+            *partners.entry(*partner).or_default() += 1;
+        }
+        weeks.insert(week_of(day));
+    }
+
+    let mut partners = partners
+        .into_iter()
+        .map(|(partner, sessions)| PartnerCount { partner, sessions })
+        .collect::<Vec<_>>();
+    partners.sort_by(|a, b| b.sessions.cmp(&a.sessions).then(a.partner.cmp(&b.partner)));
+    partners.truncate(MAX_PARTNERS);
+
+    let (current_streak_weeks, longest_streak_weeks) = streaks(&weeks, week_of(today));
+
+    PracticeStats {
+        hosted: sessions
+            .iter()
+            .filter(|(_, post)| post.owner == user)
+            .count(),
+        sessions: sessions
+            .iter()
+            .map(|(id, post)| PastSession {
+                post: *id,
+                date: post.date,
+                topics: post.topics.clone(),
+                hosted: post.owner == user,
+                participants: post.partaking_users.len(),
+            })
+            .collect(),
+        topics_by_month: topics
+            .into_iter()
+            .map(|((month, topic), sessions)| TopicMonth {
+                month,
+                topic,
+                sessions,
+            })
+            .collect(),
+        partners,
+        current_streak_weeks,
+        longest_streak_weeks,
+    }
+}
+
+/// The Monday starting the week of `day`.
+fn week_of(day: NaiveDate) -> NaiveDate {
+    day - Days::new(day.weekday().num_days_from_monday().into())
+}
+
+/// The current and the longest run of consecutive weeks. The current run
+/// still counts if this week has no session yet.
+fn streaks(weeks: &BTreeSet<NaiveDate>, this_week: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for week in weeks {
+        run = match previous {
+            Some(previous) if previous + Days::new(7) == *week => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*week);
+    }
+    let current = match previous {
+        Some(last) if last + Days::new(7) >= this_week => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn session(owner: UserId, partaking: &[UserId], topics: Vec<TopicId>, day: NaiveDate) -> Post {
+        let date =
+            DateTime::<Utc>::from_naive_utc_and_offset(day.and_hms_opt(12, 0, 0).unwrap(), Utc);
+        Post {
+            topics,
+            partaking_users: partaking.iter().copied().collect(),
+            ..Post::sample(owner, date)
+        }
+    }
+
+    #[test]
+    fn counts_sessions_partners_and_streaks() {
+        let (me, anna, ben) = (UserId::new(), UserId::new(), UserId::new());
+        let whips = TopicId::new();
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+        let week = |n: i64| monday + Duration::weeks(n);
+        let posts = [
+            session(me, &[me, anna], vec![whips], week(0)),
+            session(anna, &[me, anna, ben], vec![whips], week(1)),
+            session(anna, &[anna, ben], vec![], week(2)),
+            session(ben, &[me, ben], vec![], week(4)),
+            session(me, &[me], vec![], week(5)),
+        ]
+        .into_iter()
+        .map(|post| (PostId::new(), post))
+        .collect::<Vec<_>>();
+        let past = || posts.iter().map(|(id, post)| (*id, post));
+
+        let stats = practice_stats(me, past(), week(6));
+
+        assert_eq!(stats.sessions.len(), 4);
+        assert_eq!(stats.sessions[0].date.date_naive(), week(5));
+        assert_eq!(stats.hosted, 2);
+        assert_eq!(
+            stats.topics_by_month,
+            vec![TopicMonth {
+                month: NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+                topic: whips,
+                sessions: 2,
+            }]
+        );
+        assert_eq!(stats.partners.len(), 2);
+        assert_eq!(stats.partners.iter().map(|p| p.sessions).sum::<usize>(), 4);
+        assert_eq!(stats.longest_streak_weeks, 2);
+        assert_eq!(stats.current_streak_weeks, 2);
+
+        let later = practice_stats(me, past(), week(7));
+        assert_eq!(later.current_streak_weeks, 0);
+    }
+}
@@ -1,9 +1,21 @@
+use crate::email_queue::QueuedEmail;
+use crate::reminders::SentReminder;
+use crate::sync::PostLog;
 use peer_practice_messages::Envelope;
+use peer_practice_messages::current::archive::ArchivedPost;
+use peer_practice_messages::current::comment::{Comment, CommentId};
+use peer_practice_messages::current::direct_message::{DirectMessage, DirectMessageId};
+use peer_practice_messages::current::history::{AuditEntry, PostVersion};
+use peer_practice_messages::current::notification::{
+    Notification, NotificationId, PushSubscription,
+};
 use peer_practice_messages::current::post::{Post, PostId};
+use peer_practice_messages::current::reaction::PostReactions;
+use peer_practice_messages::current::topic::{Topic, TopicId};
 use peer_practice_messages::current::user::{User, UserId};
 use serde::de::DeserializeOwned;
 use serde_json::{Value, json};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::path::PathBuf;
 use tokio::fs;
@@ -17,10 +29,60 @@ pub enum StorageMsg {
     RetrievePosts {
         respond_to: oneshot::Sender<HashMap<PostId, Post>>,
     },
+    SavePostLog(PostLog),
+    /// Replies with `None` if no log was ever saved.
+    RetrievePostLog {
+        respond_to: oneshot::Sender<Option<PostLog>>,
+    },
     SaveUsers(HashMap<UserId, User>),
     RetrieveUsers {
         respond_to: oneshot::Sender<HashMap<UserId, User>>,
     },
+    SaveArchive(HashMap<PostId, ArchivedPost>),
+    RetrieveArchive {
+        respond_to: oneshot::Sender<HashMap<PostId, ArchivedPost>>,
+    },
+    SavePostVersions(HashMap<PostId, Vec<PostVersion>>),
+    RetrievePostVersions {
+        respond_to: oneshot::Sender<HashMap<PostId, Vec<PostVersion>>>,
+    },
+    SaveAuditLog(Vec<AuditEntry>),
+    RetrieveAuditLog {
+        respond_to: oneshot::Sender<Vec<AuditEntry>>,
+    },
+    SaveReactions(HashMap<PostId, PostReactions>),
+    RetrieveReactions {
+        respond_to: oneshot::Sender<HashMap<PostId, PostReactions>>,
+    },
+    SaveComments(HashMap<CommentId, Comment>),
+    RetrieveComments {
+        respond_to: oneshot::Sender<HashMap<CommentId, Comment>>,
+    },
+    SaveDirectMessages(HashMap<DirectMessageId, DirectMessage>),
+    RetrieveDirectMessages {
+        respond_to: oneshot::Sender<HashMap<DirectMessageId, DirectMessage>>,
+    },
+    SaveNotifications(HashMap<NotificationId, Notification>),
+    RetrieveNotifications {
+        respond_to: oneshot::Sender<HashMap<NotificationId, Notification>>,
+    },
+    SaveReminders(HashSet<SentReminder>),
+    RetrieveReminders {
+        respond_to: oneshot::Sender<HashSet<SentReminder>>,
+    },
+    SavePushSubscriptions(HashMap<UserId, Vec<PushSubscription>>),
+    RetrievePushSubscriptions {
+        respond_to: oneshot::Sender<HashMap<UserId, Vec<PushSubscription>>>,
+    },
+    SaveEmailQueue(Vec<QueuedEmail>),
+    RetrieveEmailQueue {
+        respond_to: oneshot::Sender<Vec<QueuedEmail>>,
+    },
+    SaveTopics(HashMap<TopicId, Topic>),
+    /// Replies with `None` if no topics were ever saved.
+    RetrieveTopics {
+        respond_to: oneshot::Sender<Option<HashMap<TopicId, Topic>>>,
+    },
 }
 
 async fn save_snapshot(namespace: &str, data: &Value, work_dir: &Path) {
@@ -59,6 +121,13 @@ pub fn spawn_storage_actor(work_dir: PathBuf) -> mpsc::Sender<StorageMsg> {
                         .collect::<Vec<_>>();
                     save_snapshot("posts", &Value::Array(pairs), &work_dir).await;
                 }
+                StorageMsg::SavePostLog(log) => {
+                    save_snapshot("post_log", &json!(log), &work_dir).await;
+                }
+                StorageMsg::RetrievePostLog { respond_to } => {
+                    let value = load_snapshot("post_log", &work_dir).await;
+                    let _ = respond_to.send(serde_json::from_value::<PostLog>(value).ok());
+                }
                 StorageMsg::RetrievePosts { respond_to } => {
                     let mut posts = HashMap::new();
                     let value = load_snapshot("posts", &work_dir).await;
@@ -105,6 +174,195 @@ pub fn spawn_storage_actor(work_dir: PathBuf) -> mpsc::Sender<StorageMsg> {
 
                     let _ = respond_to.send(users);
                 }
+                StorageMsg::SaveArchive(archive) => {
+                    let pairs = archive
+                        .iter()
+                        .map(|(id, archived)| json!([id, archived]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("archive", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveArchive { respond_to } => {
+                    let mut archive = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("archive", &work_dir).await {
+                        archive.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(PostId, ArchivedPost)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(archive);
+                }
+                StorageMsg::SavePostVersions(versions) => {
+                    let pairs = versions
+                        .iter()
+                        .map(|(id, versions)| json!([id, versions]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("post_versions", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrievePostVersions { respond_to } => {
+                    let mut versions = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("post_versions", &work_dir).await {
+                        versions.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(PostId, Vec<PostVersion>)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(versions);
+                }
+                StorageMsg::SaveAuditLog(entries) => {
+                    save_snapshot("audit_log", &json!(entries), &work_dir).await;
+                }
+                StorageMsg::RetrieveAuditLog { respond_to } => {
+                    let mut log = Vec::new();
+                    if let Value::Array(entries) = load_snapshot("audit_log", &work_dir).await {
+                        log.extend(
+                            entries.into_iter().filter_map(|entry| {
+                                serde_json::from_value::<AuditEntry>(entry).ok()
+                            }),
+                        );
+                    }
+
+                    let _ = respond_to.send(log);
+                }
+                StorageMsg::SaveReactions(reactions) => {
+                    let pairs = reactions
+                        .iter()
+                        .map(|(id, reactions)| json!([id, reactions]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("reactions", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveReactions { respond_to } => {
+                    let mut reactions = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("reactions", &work_dir).await {
+                        reactions.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(PostId, PostReactions)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(reactions);
+                }
+                StorageMsg::SaveComments(comments) => {
+                    let pairs = comments
+                        .iter()
+                        .map(|(id, comment)| json!([id, comment]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("comments", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveComments { respond_to } => {
+                    let mut comments = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("comments", &work_dir).await {
+                        comments.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(CommentId, Comment)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(comments);
+                }
+                StorageMsg::SaveDirectMessages(messages) => {
+                    let pairs = messages
+                        .iter()
+                        .map(|(id, message)| json!([id, message]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("direct_messages", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveDirectMessages { respond_to } => {
+                    let mut messages = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("direct_messages", &work_dir).await
+                    {
+                        messages.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(DirectMessageId, DirectMessage)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(messages);
+                }
+                StorageMsg::SaveNotifications(notifications) => {
+                    let pairs = notifications
+                        .iter()
+                        .map(|(id, notification)| json!([id, notification]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("notifications", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveNotifications { respond_to } => {
+                    let mut notifications = HashMap::new();
+                    if let Value::Array(entries) = load_snapshot("notifications", &work_dir).await {
+                        notifications.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(NotificationId, Notification)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(notifications);
+                }
+                StorageMsg::SaveReminders(reminders) => {
+                    let reminders = reminders.iter().collect::<Vec<_>>();
+                    save_snapshot("reminders", &json!(reminders), &work_dir).await;
+                }
+                StorageMsg::RetrieveReminders { respond_to } => {
+                    let mut reminders = HashSet::new();
+                    if let Value::Array(entries) = load_snapshot("reminders", &work_dir).await {
+                        reminders.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<SentReminder>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(reminders);
+                }
+                StorageMsg::SaveEmailQueue(emails) => {
+                    save_snapshot("email_queue", &json!(emails), &work_dir).await;
+                }
+                StorageMsg::RetrieveEmailQueue { respond_to } => {
+                    let mut emails = Vec::new();
+                    if let Value::Array(entries) = load_snapshot("email_queue", &work_dir).await {
+                        emails.extend(
+                            entries.into_iter().filter_map(|entry| {
+                                serde_json::from_value::<QueuedEmail>(entry).ok()
+                            }),
+                        );
+                    }
+
+                    let _ = respond_to.send(emails);
+                }
+                StorageMsg::SavePushSubscriptions(subscriptions) => {
+                    let pairs = subscriptions
+                        .iter()
+                        .map(|(user, subscriptions)| json!([user, subscriptions]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("push_subscriptions", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrievePushSubscriptions { respond_to } => {
+                    let mut subscriptions = HashMap::new();
+                    if let Value::Array(entries) =
+                        load_snapshot("push_subscriptions", &work_dir).await
+                    {
+                        subscriptions.extend(entries.into_iter().filter_map(|entry| {
+                            serde_json::from_value::<(UserId, Vec<PushSubscription>)>(entry).ok()
+                        }));
+                    }
+
+                    let _ = respond_to.send(subscriptions);
+                }
+                StorageMsg::SaveTopics(topics) => {
+                    let pairs = topics
+                        .iter()
+                        .map(|(id, topic)| json!([id, topic]))
+                        .collect::<Vec<_>>();
+                    save_snapshot("topics", &Value::Array(pairs), &work_dir).await;
+                }
+                StorageMsg::RetrieveTopics { respond_to } => {
+                    let value = load_snapshot("topics", &work_dir).await;
+                    let topics = match value {
+                        Value::Array(entries) => Some(
+                            entries
+                                .into_iter()
+                                .filter_map(|entry| {
+                                    serde_json::from_value::<(TopicId, Topic)>(entry).ok()
+                                })
+                                .collect(),
+                        ),
+                        _ => None,
+                    };
+
+                    let _ = respond_to.send(topics);
+                }
             }
         }
     });
@@ -129,7 +387,7 @@ async fn write_atomic_json(path: &Path, value: &Value) -> eyre::Result<()> {
     }
 
     let data = Envelope {
-        version: peer_practice_messages::Version::V2025_10_14,
+        version: peer_practice_messages::Version::CURRENT,
         data: value,
     };
     let data = serde_json::to_vec_pretty(&data)?;
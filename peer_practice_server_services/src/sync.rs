@@ -0,0 +1,220 @@
+use peer_practice_messages::current::post::PostId;
+use peer_practice_messages::current::sync::SyncCursor;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Removed posts remembered for clients that were offline; older removals
+/// force those clients into a full resync.
+const MAX_TOMBSTONES: usize = 1000;
+
+/// Revision at which each post last changed, so clients can fetch only what
+/// changed since their last sync. Saved next to the posts, so cursors stay
+/// valid across restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "LogSnapshot", into = "LogSnapshot")]
+pub struct PostLog {
+    epoch: Uuid,
+    revision: u64,
+    changed: HashMap<PostId, u64>,
+    tombstones: HashMap<PostId, u64>,
+    /// Clients behind this revision may have missed a forgotten removal.
+    forgotten_until: u64,
+}
+
+/// `PostLog` with its maps as pairs, as post ids cannot be JSON keys.
+#[derive(Serialize, Deserialize)]
+struct LogSnapshot {
+    epoch: Uuid,
+    revision: u64,
+    changed: Vec<(PostId, u64)>,
+    tombstones: Vec<(PostId, u64)>,
+    forgotten_until: u64,
+}
+
+impl From<PostLog> for LogSnapshot {
+    fn from(log: PostLog) -> Self {
+        Self {
+            epoch: log.epoch,
+            revision: log.revision,
+            changed: log.changed.into_iter().collect(),
+            tombstones: log.tombstones.into_iter().collect(),
+            forgotten_until: log.forgotten_until,
+        }
+    }
+}
+
+impl From<LogSnapshot> for PostLog {
+    fn from(snapshot: LogSnapshot) -> Self {
+        Self {
+            epoch: snapshot.epoch,
+            revision: snapshot.revision,
+            changed: snapshot.changed.into_iter().collect(),
+            tombstones: snapshot.tombstones.into_iter().collect(),
+            forgotten_until: snapshot.forgotten_until,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Changes {
+    /// The client has to replace all of its posts.
+    Full,
+    Since {
+        changed: Vec<PostId>,
+        removed: Vec<PostId>,
+    },
+}
+
+impl Default for PostLog {
+    fn default() -> Self {
+        Self::new(Uuid::new_v4())
+    }
+}
+
+impl PostLog {
+    pub fn new(epoch: Uuid) -> Self {
+        Self {
+            epoch,
+            revision: 0,
+            changed: HashMap::new(),
+            tombstones: HashMap::new(),
+            forgotten_until: 0,
+        }
+    }
+
+    /// Whether a change to the post was recorded.
+    pub fn knows(&self, id: &PostId) -> bool {
+        self.changed.contains_key(id)
+    }
+
+    pub fn cursor(&self) -> SyncCursor {
+        SyncCursor {
+            epoch: self.epoch,
+            revision: self.revision,
+        }
+    }
+
+    pub fn changed(&mut self, id: PostId) {
+        self.revision += 1;
+        self.tombstones.remove(&id);
+        self.changed.insert(id, self.revision);
+    }
+
+    pub fn removed(&mut self, id: PostId) {
+        self.revision += 1;
+        self.changed.remove(&id);
+        self.tombstones.insert(id, self.revision);
+        if self.tombstones.len() > MAX_TOMBSTONES
+            && let Some((oldest, revision)) = self
+                .tombstones
+                .iter()
+                .min_by_key(|(_, revision)| **revision)
+                .map(|(id, revision)| (*id, *revision))
+        {
+            self.tombstones.remove(&oldest);
+            self.forgotten_until = revision;
+        }
+    }
+
+    pub fn since(&self, cursor: Option<SyncCursor>) -> Changes {
+        let Some(cursor) = cursor else {
+            return Changes::Full;
+        };
+        if cursor.epoch != self.epoch
+            || cursor.revision > self.revision
+            || cursor.revision < self.forgotten_until
+        {
+            return Changes::Full;
+        }
+        let newer = |entries: &HashMap<PostId, u64>| {
+            entries
+                .iter()
+                .filter(|(_, revision)| **revision > cursor.revision)
+                .map(|(id, _)| *id)
+                .collect()
+        };
+        Changes::Since {
+            changed: newer(&self.changed),
+            removed: newer(&self.tombstones),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_changes_since_the_cursor() {
+        let mut log = PostLog::default();
+        let (kept, edited, removed) = (PostId::new(), PostId::new(), PostId::new());
+        log.changed(kept);
+        log.changed(edited);
+        log.changed(removed);
+        let cursor = log.cursor();
+
+        log.changed(edited);
+        log.removed(removed);
+
+        assert_eq!(
+            log.since(Some(cursor)),
+            Changes::Since {
+                changed: vec![edited],
+                removed: vec![removed],
+            }
+        );
+        assert_eq!(
+            log.since(Some(log.cursor())),
+            Changes::Since {
+                changed: vec![],
+                removed: vec![],
+            }
+        );
+        assert_eq!(log.since(None), Changes::Full);
+        assert_eq!(
+            log.since(Some(SyncCursor {
+                epoch: Uuid::new_v4(),
+                ..cursor
+            })),
+            Changes::Full
+        );
+    }
+
+    #[test]
+    fn forgotten_removals_force_a_full_resync() {
+        let mut log = PostLog::default();
+        let cursor = log.cursor();
+        for _ in 0..=MAX_TOMBSTONES {
+            log.removed(PostId::new());
+        }
+
+        assert_eq!(log.since(Some(cursor)), Changes::Full);
+        assert!(matches!(
+            log.since(Some(log.cursor())),
+            Changes::Since { .. }
+        ));
+    }
+
+    #[test]
+    fn cursors_survive_saving_the_log() {
+        let mut log = PostLog::default();
+        let (edited, removed) = (PostId::new(), PostId::new());
+        log.changed(edited);
+        log.changed(removed);
+        let cursor = log.cursor();
+        log.changed(edited);
+        log.removed(removed);
+
+        let saved = serde_json::to_value(&log).unwrap();
+        let restored = serde_json::from_value::<PostLog>(saved).unwrap();
+        assert_eq!(restored.cursor(), log.cursor());
+        assert_eq!(
+            restored.since(Some(cursor)),
+            Changes::Since {
+                changed: vec![edited],
+                removed: vec![removed],
+            }
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::storage::StorageMsg;
+use crate::ws_hub::WsHubMsg;
+use peer_practice_messages::current::messages::ServerToClient;
+use peer_practice_messages::current::topic::{Topic, TopicId, legacy_topics};
+use std::collections::HashMap;
+use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc, oneshot};
+use tracing::info;
+
+#[derive(Debug)]
+pub enum TopicsMsg {
+    New(Topic, oneshot::Sender<TopicId>),
+    /// Replaces an existing topic, unknown ids are ignored.
+    Update(TopicId, Topic),
+    List(oneshot::Sender<HashMap<TopicId, Topic>>),
+}
+
+pub fn spawn_topics_actor(
+    storage: Sender<StorageMsg>,
+    ws_hub: Sender<WsHubMsg>,
+) -> Sender<TopicsMsg> {
+    let (tx, mut rx) = mpsc::channel::<TopicsMsg>(64);
+
+    tokio::spawn(async move {
+        let mut topics = setup(&storage).await;
+
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                TopicsMsg::New(mut topic, respond_to) => {
+                    topic.name = topic.name.trim().to_string();
+                    let id = TopicId::new();
+                    topics.insert(id, topic.clone());
+                    let _ = respond_to.send(id);
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Topic(id, topic)))
+                        .await;
+                    let _ = storage.send(StorageMsg::SaveTopics(topics.clone())).await;
+                }
+                TopicsMsg::Update(id, mut topic) => {
+                    let Some(existing) = topics.get_mut(&id) else {
+                        continue;
+                    };
+                    topic.name = topic.name.trim().to_string();
+                    *existing = topic.clone();
+                    let _ = ws_hub
+                        .send(WsHubMsg::BroadcastAll(ServerToClient::Topic(id, topic)))
+                        .await;
+                    let _ = storage.send(StorageMsg::SaveTopics(topics.clone())).await;
+                }
+                TopicsMsg::List(respond_to) => {
+                    let _ = respond_to.send(topics.clone());
+                }
+            }
+        }
+    });
+
+    tx
+}
+
+/// Loads the saved topics, seeding the legacy topics on first start so posts
+/// stored before topics became data keep their names.
+async fn setup(storage: &Sender<StorageMsg>) -> HashMap<TopicId, Topic> {
+    let (respond_to, recv) = oneshot::channel();
+    let _ = storage
+        .send(StorageMsg::RetrieveTopics { respond_to })
+        .await;
+
+    match recv.await {
+        Ok(Some(topics)) => topics,
+        _ => {
+            info!("No topics stored yet, seeding the legacy topics");
+            let topics = legacy_topics();
+            let _ = storage.send(StorageMsg::SaveTopics(topics.clone())).await;
+            topics
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::spawn_storage_actor;
+    use peer_practice_messages::current::accent_colors::AccentColor;
+
+    async fn list(topics: &Sender<TopicsMsg>) -> HashMap<TopicId, Topic> {
+        let (respond_to, recv) = oneshot::channel();
+        topics.send(TopicsMsg::List(respond_to)).await.unwrap();
+        recv.await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn seeds_the_legacy_topics_once_and_keeps_updates() {
+        let dir = std::env::temp_dir().join(format!("topics-{}", uuid::Uuid::new_v4()));
+        let storage = spawn_storage_actor(dir.clone());
+        let (ws_hub, mut hub) = mpsc::channel(100);
+        let topics = spawn_topics_actor(storage.clone(), ws_hub.clone());
+        assert_eq!(list(&topics).await, legacy_topics());
+
+        let (respond_to, recv) = oneshot::channel();
+        let topic = Topic {
+            name: "  Musicality ".to_string(),
+            color: AccentColor::Teal,
+            archived: false,
+        };
+        topics
+            .send(TopicsMsg::New(topic.clone(), respond_to))
+            .await
+            .unwrap();
+        let id = recv.await.unwrap();
+        let archived = Topic {
+            archived: true,
+            ..topic.clone()
+        };
+        topics.send(TopicsMsg::Update(id, archived)).await.unwrap();
+        topics
+            .send(TopicsMsg::Update(TopicId::new(), topic))
+            .await
+            .unwrap();
+
+        let listed = list(&topics).await;
+        assert_eq!(listed.len(), legacy_topics().len() + 1);
+        assert_eq!(listed[&id].name, "Musicality");
+        assert!(listed[&id].archived);
+
+        let mut broadcasts = Vec::new();
+        while let Ok(WsHubMsg::BroadcastAll(ServerToClient::Topic(id, topic))) = hub.try_recv() {
+            broadcasts.push((id, topic.archived));
+        }
+        assert_eq!(broadcasts, [(id, false), (id, true)]);
+
+        let restarted = spawn_topics_actor(storage, ws_hub);
+        let reloaded = list(&restarted).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloaded, listed);
+    }
+}
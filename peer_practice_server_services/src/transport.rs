@@ -0,0 +1,254 @@
+use eyre::{WrapErr, bail};
+use lettre::address::Envelope;
+pub use lettre::transport::smtp::authentication::Credentials;
+use lettre::{
+    AsyncFileTransport, AsyncSendmailTransport, AsyncSmtpTransport, AsyncTransport, Message,
+    Tokio1Executor,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// The kinds of transport that can be picked in the config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportKind {
+    /// SMTP upgraded to TLS with STARTTLS, usually on port 587.
+    #[default]
+    Starttls,
+    /// SMTP over TLS from the first byte, usually on port 465.
+    Tls,
+    /// Unauthenticated SMTP without TLS, for a relay on the same host.
+    Relay,
+    Sendmail,
+    /// Writes each email to a file, for development.
+    File,
+    /// Posts each email to an HTTP mail API.
+    Http,
+}
+
+/// How emails leave the server.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    Smtp {
+        security: SmtpSecurity,
+        /// Host name, optionally followed by `:port`.
+        relay: String,
+        credentials: Option<Credentials>,
+    },
+    Sendmail {
+        /// `sendmail` from the `PATH` if `None`.
+        command: Option<PathBuf>,
+    },
+    FileDrop {
+        dir: PathBuf,
+    },
+    /// Posts `{"from", "to", "raw"}` as JSON, with the whole MIME message in
+    /// `raw`, authenticated with a bearer token.
+    Http {
+        url: String,
+        token: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    Starttls,
+    Tls,
+    None,
+}
+
+/// A transport that is set up once and reused for every email. SMTP
+/// connections are pooled.
+pub enum Mailer {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    Sendmail(AsyncSendmailTransport<Tokio1Executor>),
+    FileDrop(AsyncFileTransport<Tokio1Executor>),
+    Http {
+        client: reqwest::Client,
+        url: String,
+        token: String,
+    },
+}
+
+impl Mailer {
+    pub fn new(transport: &Transport) -> eyre::Result<Self> {
+        Ok(match transport {
+            Transport::Smtp {
+                security,
+                relay,
+                credentials,
+            } => {
+                let (host, port) = match relay.rsplit_once(':') {
+                    Some((host, port)) => (
+                        host,
+                        Some(
+                            port.parse::<u16>()
+                                .wrap_err_with(|| format!("Invalid port in \"{relay}\"."))?,
+                        ),
+                    ),
+                    None => (relay.as_str(), None),
+                };
+                let mut builder = match security {
+                    SmtpSecurity::Starttls => {
+                        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+                    }
+                    SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+                    SmtpSecurity::None => {
+                        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+                    }
+                };
+                if let Some(port) = port {
+                    builder = builder.port(port);
+                }
+                if let Some(credentials) = credentials {
+                    builder = builder.credentials(credentials.clone());
+                }
+                Mailer::Smtp(builder.build())
+            }
+            Transport::Sendmail { command: None } => {
+                Mailer::Sendmail(AsyncSendmailTransport::new())
+            }
+            Transport::Sendmail {
+                command: Some(command),
+            } => Mailer::Sendmail(AsyncSendmailTransport::new_with_command(command)),
+            Transport::FileDrop { dir } => {
+                std::fs::create_dir_all(dir)
+                    .wrap_err_with(|| format!("Could not create {}.", dir.display()))?;
+                Mailer::FileDrop(AsyncFileTransport::new(dir))
+            }
+            Transport::Http { url, token } => Mailer::Http {
+                client: reqwest::Client::new(),
+                url: url.clone(),
+                token: token.clone(),
+            },
+        })
+    }
+
+    pub async fn send(&self, email: Message) -> eyre::Result<()> {
+        self.send_raw(email.envelope(), &email.formatted()).await
+    }
+
+    /// Sends an already formatted email, as kept in the delivery queue.
+    /// Failures that retrying cannot fix are [`Rejected`].
+    pub async fn send_raw(&self, envelope: &Envelope, raw: &[u8]) -> eyre::Result<()> {
+        match self {
+            Mailer::Smtp(transport) => {
+                if let Err(err) = transport.send_raw(envelope, raw).await {
+                    if err
+                        .status()
+                        .is_some_and(|code| smtp_rejects_recipient(code.into()))
+                    {
+                        return Err(Rejected(err.to_string()).into());
+                    }
+                    return Err(err).wrap_err("Failed to send email.");
+                }
+            }
+            Mailer::Sendmail(transport) => {
+                transport
+                    .send_raw(envelope, raw)
+                    .await
+                    .wrap_err("Failed to hand email to sendmail.")?;
+            }
+            Mailer::FileDrop(transport) => {
+                transport
+                    .send_raw(envelope, raw)
+                    .await
+                    .wrap_err("Failed to write email to file.")?;
+            }
+            Mailer::Http { client, url, token } => {
+                let body = serde_json::json!({
+                    "from": envelope.from().map(|from| from.to_string()),
+                    "to": envelope.to().iter().map(|to| to.to_string()).collect::<Vec<_>>(),
+                    "raw": String::from_utf8_lossy(raw),
+                });
+                let response = client
+                    .post(url)
+                    .bearer_auth(token)
+                    .json(&body)
+                    .send()
+                    .await
+                    .wrap_err("Failed to reach mail API.")?;
+                let status = response.status();
+                if !status.is_success() {
+                    let text = response.text().await.unwrap_or_default();
+                    if http_rejects_recipient(status) {
+                        return Err(Rejected(format!("{status}: {text}")).into());
+                    }
+                    bail!("Mail API answered {status}: {text}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether an SMTP reply refuses the recipient itself: no such mailbox (550)
+/// or a malformed address (553). Other permanent errors, such as failed
+/// authentication, are about the server's setup and are retried.
+fn smtp_rejects_recipient(code: u16) -> bool {
+    matches!(code, 550 | 553)
+}
+
+/// Whether a mail API refused the message itself rather than the server's
+/// credentials or its rate.
+fn http_rejects_recipient(status: reqwest::StatusCode) -> bool {
+    matches!(
+        status,
+        reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNPROCESSABLE_ENTITY
+    )
+}
+
+/// The email was refused for good, e.g. because the address does not exist.
+#[derive(Debug)]
+pub struct Rejected(pub String);
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rejected: {}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn file_drop_writes_one_file_per_email() {
+        let dir = std::env::temp_dir().join(format!("mail-{}", uuid::Uuid::new_v4()));
+        let mailer = Mailer::new(&Transport::FileDrop { dir: dir.clone() }).unwrap();
+        let email = Message::builder()
+            .from("from@example.com".parse().unwrap())
+            .to("to@example.com".parse().unwrap())
+            .subject("Hello")
+            .body("Hi there".to_string())
+            .unwrap();
+
+        mailer.send(email).await.unwrap();
+
+        let files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].contains("Subject: Hello"));
+    }
+
+    #[test]
+    fn only_recipient_errors_are_rejections() {
+        assert!(smtp_rejects_recipient(550));
+        assert!(smtp_rejects_recipient(553));
+        assert!(!smtp_rejects_recipient(535));
+        assert!(!smtp_rejects_recipient(421));
+        assert!(http_rejects_recipient(
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY
+        ));
+        assert!(!http_rejects_recipient(reqwest::StatusCode::UNAUTHORIZED));
+        assert!(!http_rejects_recipient(reqwest::StatusCode::FORBIDDEN));
+        assert!(!http_rejects_recipient(
+            reqwest::StatusCode::TOO_MANY_REQUESTS
+        ));
+    }
+}
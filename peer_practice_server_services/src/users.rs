@@ -5,6 +5,7 @@ use crate::storage::StorageMsg;
 use crate::ws_hub::WsHubMsg;
 use peer_practice_messages::current::email::Email;
 use peer_practice_messages::current::messages::ServerToClient;
+use peer_practice_messages::current::user::calendar_token::CalendarToken;
 use peer_practice_messages::current::user::{User, UserId};
 use tokio::sync::mpsc::Sender;
 use tracing::{error, info};
@@ -25,6 +26,18 @@ pub enum UsersMsg {
     Remove {
         id: UserId,
     },
+    GetCalendarToken {
+        id: UserId,
+        renew: bool,
+        respond_to: oneshot::Sender<Option<CalendarToken>>,
+    },
+    GetByCalendarToken {
+        token: CalendarToken,
+        respond_to: oneshot::Sender<Option<UserId>>,
+    },
+    List {
+        respond_to: oneshot::Sender<Vec<User>>,
+    },
 }
 
 pub fn spawn_users_actor(
@@ -58,6 +71,9 @@ pub fn spawn_users_actor(
                                 id,
                                 email,
                                 display_name: None,
+                                calendar_token: None,
+                                config: Default::default(),
+                                level: None,
                             },
                         );
                         let _ = storage
@@ -99,6 +115,38 @@ pub fn spawn_users_actor(
                     let val = id_to_user.get(&id).cloned();
                     let _ = respond_to.send(val);
                 }
+                UsersMsg::GetCalendarToken {
+                    id,
+                    renew,
+                    respond_to,
+                } => {
+                    let val = if let Some(user) = id_to_user.get_mut(&id) {
+                        match user.calendar_token {
+                            Some(token) if !renew => Some(token),
+                            _ => {
+                                let token = CalendarToken::new();
+                                user.calendar_token = Some(token);
+                                let _ = storage
+                                    .send(StorageMsg::SaveUsers(id_to_user.clone()))
+                                    .await;
+                                Some(token)
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    let _ = respond_to.send(val);
+                }
+                UsersMsg::List { respond_to } => {
+                    let _ = respond_to.send(id_to_user.values().cloned().collect());
+                }
+                UsersMsg::GetByCalendarToken { token, respond_to } => {
+                    let val = id_to_user
+                        .values()
+                        .find(|user| user.calendar_token == Some(token))
+                        .map(|user| user.id);
+                    let _ = respond_to.send(val);
+                }
             }
         }
     });
@@ -1,3 +1,5 @@
 pub use peer_practice_messages::Envelope;
 pub use peer_practice_messages::Version;
 pub use peer_practice_messages::current::*;
+pub use peer_practice_messages::handshake;
+pub use peer_practice_messages::v2025_10_14;
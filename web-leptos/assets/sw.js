@@ -0,0 +1,22 @@
+// Shows Web Push notifications sent by the server and opens the site when
+// one is clicked.
+self.addEventListener("push", (event) => {
+    const message = event.data ? event.data.json() : {};
+    event.waitUntil(
+        self.registration.showNotification(message.title || "Peer Practice", {
+            body: message.body || "",
+            data: { url: message.url || "/" },
+        }),
+    );
+});
+
+self.addEventListener("notificationclick", (event) => {
+    event.notification.close();
+    const url = event.notification.data.url;
+    event.waitUntil(
+        self.clients.matchAll({ type: "window" }).then((windows) => {
+            const open = windows.find((client) => "focus" in client);
+            return open ? open.focus() : self.clients.openWindow(url);
+        }),
+    );
+});
@@ -1,11 +1,30 @@
 use futures_channel::mpsc::UnboundedSender;
 use futures_util::SinkExt;
-use leptos::prelude::{Get, GetUntracked, ReadSignal, Update, WriteSignal, signal};
+use leptos::prelude::{
+    Get, GetUntracked, LocalStorage, ReadSignal, Set, StoredValue, Update, UpdateValue,
+    WriteSignal, signal,
+};
 use leptos::task::spawn_local;
+use peer_practice_shared::archive::ArchivedPost;
+use peer_practice_shared::comment::{Comment, CommentId};
+use peer_practice_shared::direct_message::{DirectMessage, DirectMessageId};
+use peer_practice_shared::email::UndeliveredEmail;
+use peer_practice_shared::history::{AuditEntry, PostVersion};
+use peer_practice_shared::level::Levels;
 use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::notification::{Notification, NotificationId};
 use peer_practice_shared::post::{Post, PostId};
+use peer_practice_shared::reaction::{Reaction, ReactionCounts};
+use peer_practice_shared::request::{ClientRequest, RequestError, RequestId};
+use peer_practice_shared::rotation::RotationSchedule;
+use peer_practice_shared::search::PostQuery;
+use peer_practice_shared::stats::PracticeStats;
+use peer_practice_shared::sync::SyncCursor;
+use peer_practice_shared::topic::{Topic, TopicId};
 use peer_practice_shared::user::UserId;
+use peer_practice_shared::user::calendar_token::CalendarToken;
 use peer_practice_shared::user::display_user::UserDisplay;
+use peer_practice_shared::user::user_config::UserConfig;
 use std::collections::HashMap;
 
 pub fn initialize_app_state() -> (AppStateReader, AppStateWriter) {
@@ -14,13 +33,59 @@ pub fn initialize_app_state() -> (AppStateReader, AppStateWriter) {
     let (posts_read, posts_write) = signal(HashMap::new());
     let (users_read, users_write) = signal(HashMap::new());
     let (pending_route_read, pending_route_write) = signal(None);
+    let (calendar_token_read, calendar_token_write) = signal(None);
+    let (user_config_read, user_config_write) = signal(None);
+    let (rotation_read, rotation_write) = signal(None);
+    let (topics_read, topics_write) = signal(HashMap::new());
+    let (organizer_read, organizer_write) = signal(false);
+    let (levels_read, levels_write) = signal(Levels::default());
+    let (comments_read, comments_write) = signal(HashMap::new());
+    let (comment_counts_read, comment_counts_write) = signal(HashMap::new());
+    let (direct_messages_read, direct_messages_write) = signal(HashMap::new());
+    let (reactions_read, reactions_write) = signal(HashMap::new());
+    let (my_reactions_read, my_reactions_write) = signal(HashMap::new());
+    let (search_results_read, search_results_write) = signal(None);
+    let (posts_cursor_read, posts_cursor_write) = signal(None);
+    let (post_history_read, post_history_write) = signal(HashMap::new());
+    let (audit_log_read, audit_log_write) = signal(None);
+    let (undelivered_emails_read, undelivered_emails_write) = signal(None);
+    let (archive_read, archive_write) = signal(None);
+    let (practice_stats_read, practice_stats_write) = signal(None);
+    let (notifications_read, notifications_write) = signal(HashMap::new());
+    let (vapid_public_key_read, vapid_public_key_write) = signal(None);
+    let (notice_read, notice_write) = signal(None);
+    let (reload_required_read, reload_required_write) = signal(false);
+    let requests = StoredValue::new_local(PendingRequests::default());
     (
         AppStateReader {
             tx: tx_read,
+            requests,
             user_id: user_id_read,
             posts: posts_read,
             users: users_read,
             pending_route: pending_route_read,
+            calendar_token: calendar_token_read,
+            user_config: user_config_read,
+            rotation: rotation_read,
+            topics: topics_read,
+            organizer: organizer_read,
+            levels: levels_read,
+            comments: comments_read,
+            comment_counts: comment_counts_read,
+            direct_messages: direct_messages_read,
+            reactions: reactions_read,
+            my_reactions: my_reactions_read,
+            search_results: search_results_read,
+            posts_cursor: posts_cursor_read,
+            post_history: post_history_read,
+            audit_log: audit_log_read,
+            undelivered_emails: undelivered_emails_read,
+            archive: archive_read,
+            practice_stats: practice_stats_read,
+            notifications: notifications_read,
+            vapid_public_key: vapid_public_key_read,
+            notice: notice_read,
+            reload_required: reload_required_read,
         },
         AppStateWriter {
             tx: tx_write,
@@ -28,30 +93,137 @@ pub fn initialize_app_state() -> (AppStateReader, AppStateWriter) {
             posts: posts_write,
             users: users_write,
             pending_route: pending_route_write,
+            calendar_token: calendar_token_write,
+            user_config: user_config_write,
+            rotation: rotation_write,
+            topics: topics_write,
+            organizer: organizer_write,
+            levels: levels_write,
+            comments: comments_write,
+            comment_counts: comment_counts_write,
+            direct_messages: direct_messages_write,
+            reactions: reactions_write,
+            my_reactions: my_reactions_write,
+            search_results: search_results_write,
+            posts_cursor: posts_cursor_write,
+            post_history: post_history_write,
+            audit_log: audit_log_write,
+            undelivered_emails: undelivered_emails_write,
+            archive: archive_write,
+            practice_stats: practice_stats_write,
+            notifications: notifications_write,
+            vapid_public_key: vapid_public_key_write,
+            notice: notice_write,
+            reload_required: reload_required_write,
         },
     )
 }
+/// Called with the server's answer to a request.
+type OnReply = Box<dyn FnOnce(Result<(), RequestError>)>;
+
+/// Requests sent to the server that wait for an `Ack` or `Error`.
+#[derive(Default)]
+pub struct PendingRequests {
+    next_id: u64,
+    on_reply: HashMap<RequestId, OnReply>,
+}
+
+/// A short message about the outcome of something the user did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notice {
+    pub text: String,
+    pub failed: bool,
+}
+
+impl Notice {
+    pub fn success(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            failed: false,
+        }
+    }
+
+    pub fn failure(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            failed: true,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct AppStateWriter {
-    tx: WriteSignal<Option<UnboundedSender<ClientToServer>>>,
+    tx: WriteSignal<Option<UnboundedSender<ClientRequest>>>,
     pub user_id: WriteSignal<Option<UserId>>,
     pub posts: WriteSignal<HashMap<PostId, Post>>,
     pub users: WriteSignal<HashMap<UserId, UserDisplay>>,
     pub pending_route: WriteSignal<Option<String>>,
+    pub calendar_token: WriteSignal<Option<CalendarToken>>,
+    pub user_config: WriteSignal<Option<UserConfig>>,
+    pub rotation: WriteSignal<Option<RotationSchedule>>,
+    pub topics: WriteSignal<HashMap<TopicId, Topic>>,
+    pub organizer: WriteSignal<bool>,
+    pub levels: WriteSignal<Levels>,
+    pub comments: WriteSignal<HashMap<CommentId, Comment>>,
+    pub comment_counts: WriteSignal<HashMap<PostId, usize>>,
+    pub direct_messages: WriteSignal<HashMap<DirectMessageId, DirectMessage>>,
+    pub reactions: WriteSignal<HashMap<PostId, ReactionCounts>>,
+    pub my_reactions: WriteSignal<HashMap<PostId, Vec<Reaction>>>,
+    pub search_results: WriteSignal<Option<(PostQuery, Vec<PostId>)>>,
+    pub posts_cursor: WriteSignal<Option<SyncCursor>>,
+    pub post_history: WriteSignal<HashMap<PostId, Vec<PostVersion>>>,
+    pub audit_log: WriteSignal<Option<Vec<AuditEntry>>>,
+    pub undelivered_emails: WriteSignal<Option<Vec<UndeliveredEmail>>>,
+    pub archive: WriteSignal<Option<Vec<(PostId, ArchivedPost)>>>,
+    pub practice_stats: WriteSignal<Option<PracticeStats>>,
+    pub notifications: WriteSignal<HashMap<NotificationId, Notification>>,
+    pub vapid_public_key: WriteSignal<Option<String>>,
+    pub notice: WriteSignal<Option<Notice>>,
+    pub reload_required: WriteSignal<bool>,
 }
 impl AppStateWriter {
-    pub(crate) fn set_tx(&self, tx: Option<UnboundedSender<ClientToServer>>) {
+    pub(crate) fn set_tx(&self, tx: Option<UnboundedSender<ClientRequest>>) {
         self.tx.update(|s| *s = tx);
     }
 }
 
 #[derive(Copy, Clone)]
 pub struct AppStateReader {
-    tx: ReadSignal<Option<UnboundedSender<ClientToServer>>>,
+    tx: ReadSignal<Option<UnboundedSender<ClientRequest>>>,
+    requests: StoredValue<PendingRequests, LocalStorage>,
     pub user_id: ReadSignal<Option<UserId>>,
     pub posts: ReadSignal<HashMap<PostId, Post>>,
     pub users: ReadSignal<HashMap<UserId, UserDisplay>>,
     pub pending_route: ReadSignal<Option<String>>,
+    pub calendar_token: ReadSignal<Option<CalendarToken>>,
+    pub user_config: ReadSignal<Option<UserConfig>>,
+    pub rotation: ReadSignal<Option<RotationSchedule>>,
+    pub topics: ReadSignal<HashMap<TopicId, Topic>>,
+    pub organizer: ReadSignal<bool>,
+    pub levels: ReadSignal<Levels>,
+    /// Comments of the threads opened so far.
+    pub comments: ReadSignal<HashMap<CommentId, Comment>>,
+    pub comment_counts: ReadSignal<HashMap<PostId, usize>>,
+    pub direct_messages: ReadSignal<HashMap<DirectMessageId, DirectMessage>>,
+    pub reactions: ReadSignal<HashMap<PostId, ReactionCounts>>,
+    pub my_reactions: ReadSignal<HashMap<PostId, Vec<Reaction>>>,
+    /// Latest answer to a search, with the query it answers.
+    pub search_results: ReadSignal<Option<(PostQuery, Vec<PostId>)>>,
+    /// How far `posts` caught up with the server, sent along when reconnecting.
+    pub posts_cursor: ReadSignal<Option<SyncCursor>>,
+    pub post_history: ReadSignal<HashMap<PostId, Vec<PostVersion>>>,
+    pub audit_log: ReadSignal<Option<Vec<AuditEntry>>>,
+    /// Emails the server is retrying or gave up on, only loaded by organizers.
+    pub undelivered_emails: ReadSignal<Option<Vec<UndeliveredEmail>>>,
+    /// Deleted and expired posts, as far as the user may see them.
+    pub archive: ReadSignal<Option<Vec<(PostId, ArchivedPost)>>>,
+    pub practice_stats: ReadSignal<Option<PracticeStats>>,
+    pub notifications: ReadSignal<HashMap<NotificationId, Notification>>,
+    /// Application server key for Web Push subscriptions.
+    pub vapid_public_key: ReadSignal<Option<String>>,
+    pub notice: ReadSignal<Option<Notice>>,
+    /// The server no longer speaks this client's message version.
+    pub reload_required: ReadSignal<bool>,
 }
 
 impl AppStateReader {
@@ -62,11 +234,59 @@ impl AppStateReader {
         self.tx.get_untracked().is_some()
     }
     pub fn send(&self, msg: ClientToServer) {
-        match self.tx.get_untracked().clone() {
-            None => {}
-            Some(mut tx) => spawn_local(async move {
-                let _ = tx.send(msg).await;
-            }),
+        self.request(msg, |_| {});
+    }
+
+    /// Sends `msg` and calls `on_reply` once the server answered it, or with
+    /// an error if the connection is lost first.
+    pub fn request(
+        &self,
+        msg: ClientToServer,
+        on_reply: impl FnOnce(Result<(), RequestError>) + 'static,
+    ) {
+        let Some(mut tx) = self.tx.get_untracked() else {
+            on_reply(Err(RequestError::internal("Not connected to the server.")));
+            return;
+        };
+        let Some(id) = self.requests.try_update_value(|requests| {
+            let id = RequestId(requests.next_id);
+            requests.next_id += 1;
+            requests.on_reply.insert(id, Box::new(on_reply));
+            id
+        }) else {
+            return;
+        };
+        spawn_local(async move {
+            let _ = tx.send(ClientRequest { id, message: msg }).await;
+        });
+    }
+
+    /// Hands the server's answer to whoever sent the request.
+    pub(crate) fn reply(&self, id: RequestId, result: Result<(), RequestError>) {
+        let on_reply = self
+            .requests
+            .try_update_value(|requests| requests.on_reply.remove(&id))
+            .flatten();
+        if let Some(on_reply) = on_reply {
+            on_reply(result);
+        }
+    }
+
+    /// Fails every request still waiting for an answer, e.g. after the
+    /// connection dropped.
+    pub(crate) fn fail_pending(&self, notice: WriteSignal<Option<Notice>>) {
+        let pending = self
+            .requests
+            .try_update_value(|requests| std::mem::take(&mut requests.on_reply))
+            .unwrap_or_default();
+        if pending.is_empty() {
+            return;
+        }
+        notice.set(Some(Notice::failure(
+            "Lost the connection before the server confirmed your changes.",
+        )));
+        for on_reply in pending.into_values() {
+            on_reply(Err(RequestError::internal("Connection lost.")));
         }
     }
 }
@@ -0,0 +1,95 @@
+use leptos::prelude::*;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::archive::Removal;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::{convert_utc_to_local, convert_utc_to_local_date};
+
+/// Posts that expired or were deleted; deleted ones can be put back on the board.
+#[component]
+pub fn Archive(state: AppStateReader) -> impl IntoView {
+    state.send(ClientToServer::GetArchive);
+
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    view! {
+        <div class="card" style="margin: 1rem;">
+            <div class="cluster" style="--cluster-justify: space-between;">
+                <h2 class="card-title">"Archive"</h2>
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="secondary"
+                    on:click=move |_| state.send(ClientToServer::GetArchive)
+                >
+                    "Refresh"
+                </button>
+            </div>
+            {move || {
+                let Some(archive) = state.archive.get() else {
+                    return view! { <p>"Loading..."</p> }.into_any();
+                };
+                if archive.is_empty() {
+                    return view! { <p>"Nothing has been deleted or expired yet."</p> }
+                        .into_any();
+                }
+                let topics = state.topics.get();
+                let me = state.user_id.get();
+                let organizer = state.organizer.get();
+                archive
+                    .into_iter()
+                    .map(|(id, archived)| {
+                        let may_restore = match archived.removal {
+                            Removal::Deleted(by) => {
+                                organizer || Some(by) == me || Some(archived.post.owner) == me
+                            }
+                            Removal::Expired => false,
+                        };
+                        let removal = match archived.removal {
+                            Removal::Deleted(by) => format!("Deleted by {}", name(by)),
+                            Removal::Expired => "Expired".to_string(),
+                        };
+                        view! {
+                            <div
+                                class="cluster"
+                                style="--cluster-justify: space-between; margin-top: .75rem; padding-top: .75rem; border-top: 1px solid var(--bg-strong-color);"
+                            >
+                                <div>
+                                    <strong>{archived.post.title(&topics)}</strong>
+                                    " · "
+                                    {convert_utc_to_local_date(archived.post.date).to_string()}
+                                    " · "
+                                    {name(archived.post.owner)}
+                                    <div style="font-size: .85em; opacity: .8;">
+                                        {removal} " on "
+                                        {convert_utc_to_local(archived.removed_at)
+                                            .format("%Y-%m-%d %H:%M")
+                                            .to_string()}
+                                    </div>
+                                </div>
+                                <Show when=move || may_restore>
+                                    <button
+                                        type="button"
+                                        class="btn"
+                                        data-theme="primary"
+                                        on:click=move |_| state.send(ClientToServer::RestorePost(id))
+                                    >
+                                        "Restore"
+                                    </button>
+                                </Show>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+                    .into_any()
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,181 @@
+use crate::app_state::AppStateReader;
+use crate::event_card::markdown_to_safe_html;
+use leptos::prelude::*;
+use peer_practice_shared::comment::{Comment, CommentId, thread};
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::post::PostId;
+
+/// Replies deeper than this are no longer indented further.
+const MAX_INDENT: usize = 4;
+
+/// The comments of a post as a thread, with a box to write a new one.
+#[component]
+pub fn CommentThread(post_id: PostId, state: AppStateReader) -> impl IntoView {
+    let (draft, set_draft) = signal(String::new());
+    let (replying_to, set_replying_to) = signal(None::<CommentId>);
+    let (editing, set_editing) = signal(None::<CommentId>);
+    state.send(ClientToServer::GetComments(post_id));
+
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let post_owner = move || state.posts.get().get(&post_id).map(|post| post.owner);
+
+    let submit = move || {
+        let content = draft.get_untracked().trim().to_string();
+        if content.is_empty() || content.len() > Comment::MAX_LENGTH {
+            return;
+        }
+        match editing.get_untracked() {
+            Some(id) => state.send(ClientToServer::EditComment(id, content)),
+            None => state.send(ClientToServer::NewComment(
+                post_id,
+                replying_to.get_untracked(),
+                content,
+            )),
+        }
+        set_draft.set(String::new());
+        set_replying_to.set(None);
+        set_editing.set(None);
+    };
+
+    let comment_view = move |id: CommentId, depth: usize, comment: Comment| {
+        let me = state.user_id.get();
+        let is_author = me == Some(comment.author);
+        let can_delete = is_author || (me.is_some() && me == post_owner());
+        let content = comment.content.clone();
+        view! {
+            <div
+                class="surface"
+                data-accent="base"
+                style=format!(
+                    "margin-left: {}rem; margin-top: .5rem; padding: .5rem .75rem; border-radius: .6rem;",
+                    depth.min(MAX_INDENT) * 2,
+                )
+            >
+                <div class="cluster" style="--cluster-justify: space-between; --cluster-gap: .5rem;">
+                    <strong>{name(comment.author)}</strong>
+                    <span style="opacity: .7; font-size: .85em;">
+                        {convert_utc_to_local(comment.created).format("%Y-%m-%d %H:%M").to_string()}
+                        {comment.edited.map(|_| " (edited)")}
+                    </span>
+                </div>
+                <div class="markdown-body" inner_html=markdown_to_safe_html(&comment.content) />
+                <div class="cluster" style="--cluster-justify: flex-start; --cluster-gap: .5rem;">
+                    <button
+                        type="button"
+                        class="btn"
+                        data-theme="secondary"
+                        on:click=move |_| {
+                            set_editing.set(None);
+                            set_replying_to.set(Some(id));
+                        }
+                    >
+                        "Reply"
+                    </button>
+                    <Show when=move || is_author>
+                        <button
+                            type="button"
+                            class="btn"
+                            data-theme="secondary"
+                            on:click={
+                                let content = content.clone();
+                                move |_| {
+                                    set_replying_to.set(None);
+                                    set_editing.set(Some(id));
+                                    set_draft.set(content.clone());
+                                }
+                            }
+                        >
+                            "Edit"
+                        </button>
+                    </Show>
+                    <Show when=move || can_delete>
+                        <button
+                            type="button"
+                            class="btn"
+                            data-theme="danger"
+                            title="Deletes the comment and all replies to it"
+                            on:click=move |_| state.send(ClientToServer::DeleteComment(id))
+                        >
+                            "Delete"
+                        </button>
+                    </Show>
+                </div>
+            </div>
+        }
+    };
+
+    let context = move || {
+        let comments = state.comments.get();
+        if let Some(id) = editing.get() {
+            comments
+                .get(&id)
+                .map(|_| "Editing your comment".to_string())
+        } else if let Some(id) = replying_to.get() {
+            comments
+                .get(&id)
+                .map(|comment| format!("Replying to {}", name(comment.author)))
+        } else {
+            None
+        }
+    };
+
+    view! {
+        <div style="margin-top: .75rem;">
+            {move || {
+                let comments = state.comments.get();
+                thread(post_id, &comments)
+                    .into_iter()
+                    .filter_map(|(id, depth)| {
+                        comments.get(&id).cloned().map(|comment| comment_view(id, depth, comment))
+                    })
+                    .collect_view()
+            }}
+            <Show when=move || context().is_some()>
+                <div class="cluster" style="--cluster-justify: flex-start; --cluster-gap: .5rem; margin-top: .5rem; opacity: .8;">
+                    <span>{context}</span>
+                    <button
+                        type="button"
+                        class="btn"
+                        data-theme="secondary"
+                        on:click=move |_| {
+                            if editing.get_untracked().is_some() {
+                                set_draft.set(String::new());
+                            }
+                            set_replying_to.set(None);
+                            set_editing.set(None);
+                        }
+                    >
+                        "Cancel"
+                    </button>
+                </div>
+            </Show>
+            <div class="cluster" style="--cluster-gap: .5rem; margin-top: .5rem; align-items: flex-end;">
+                <textarea
+                    data-theme="base"
+                    style="--accent: var(--bg-strongest-color); flex: 1 1 auto; min-height: 4rem; padding: .5rem .75rem; border-radius: .6rem; border: 1px solid currentColor;"
+                    placeholder="Write a comment (markdown)"
+                    maxlength=Comment::MAX_LENGTH
+                    prop:value=draft
+                    on:input=move |ev| set_draft.set(event_target_value(&ev))
+                />
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="primary"
+                    disabled=move || draft.get().trim().is_empty()
+                    on:click=move |_| submit()
+                >
+                    {move || if editing.get().is_some() { "Save" } else { "Send" }}
+                </button>
+            </div>
+        </div>
+    }
+}
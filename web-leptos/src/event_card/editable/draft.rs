@@ -1,15 +1,21 @@
 use chrono::NaiveDate;
-use peer_practice_shared::level::Level;
-use peer_practice_shared::post::PostId;
+use peer_practice_shared::level::LevelRange;
+use peer_practice_shared::post::{PostId, RoleTargets};
+use peer_practice_shared::topic::TopicId;
 use serde::{Deserialize, Serialize};
 use web_sys::window;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Draft {
-    pub title: String,
+    #[serde(default)]
+    pub topics: Vec<TopicId>,
     pub ideas: String,
-    pub level: Level,
+    pub level: LevelRange,
     pub date: NaiveDate,
+    #[serde(default)]
+    pub capacity: Option<u32>,
+    #[serde(default)]
+    pub role_targets: Option<RoleTargets>,
 }
 
 pub fn storage_key(post_id: PostId) -> String {
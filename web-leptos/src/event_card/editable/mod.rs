@@ -2,14 +2,14 @@ use chrono::NaiveDate;
 use leptos::prelude::*;
 use std::sync::Arc;
 
-use crate::app_state::AppStateReader;
+use crate::app_state::{AppStateReader, AppStateWriter, Notice};
 use crate::components::buttons::ConfirmDeleteButton;
 use crate::components::buttons::ServerButton;
 use crate::event_card::editable::draft::{Draft, clear_draft, save_draft};
 use crate::event_card::{EventCardProps, event_card_footer, markdown_to_safe_html};
 use peer_practice_shared::level::Level;
 use peer_practice_shared::messages::ClientToServer;
-use peer_practice_shared::post::Topics;
+use peer_practice_shared::post::{PostId, RoleTargets};
 use peer_practice_shared::{convert_to_utc, convert_utc_to_local_date, ymd};
 
 mod draft;
@@ -20,11 +20,26 @@ pub fn EventCardEditable(
     #[prop(optional, into)] accent_color: Option<ReadSignal<String>>,
     #[prop(optional)] on_submitted: Option<Callback<()>>,
 ) -> impl IntoView {
-    let (title, set_title) = signal(props.title.to_string());
-    let (level, set_level) = signal(props.level);
+    let set_recently_deleted: WriteSignal<Option<PostId>> = expect_context();
+    let write_state: AppStateWriter = expect_context();
+    let (level, set_level) = signal(props.level.clone());
     let (ideas, set_ideas) = signal(props.ideas.clone());
     let (show_preview, _set_show_preview) = signal(false);
-    let (topics, set_topics) = signal::<Topics>(props.title.as_str().into());
+    let (topics, set_topics) = signal(props.topics.clone());
+    let (capacity, set_capacity) = signal(
+        state
+            .posts
+            .get_untracked()
+            .get(&props.id)
+            .and_then(|post| post.capacity),
+    );
+    let (role_targets, set_role_targets) = signal(
+        state
+            .posts
+            .get_untracked()
+            .get(&props.id)
+            .and_then(|post| post.role_targets),
+    );
 
     let accent_color = accent_color.unwrap_or_else(|| {
         let (default_accent, _set_default_accent) =
@@ -48,30 +63,35 @@ pub fn EventCardEditable(
 
     let initial_draft = draft::load_draft(post_id);
     if let Some(d) = initial_draft.clone() {
-        set_title.set(d.title);
+        set_topics.set(d.topics);
         set_ideas.set(d.ideas);
         set_level.set(d.level);
+        set_capacity.set(d.capacity);
+        set_role_targets.set(d.role_targets);
         let draft_date = d.date.format("%Y-%m-%d").to_string();
         if date_options.contains(&draft_date) {
             set_date_selected.set(draft_date);
         }
-        set_topics.set(title.get().as_str().into());
     }
     let (has_draft, set_has_draft) = signal(initial_draft.is_some());
 
     Effect::new({
         move |_| {
-            let t: Topics = topics.get();
+            let t = topics.get();
             let i = ideas.get();
             let lv = level.get();
+            let cap = capacity.get();
+            let targets = role_targets.get();
             let date_str = date_selected.get();
             if let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
                 let mut should_save = true;
 
                 if let Some(existing) = state.posts.get().get(&post_id)
-                    && t == existing.title
+                    && t == existing.topics
                     && i == existing.content
                     && lv == existing.level
+                    && cap == existing.capacity
+                    && targets == existing.role_targets
                     && date == convert_utc_to_local_date(existing.date)
                 {
                     should_save = false;
@@ -81,10 +101,12 @@ pub fn EventCardEditable(
 
                 if should_save {
                     let draft = Draft {
-                        title: format!("{}", t),
+                        topics: t,
                         ideas: i,
                         level: lv,
                         date,
+                        capacity: cap,
+                        role_targets: targets,
                     };
                     save_draft(post_id, &draft);
                     set_has_draft.set(true);
@@ -104,33 +126,63 @@ pub fn EventCardEditable(
                 let Ok(date) = NaiveDate::parse_from_str(&date_selected.get(), "%Y-%m-%d") else {
                     return;
                 };
+                if topics.get().is_empty() {
+                    return;
+                }
                 if let Some(existing) = state.posts.get().get(&post_id) {
                     let updated = peer_practice_shared::post::Post {
-                        title: topics.get(),
+                        topics: topics.get(),
                         content: ideas.get(),
                         level: level.get(),
                         owner: existing.owner,
                         date: convert_to_utc(date),
                         partaking_users: existing.partaking_users.clone(),
+                        capacity: capacity.get(),
+                        waitlist: existing.waitlist.clone(),
+                        roles: existing.roles.clone(),
+                        role_targets: role_targets.get(),
                     };
-                    state.send(ClientToServer::UpdatePost(post_id, updated));
-                    clear_draft(post_id);
-                    set_has_draft.set(false);
+                    // Shown right away, put back if the server refuses it.
+                    let previous = existing.clone();
+                    write_state.posts.update(|posts| {
+                        posts.insert(post_id, updated.clone());
+                    });
+                    state.request(
+                        ClientToServer::UpdatePost(post_id, updated),
+                        move |reply| match reply {
+                            Ok(()) => {
+                                clear_draft(post_id);
+                                set_has_draft.set(false);
+                                write_state.notice.set(Some(Notice::success("Post saved.")));
+                            }
+                            Err(_) => write_state.posts.update(|posts| {
+                                posts.insert(post_id, previous);
+                            }),
+                        },
+                    );
                 } else {
                     let Some(owner) = state.user_id.get() else {
                         return;
                     };
                     let new_post = peer_practice_shared::post::Post {
-                        title: topics.get(),
+                        topics: topics.get(),
                         content: ideas.get(),
                         level: level.get(),
                         owner,
                         date: convert_to_utc(date),
                         partaking_users: Default::default(),
+                        capacity: capacity.get(),
+                        waitlist: Vec::new(),
+                        roles: Default::default(),
+                        role_targets: role_targets.get(),
                     };
-                    state.send(ClientToServer::NewPost(new_post));
-                    clear_draft(post_id);
-                    set_has_draft.set(false);
+                    // The draft is kept until the server took the post.
+                    state.request(ClientToServer::NewPost(new_post), move |reply| {
+                        if reply.is_ok() {
+                            clear_draft(post_id);
+                            set_has_draft.set(false);
+                        }
+                    });
                     if let Some(cb) = on_submitted {
                         cb.run(());
                     }
@@ -144,29 +196,53 @@ pub fn EventCardEditable(
                 flex-wrap: nowrap; align-items: center; \
                 "
             >
-                <select
-                    class="combo card-title-input border-round"
-                    data-theme="accent"
-                    data-accent-strength="base"
-                    style=move || {
-                        format!("flex: 1 1 8rem; min-width: 0; --accent: {};", accent_color.get())
-                    }
-                    prop:value=move || topics.get().to_string()
-                    on:change=move |ev| {
-                        let v = event_target_value(&ev);
-                        set_topics.set(v.as_str().into());
-                        set_title.set(v);
-                    }
+                <div
+                    class="cluster card-title-input"
+                    style="flex: 1 1 8rem; min-width: 0; --cluster-justify: flex-start; --cluster-gap: .35rem;"
                 >
-                    {Topics::ALL
-                        .iter()
-                        .map(|t| {
-                            let v = t.to_string();
-                            let label = t.to_string();
-                            view! { <option value=v.clone()>{label}</option> }
-                        })
-                        .collect_view()}
-                </select>
+                    {move || {
+                        let selected = topics.get();
+                        let mut choices = state
+                            .topics
+                            .get()
+                            .into_iter()
+                            .filter(|(id, topic)| !topic.archived || selected.contains(id))
+                            .collect::<Vec<_>>();
+                        choices.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+                        choices
+                            .into_iter()
+                            .map(|(id, topic)| {
+                                let active = selected.contains(&id);
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="btn"
+                                        data-theme="accent"
+                                        aria-pressed=active.to_string()
+                                        title=if active { "Remove topic" } else { "Add topic" }
+                                        style=format!(
+                                            "--accent: {}; font-size: .9rem; padding: .25rem .6rem; opacity: {};",
+                                            topic.color.css_var(),
+                                            if active { "1" } else { ".45" },
+                                        )
+                                        on:click=move |_| {
+                                            set_topics
+                                                .update(|topics| {
+                                                    if !topics.contains(&id) {
+                                                        topics.push(id);
+                                                    } else if topics.len() > 1 {
+                                                        topics.retain(|topic| *topic != id);
+                                                    }
+                                                });
+                                        }
+                                    >
+                                        {topic.name}
+                                    </button>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                </div>
 
                 <select
                     class="combo"
@@ -207,29 +283,146 @@ pub fn EventCardEditable(
                     class="combo"
                     data-theme="accent"
                     data-accent-strength="base"
+                    title="Lowest level this post is for"
                     style=move || {
                         format!(
                             "flex: 1 1 auto; min-width: 0; max-width: 100%; --accent: {};",
                             accent_color.get(),
                         )
                     }
-                    prop:value=move || level.get().as_str().to_string()
+                    prop:value=move || level.get().from.to_string()
                     on:change=move |ev| {
-                        let v = event_target_value(&ev);
-                        set_level.set(Level::from(v.as_str()));
+                        let picked = Level::new(event_target_value(&ev));
+                        set_level
+                            .update(|range| {
+                                range.from = picked;
+                                if let Some(ordered) = state.levels.get_untracked().normalize(range.clone()) {
+                                    *range = ordered;
+                                }
+                            });
                     }
                 >
-                    {Level::all()
-                        .iter()
-                        .map(|lv| {
-                            let v = lv.as_str().to_string();
-                            let label = lv.to_string();
-                            view! { <option value=v.clone()>{label}</option> }
-                        })
-                        .collect_view()}
+                    {move || level_options(state)}
+                </select>
+                <span style="flex: 0 0 auto; opacity: .8;">"–"</span>
+                <select
+                    class="combo"
+                    data-theme="accent"
+                    data-accent-strength="base"
+                    title="Highest level this post is for"
+                    style=move || {
+                        format!(
+                            "flex: 1 1 auto; min-width: 0; max-width: 100%; --accent: {};",
+                            accent_color.get(),
+                        )
+                    }
+                    prop:value=move || level.get().to.to_string()
+                    on:change=move |ev| {
+                        let picked = Level::new(event_target_value(&ev));
+                        set_level
+                            .update(|range| {
+                                range.to = picked;
+                                if let Some(ordered) = state.levels.get_untracked().normalize(range.clone()) {
+                                    *range = ordered;
+                                }
+                            });
+                    }
+                >
+                    {move || level_options(state)}
                 </select>
             </div>
 
+            <div
+                class="cluster"
+                style="\
+                --cluster-justify: flex-start; --cluster-gap: .75rem; margin-top: .75rem; \
+                flex-wrap: nowrap; \
+                "
+            >
+                <span style="flex: 0 0 auto; min-width: 3rem; text-align: left; opacity: .8;">
+                    "Spots"
+                </span>
+                <input
+                    type="number"
+                    min="1"
+                    class="surface"
+                    data-accent="base"
+                    placeholder="Unlimited"
+                    title="Maximum number of participants including you"
+                    style=move || {
+                        format!(
+                            "--accent: {}; flex: 1 1 auto; min-width: 0; padding: .5rem .75rem; border-radius: .5rem;",
+                            accent_color.get(),
+                        )
+                    }
+                    prop:value=move || capacity.get().map(|c| c.to_string()).unwrap_or_default()
+                    on:input=move |ev| {
+                        let v = event_target_value(&ev);
+                        set_capacity.set(v.trim().parse::<u32>().ok().filter(|c| *c > 0));
+                    }
+                />
+            </div>
+
+            <div
+                class="cluster"
+                style="\
+                --cluster-justify: flex-start; --cluster-gap: .75rem; margin-top: .75rem; \
+                flex-wrap: nowrap; \
+                "
+            >
+                <span style="flex: 0 0 auto; min-width: 3rem; text-align: left; opacity: .8;">
+                    "Balance"
+                </span>
+                <input
+                    type="number"
+                    min="0"
+                    class="surface"
+                    data-accent="base"
+                    placeholder="Leads"
+                    title="Number of leads you are looking for"
+                    style=move || {
+                        format!(
+                            "--accent: {}; flex: 1 1 auto; min-width: 0; padding: .5rem .75rem; border-radius: .5rem;",
+                            accent_color.get(),
+                        )
+                    }
+                    prop:value=move || {
+                        role_targets.get().map(|t| t.leads.to_string()).unwrap_or_default()
+                    }
+                    on:input=move |ev| {
+                        let leads = event_target_value(&ev).trim().parse::<u32>().unwrap_or(0);
+                        set_role_targets
+                            .update(|targets| {
+                                *targets = role_targets_with(*targets, Some(leads), None);
+                            });
+                    }
+                />
+                <input
+                    type="number"
+                    min="0"
+                    class="surface"
+                    data-accent="base"
+                    placeholder="Follows"
+                    title="Number of follows you are looking for"
+                    style=move || {
+                        format!(
+                            "--accent: {}; flex: 1 1 auto; min-width: 0; padding: .5rem .75rem; border-radius: .5rem;",
+                            accent_color.get(),
+                        )
+                    }
+                    prop:value=move || {
+                        role_targets.get().map(|t| t.follows.to_string()).unwrap_or_default()
+                    }
+                    on:input=move |ev| {
+                        let follows = event_target_value(&ev).trim().parse::<u32>().unwrap_or(0);
+                        set_role_targets
+                            .update(|targets| {
+                                *targets = role_targets_with(*targets, None, Some(follows));
+                            });
+                    }
+                />
+            </div>
+
             <div
                 class="cluster"
                 style="--cluster-justify: flex-start; --cluster-gap: .75rem; margin-top: .75rem;"
@@ -332,14 +525,15 @@ pub fn EventCardEditable(
                                 title="Reset to server version (discard local draft)"
                                 on:click=move |_| {
                                     if let Some(existing) = state.posts.get().get(&post_id) {
-                                        set_title.set(format!("{}", existing.title));
                                         set_ideas.set(existing.content.clone());
-                                        set_level.set(existing.level);
+                                        set_level.set(existing.level.clone());
+                                        set_capacity.set(existing.capacity);
+                                        set_role_targets.set(existing.role_targets);
                                         let d = convert_utc_to_local_date(existing.date)
                                             .format("%Y-%m-%d")
                                             .to_string();
                                         set_date_selected.set(d);
-                                        set_topics.set(existing.title);
+                                        set_topics.set(existing.topics.clone());
                                         clear_draft(post_id);
                                         set_has_draft.set(false);
                                     }
@@ -353,10 +547,24 @@ pub fn EventCardEditable(
                                 button_label="🗑️".to_string()
                                 button_title="Delete post".to_string()
                                 confirm_title="Delete this post?".to_string()
-                                confirm_message="This action cannot be undone.".to_string()
+                                confirm_message="You can undo this for a short while or restore the post from the archive."
+                                    .to_string()
                                 on_confirm=Callback::new({
                                     move |_| {
-                                        state.send(ClientToServer::DeletePost(post_id));
+                                        let removed = state.posts.get_untracked().get(&post_id).cloned();
+                                        write_state.posts.update(|posts| {
+                                            posts.remove(&post_id);
+                                        });
+                                        state.request(
+                                            ClientToServer::DeletePost(post_id),
+                                            move |reply| match (reply, removed) {
+                                                (Ok(()), _) => set_recently_deleted.set(Some(post_id)),
+                                                (Err(_), Some(post)) => write_state.posts.update(|posts| {
+                                                    posts.insert(post_id, post);
+                                                }),
+                                                (Err(_), None) => {}
+                                            },
+                                        );
                                     }
                                 })
                             />
@@ -367,3 +575,30 @@ pub fn EventCardEditable(
         </form>
     }
 }
+
+fn level_options(state: AppStateReader) -> impl IntoView {
+    state
+        .levels
+        .get()
+        .all()
+        .iter()
+        .map(|level| {
+            let value = level.to_string();
+            view! { <option value=value.clone()>{value.clone()}</option> }
+        })
+        .collect_view()
+}
+
+/// Applies an edited lead or follow target; clearing both removes the targets.
+fn role_targets_with(
+    current: Option<RoleTargets>,
+    leads: Option<u32>,
+    follows: Option<u32>,
+) -> Option<RoleTargets> {
+    let current = current.unwrap_or_default();
+    let updated = RoleTargets {
+        leads: leads.unwrap_or(current.leads),
+        follows: follows.unwrap_or(current.follows),
+    };
+    (updated != RoleTargets::default()).then_some(updated)
+}
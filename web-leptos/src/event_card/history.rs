@@ -0,0 +1,128 @@
+use crate::app_state::AppStateReader;
+use leptos::prelude::*;
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::history::{LineDiff, PostVersion, changed_fields, diff_lines};
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::post::PostId;
+
+/// Saved versions of a post, newest first, each with what changed before it.
+#[component]
+pub fn PostHistory(post_id: PostId, state: AppStateReader) -> impl IntoView {
+    state.send(ClientToServer::GetPostHistory(post_id));
+
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let version_view = move |previous: Option<PostVersion>, version: PostVersion| {
+        let heading = format!(
+            "{} by {}",
+            convert_utc_to_local(version.at).format("%Y-%m-%d %H:%M"),
+            name(version.by),
+        );
+        let changes = match &previous {
+            None => view! { <p style="margin: .25rem 0; opacity: .8;">"Created"</p> }.into_any(),
+            Some(previous) => {
+                let fields = changed_fields(&previous.post, &version.post, &state.topics.get());
+                let content = if previous.post.content == version.post.content {
+                    Vec::new()
+                } else {
+                    diff_lines(&previous.post.content, &version.post.content)
+                };
+                let unchanged = fields.is_empty() && content.is_empty();
+                view! {
+                    <Show when=move || unchanged>
+                        <p style="margin: .25rem 0; opacity: .8;">"Saved without changes"</p>
+                    </Show>
+                    {fields
+                        .into_iter()
+                        .map(|change| {
+                            view! {
+                                <div>
+                                    <strong>{change.field} ": "</strong>
+                                    <del style="color: var(--danger-color);">{change.before}</del>
+                                    " → "
+                                    <ins style="color: var(--success-color);">{change.after}</ins>
+                                </div>
+                            }
+                        })
+                        .collect_view()}
+                    <pre style="margin: .25rem 0; white-space: pre-wrap;">
+                        {content
+                            .into_iter()
+                            .map(|line| match line {
+                                LineDiff::Same(line) => {
+                                    view! { <div style="opacity: .6;">{format!("  {line}")}</div> }
+                                        .into_any()
+                                }
+                                LineDiff::Added(line) => {
+                                    view! {
+                                        <div style="color: var(--success-color);">
+                                            {format!("+ {line}")}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                                LineDiff::Removed(line) => {
+                                    view! {
+                                        <div style="color: var(--danger-color);">
+                                            {format!("- {line}")}
+                                        </div>
+                                    }
+                                        .into_any()
+                                }
+                            })
+                            .collect_view()}
+                    </pre>
+                }
+                .into_any()
+            }
+        };
+        view! {
+            <div
+                class="surface"
+                data-accent="base"
+                style="margin-top: .5rem; padding: .5rem .75rem; border-radius: .6rem;"
+            >
+                <div style="opacity: .85; font-size: .9em;">{heading}</div>
+                {changes}
+            </div>
+        }
+    };
+
+    view! {
+        <div style="margin-top: .75rem;">
+            {move || {
+                let versions = state
+                    .post_history
+                    .get()
+                    .get(&post_id)
+                    .cloned()
+                    .unwrap_or_default();
+                if versions.is_empty() {
+                    return view! { <p style="opacity: .8;">"No saved versions yet."</p> }
+                        .into_any();
+                }
+                let mut entries = versions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, version)| {
+                        let previous = index.checked_sub(1).map(|i| versions[i].clone());
+                        (previous, version.clone())
+                    })
+                    .collect::<Vec<_>>();
+                entries.reverse();
+                entries
+                    .into_iter()
+                    .map(|(previous, version)| version_view(previous, version))
+                    .collect_view()
+                    .into_any()
+            }}
+        </div>
+    }
+}
@@ -1,23 +1,31 @@
 use crate::app_state::AppStateReader;
 use crate::components::buttons::ServerButton;
+use crate::event_card::comments::CommentThread;
+use crate::event_card::history::PostHistory;
 use leptos::prelude::*;
-use peer_practice_shared::level::Level;
+use peer_practice_shared::dance_role::DanceRole;
+use peer_practice_shared::level::LevelRange;
 use peer_practice_shared::messages::ClientToServer;
-use peer_practice_shared::post::PostId;
+use peer_practice_shared::post::{Post, PostId};
+use peer_practice_shared::reaction::Reaction;
+use peer_practice_shared::topic::TopicId;
 use peer_practice_shared::user::UserId;
 use pulldown_cmark::{Options, Parser, html};
 use std::collections::HashSet;
 use std::sync::Arc;
 
+pub mod comments;
 pub mod editable;
+pub mod history;
 pub mod readonly;
 
 #[derive(Clone, PartialEq)]
 pub struct EventCardProps {
     pub id: PostId,
     pub title: String,
+    pub topics: Vec<TopicId>,
     pub date: String,
-    pub level: Level,
+    pub level: LevelRange,
     pub ideas: String,
     pub partaking: HashSet<UserId>,
     pub author: String,
@@ -55,11 +63,118 @@ fn event_card_footer(props: EventCardProps, state: AppStateReader) -> impl IntoV
         }
     };
 
+    let waiting = move || match state.user_id.get() {
+        None => false,
+        Some(id) => state
+            .posts
+            .get()
+            .get(&post_id)
+            .is_some_and(|post| post.is_waiting(&id)),
+    };
+    let full = move || state.posts.get().get(&post_id).is_some_and(Post::is_full);
+    let capacity = move || {
+        state
+            .posts
+            .get()
+            .get(&post_id)
+            .and_then(|post| post.capacity)
+            .map(|capacity| format!("/{capacity}"))
+            .unwrap_or_default()
+    };
+    let waitlist_len = move || {
+        state
+            .posts
+            .get()
+            .get(&post_id)
+            .map(|post| post.waitlist.len())
+            .unwrap_or_default()
+    };
+
+    let (role, set_role) = signal(
+        state
+            .user_id
+            .get_untracked()
+            .and_then(|id| {
+                state
+                    .posts
+                    .get_untracked()
+                    .get(&post_id)
+                    .map(|post| post.role_of(&id))
+            })
+            .unwrap_or_default(),
+    );
+    let balance = move || {
+        let posts = state.posts.get();
+        let Some(post) = posts.get(&post_id) else {
+            return String::new();
+        };
+        let counts = post.role_counts();
+        let mut balance = match post.role_targets {
+            Some(targets) => format!(
+                "Leads {}/{} · Follows {}/{}",
+                counts.leads, targets.leads, counts.follows, targets.follows
+            ),
+            None if counts.leads + counts.follows > 0 => {
+                format!("Leads {} · Follows {}", counts.leads, counts.follows)
+            }
+            None => return String::new(),
+        };
+        if counts.either > 0 {
+            balance.push_str(&format!(" · Either {}", counts.either));
+        }
+        balance
+    };
+
+    let reaction_button = move |reaction: Reaction| {
+        let count = move || {
+            state
+                .reactions
+                .get()
+                .get(&post_id)
+                .and_then(|counts| counts.get(&reaction).copied())
+                .unwrap_or_default()
+        };
+        let mine = move || {
+            state
+                .my_reactions
+                .get()
+                .get(&post_id)
+                .is_some_and(|mine| mine.contains(&reaction))
+        };
+        view! {
+            <button
+                type="button"
+                class="btn"
+                data-theme=move || if mine() { "primary" } else { "ghost" }
+                title=reaction.label()
+                style="padding: .25rem .5rem;"
+                on:click=move |_| state.send(ClientToServer::ToggleReaction(post_id, reaction))
+            >
+                {reaction.emoji()}
+                {move || match count() {
+                    0 => String::new(),
+                    n => format!(" {n}"),
+                }}
+            </button>
+        }
+    };
+
+    let (show_comments, set_show_comments) = signal(false);
+    let (show_history, set_show_history) = signal(false);
+    let comment_count = move || {
+        state
+            .comment_counts
+            .get()
+            .get(&post_id)
+            .copied()
+            .unwrap_or_default()
+    };
+
     let toggle_join = move || {
-        if partaking() {
+        if partaking() || waiting() {
             state.send(ClientToServer::Leave(props.id));
         } else {
-            state.send(ClientToServer::Join(props.id));
+            state.send(ClientToServer::JoinAs(props.id, role.get_untracked()));
         }
     };
 
@@ -68,20 +183,105 @@ fn event_card_footer(props: EventCardProps, state: AppStateReader) -> impl IntoV
             <div class="cluster" style="--cluster-gap: .75rem; --cluster-justify: flex-start;">
                 <span style="min-width: 3rem; text-align: left; opacity: .8;">"Joining"</span>
 
+                <select
+                    class="combo"
+                    title="Role you dance in this practice"
+                    style="flex: 0 0 auto; width: auto;"
+                    prop:value=move || role.get().as_str().to_string()
+                    on:change=move |ev| {
+                        let Ok(v) = event_target_value(&ev).parse::<DanceRole>() else {
+                            return;
+                        };
+                        set_role.set(v);
+                        if partaking() || waiting() {
+                            state.send(ClientToServer::JoinAs(post_id, v));
+                        }
+                    }
+                >
+                    {DanceRole::all()
+                        .iter()
+                        .map(|r| {
+                            let v = r.as_str().to_string();
+                            let label = r.to_string();
+                            view! { <option value=v.clone()>{label}</option> }
+                        })
+                        .collect_view()}
+                </select>
+
                 <ServerButton
                     class=Signal::derive(move || { "btn".to_string() })
-                    data_theme=Arc::new(move || { if partaking() { "success" } else { "primary" } })
+                    data_theme=Arc::new(move || {
+                        if partaking() {
+                            "success"
+                        } else if waiting() {
+                            "secondary"
+                        } else {
+                            "primary"
+                        }
+                    })
                     on_click=Callback::new(move |_| toggle_join())
                 >
-                    {move || if partaking() { "Joined".to_string() } else { "Join".to_string() }}
+                    {move || {
+                        if partaking() {
+                            "Joined".to_string()
+                        } else if waiting() {
+                            "Waitlisted".to_string()
+                        } else if full() {
+                            "Join waitlist".to_string()
+                        } else {
+                            "Join".to_string()
+                        }
+                    }}
                 </ServerButton>
 
                 <span style="display: inline-flex; align-items: center; gap: .35rem; opacity: .9;">
-                    "👥 " {move || count}
+                    "👥 " {move || count} {capacity}
                 </span>
+                <Show when=move || post_id != PostId::NULL>
+                    <span class="cluster" style="--cluster-gap: .25rem;">
+                        {Reaction::ALL.iter().copied().map(reaction_button).collect_view()}
+                    </span>
+                </Show>
+                <Show when=move || { waitlist_len() > 0 }>
+                    <span
+                        style="display: inline-flex; align-items: center; gap: .35rem; opacity: .9;"
+                        title="Waiting for a free spot"
+                    >
+                        "⏳ "
+                        {waitlist_len}
+                    </span>
+                </Show>
+                <span style="opacity: .8;">{balance}</span>
+                <Show when=move || post_id != PostId::NULL>
+                    <button
+                        type="button"
+                        class="btn"
+                        data-theme="ghost"
+                        title="Show or hide comments"
+                        on:click=move |_| set_show_comments.update(|show| *show = !*show)
+                    >
+                        "💬 "
+                        {comment_count}
+                    </button>
+                    <button
+                        type="button"
+                        class="btn"
+                        data-theme="ghost"
+                        title="Show what changed in this post"
+                        on:click=move |_| set_show_history.update(|show| *show = !*show)
+                    >
+                        "🕘"
+                    </button>
+                </Show>
             </div>
             <em style="opacity: .8;">{"by "} {props.author.to_string()}</em>
         </div>
+        <Show when=move || show_history.get()>
+            <PostHistory post_id=post_id state=state />
+        </Show>
+        <Show when=move || show_comments.get()>
+            <CommentThread post_id=post_id state=state />
+        </Show>
     }
 }
 
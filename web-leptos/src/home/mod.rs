@@ -2,16 +2,42 @@ use crate::app_state::AppStateReader;
 use crate::event_card::{EventCardProps, editable::EventCardEditable, readonly::EventCardReadonly};
 use leptos::prelude::*;
 use peer_practice_shared::convert_utc_to_local_date;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::post::PostId;
+use peer_practice_shared::search::PostQuery;
 use peer_practice_shared::user::UserId;
+use search::SearchBar;
 use std::collections::HashSet;
 
+mod search;
+
 #[component]
 pub fn Home(#[prop(into)] state: AppStateReader) -> impl IntoView {
     let read_new_post: ReadSignal<Option<EventCardProps>> = expect_context();
     let write_new_post: WriteSignal<Option<EventCardProps>> = expect_context();
+    let (query, set_query) = signal(PostQuery::default());
+
+    // Searching again when posts change keeps the results current.
+    Effect::new(move |_| {
+        let query = query.get();
+        state.posts.track();
+        if !query.is_empty() {
+            state.send(ClientToServer::SearchPosts(query));
+        }
+    });
+
+    // `None` shows every post, ordered by date.
+    let matches = move || {
+        let query = query.get();
+        if query.is_empty() {
+            return None;
+        }
+        state.search_results.get().map(|(_, results)| results)
+    };
 
     view! {
         <div>
+            <SearchBar state set_query />
             <Show when=move || {
                 read_new_post.get().is_some()
             }>
@@ -36,19 +62,26 @@ pub fn Home(#[prop(into)] state: AppStateReader) -> impl IntoView {
 
             {move || {
                 let current_user = state.user_id.get();
-                let mut items = state
-                    .posts
-                    .get()
+                let topics = state.topics.get();
+                let posts = state.posts.get();
+                let matches = matches();
+                let ids = match &matches {
+                    Some(results) => results.clone(),
+                    None => posts.keys().copied().collect::<Vec<PostId>>(),
+                };
+                let mut items = ids
                     .iter()
+                    .filter_map(|id| posts.get(id).map(|post| (id, post)))
                     .map(|(&id, post)| (
                         post.owner,
                         EventCardProps {
                             id,
-                            title: format!("{}", post.title),
+                            title: post.title(&topics),
+                            topics: post.topics.clone(),
                             date: convert_utc_to_local_date(post.date)
                                 .format("%Y-%m-%d")
                                 .to_string(),
-                            level: post.level,
+                            level: post.level.clone(),
                             ideas: post.content.clone(),
                             partaking: post.partaking_users.iter().cloned().collect::<HashSet<_>>(),
                             author: state
@@ -60,10 +93,12 @@ pub fn Home(#[prop(into)] state: AppStateReader) -> impl IntoView {
                         },
                     ))
                     .collect::<Vec<(UserId, EventCardProps)>>();
-                items
-                    .sort_by(|a, b| {
-                        a.1.date.cmp(&b.1.date).then_with(|| a.1.title.cmp(&b.1.title))
-                    });
+                if matches.is_none() {
+                    items
+                        .sort_by(|a, b| {
+                            a.1.date.cmp(&b.1.date).then_with(|| a.1.title.cmp(&b.1.title))
+                        });
+                }
                 items
                     .into_iter()
                     .map(|(owner, props)| {
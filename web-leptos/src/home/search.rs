@@ -0,0 +1,168 @@
+use chrono::{Days, NaiveDate};
+use leptos::prelude::*;
+use std::str::FromStr;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_to_utc;
+use peer_practice_shared::level::Level;
+use peer_practice_shared::search::PostQuery;
+use peer_practice_shared::topic::TopicId;
+
+const INPUT_STYLE: &str = "--accent: var(--bg-strongest-color); padding: .5rem .75rem; border-radius: .6rem; border: 1px solid currentColor; min-width: 0;";
+
+/// Text search and filters over all posts; the search itself runs on the server.
+#[component]
+pub fn SearchBar(state: AppStateReader, set_query: WriteSignal<PostQuery>) -> impl IntoView {
+    let (text, set_text) = signal(String::new());
+    let (level, set_level) = signal(String::new());
+    let (topic, set_topic) = signal(String::new());
+    let (from, set_from) = signal(String::new());
+    let (to, set_to) = signal(String::new());
+    let (owner, set_owner) = signal(String::new());
+    let (joined_by_me, set_joined_by_me) = signal(false);
+
+    // Everyone who has posts, by name.
+    let owners = move || {
+        let users = state.users.get();
+        let mut owners = state
+            .posts
+            .get()
+            .values()
+            .map(|post| post.owner)
+            .collect::<Vec<_>>();
+        owners.sort();
+        owners.dedup();
+        let mut owners = owners
+            .into_iter()
+            .map(|id| {
+                let name = users
+                    .get(&id)
+                    .and_then(|user| user.display_name.clone())
+                    .unwrap_or_else(|| "-".to_string());
+                (id, name)
+            })
+            .collect::<Vec<_>>();
+        owners.sort_by(|a, b| a.1.cmp(&b.1));
+        owners
+    };
+
+    Effect::new(move |_| {
+        let day = |value: String| NaiveDate::parse_from_str(&value, "%Y-%m-%d").ok();
+        set_query.set(PostQuery {
+            text: text.get().trim().to_string(),
+            level: Some(level.get())
+                .filter(|level| !level.is_empty())
+                .map(Level::new),
+            topics: TopicId::from_str(&topic.get()).into_iter().collect(),
+            from: day(from.get()).map(convert_to_utc),
+            until: day(to.get())
+                .and_then(|day| day.checked_add_days(Days::new(1)))
+                .map(convert_to_utc),
+            owner: owner
+                .get()
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| owners().get(index).map(|(id, _)| *id)),
+            joined_by_me: joined_by_me.get(),
+        });
+    });
+
+    view! {
+        <div
+            class="cluster no-print"
+            style="--cluster-justify: flex-start; --cluster-gap: .5rem; margin: .5rem 1rem;"
+        >
+            <input
+                type="search"
+                data-theme="base"
+                style=format!("{INPUT_STYLE} flex: 1 1 12rem;")
+                placeholder="Search posts"
+                prop:value=text
+                on:input=move |ev| set_text.set(event_target_value(&ev))
+            />
+            <select
+                class="combo"
+                style="width: auto;"
+                prop:value=level
+                on:change=move |ev| set_level.set(event_target_value(&ev))
+            >
+                <option value="">"Any level"</option>
+                {move || {
+                    state
+                        .levels
+                        .get()
+                        .all()
+                        .iter()
+                        .map(|level| {
+                            let name = level.to_string();
+                            view! { <option value=name.clone()>{name.clone()}</option> }
+                        })
+                        .collect_view()
+                }}
+            </select>
+            <select
+                class="combo"
+                style="width: auto;"
+                prop:value=topic
+                on:change=move |ev| set_topic.set(event_target_value(&ev))
+            >
+                <option value="">"Any topic"</option>
+                {move || {
+                    let mut topics = state.topics.get().into_iter().collect::<Vec<_>>();
+                    topics.sort_by(|a, b| a.1.name.cmp(&b.1.name));
+                    topics
+                        .into_iter()
+                        .map(|(id, topic)| {
+                            view! { <option value=id.to_string()>{topic.name}</option> }
+                        })
+                        .collect_view()
+                }}
+            </select>
+            <select
+                class="combo"
+                style="width: auto;"
+                prop:value=owner
+                on:change=move |ev| set_owner.set(event_target_value(&ev))
+            >
+                <option value="">"Anyone's posts"</option>
+                {move || {
+                    owners()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, (_, name))| {
+                            view! { <option value=index.to_string()>{name}</option> }
+                        })
+                        .collect_view()
+                }}
+            </select>
+            <label class="cluster" style="--cluster-gap: .25rem;">
+                "From"
+                <input
+                    type="date"
+                    data-theme="base"
+                    style=INPUT_STYLE
+                    prop:value=from
+                    on:change=move |ev| set_from.set(event_target_value(&ev))
+                />
+            </label>
+            <label class="cluster" style="--cluster-gap: .25rem;">
+                "To"
+                <input
+                    type="date"
+                    data-theme="base"
+                    style=INPUT_STYLE
+                    prop:value=to
+                    on:change=move |ev| set_to.set(event_target_value(&ev))
+                />
+            </label>
+            <label class="cluster" style="--cluster-gap: .25rem;">
+                <input
+                    type="checkbox"
+                    prop:checked=joined_by_me
+                    on:change=move |ev| set_joined_by_me.set(event_target_checked(&ev))
+                />
+                "Joined by me"
+            </label>
+        </div>
+    }
+}
@@ -0,0 +1,207 @@
+use leptos::prelude::*;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::direct_message::{DirectMessage, conversations};
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::user::UserId;
+
+const PREVIEW_LENGTH: usize = 60;
+
+/// Private conversations of the signed-in user.
+#[component]
+pub fn Inbox(state: AppStateReader) -> impl IntoView {
+    let (partner, set_partner) = signal(None::<UserId>);
+    let (draft, set_draft) = signal(String::new());
+
+    let name = move |id: UserId| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let inbox = move || match state.user_id.get() {
+        Some(me) => conversations(me, &state.direct_messages.get()),
+        None => Vec::new(),
+    };
+
+    // Everyone we know a name of and have not written with yet.
+    let new_partners = move || {
+        let me = state.user_id.get();
+        let known = inbox()
+            .into_iter()
+            .map(|conversation| conversation.partner)
+            .collect::<Vec<_>>();
+        let mut users = state
+            .users
+            .get()
+            .into_iter()
+            .filter(|(id, user)| {
+                Some(*id) != me && !known.contains(id) && user.display_name.is_some()
+            })
+            .map(|(id, user)| (id, user.display_name.unwrap_or_default()))
+            .collect::<Vec<_>>();
+        users.sort_by(|a, b| a.1.cmp(&b.1));
+        users
+    };
+
+    // Reading a conversation marks it read, also when messages arrive while it is open.
+    Effect::new(move |_| {
+        let (Some(me), Some(partner)) = (state.user_id.get(), partner.get()) else {
+            return;
+        };
+        let unread = state
+            .direct_messages
+            .get()
+            .values()
+            .any(|message| message.to == me && message.from == partner && !message.read);
+        if unread {
+            state.send(ClientToServer::MarkConversationRead(partner));
+        }
+    });
+
+    let messages = move || {
+        let (Some(me), Some(partner)) = (state.user_id.get(), partner.get()) else {
+            return Vec::new();
+        };
+        let mut messages = state
+            .direct_messages
+            .get()
+            .into_values()
+            .filter(|message| message.partner_of(me) == Some(partner))
+            .collect::<Vec<_>>();
+        messages.sort_by_key(|message| message.sent);
+        messages
+    };
+
+    let send = move || {
+        let Some(partner) = partner.get_untracked() else {
+            return;
+        };
+        let content = draft.get_untracked().trim().to_string();
+        if content.is_empty() || content.len() > DirectMessage::MAX_LENGTH {
+            return;
+        }
+        state.send(ClientToServer::SendDirectMessage(partner, content));
+        set_draft.set(String::new());
+    };
+
+    let message_view = move |message: DirectMessage| {
+        let mine = state.user_id.get() == Some(message.from);
+        view! {
+            <div
+                class="surface"
+                data-accent="base"
+                style=format!(
+                    "max-width: 80%; align-self: {}; padding: .5rem .75rem; border-radius: .6rem; white-space: pre-wrap;",
+                    if mine { "flex-end" } else { "flex-start" },
+                )
+            >
+                <div>{message.content}</div>
+                <div style="opacity: .6; font-size: .8em; text-align: right;">
+                    {convert_utc_to_local(message.sent).format("%Y-%m-%d %H:%M").to_string()}
+                    {(mine && message.read).then_some(" · read")}
+                </div>
+            </div>
+        }
+    };
+
+    view! {
+        <div class="card">
+            <h2 class="card-title">"Messages"</h2>
+            <div style="display: grid; grid-template-columns: minmax(10rem, 1fr) 3fr; gap: 1rem; margin-top: 1rem;">
+                <div style="display: flex; flex-direction: column; gap: .5rem;">
+                    {move || {
+                        let selected = partner.get();
+                        inbox()
+                            .into_iter()
+                            .map(|conversation| {
+                                let preview = state
+                                    .direct_messages
+                                    .get()
+                                    .get(&conversation.last)
+                                    .map(|message| {
+                                        message.content.chars().take(PREVIEW_LENGTH).collect::<String>()
+                                    })
+                                    .unwrap_or_default();
+                                let id = conversation.partner;
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="btn"
+                                        data-theme=if selected == Some(id) { "primary" } else { "secondary" }
+                                        style="display: flex; flex-direction: column; align-items: flex-start; text-align: left;"
+                                        on:click=move |_| set_partner.set(Some(id))
+                                    >
+                                        <span style="font-weight: 600;">
+                                            {name(id)}
+                                            {(conversation.unread > 0)
+                                                .then(|| format!(" ({})", conversation.unread))}
+                                        </span>
+                                        <span style="opacity: .7; font-size: .85em;">{preview}</span>
+                                    </button>
+                                }
+                            })
+                            .collect_view()
+                    }}
+                    <select
+                        class="combo"
+                        prop:value=""
+                        on:change=move |ev| {
+                            let index = event_target_value(&ev).parse::<usize>().ok();
+                            if let Some((id, _)) = index.and_then(|i| new_partners().get(i).cloned()) {
+                                set_partner.set(Some(id));
+                            }
+                        }
+                    >
+                        <option value="" disabled=true>
+                            "New conversation…"
+                        </option>
+                        {move || {
+                            new_partners()
+                                .into_iter()
+                                .enumerate()
+                                .map(|(index, (_, name))| {
+                                    view! { <option value=index.to_string()>{name}</option> }
+                                })
+                                .collect_view()
+                        }}
+                    </select>
+                </div>
+                <Show
+                    when=move || partner.get().is_some()
+                    fallback=|| {
+                        view! { <p style="opacity: .8;">"Pick a conversation or start a new one."</p> }
+                    }
+                >
+                    <div style="display: flex; flex-direction: column; gap: .5rem;">
+                        <h3 style="margin: 0;">{move || partner.get().map(name)}</h3>
+                        {move || messages().into_iter().map(message_view).collect_view()}
+                        <div class="cluster" style="--cluster-gap: .5rem; align-items: flex-end;">
+                            <textarea
+                                data-theme="base"
+                                style="--accent: var(--bg-strongest-color); flex: 1 1 auto; min-height: 3rem; padding: .5rem .75rem; border-radius: .6rem; border: 1px solid currentColor;"
+                                placeholder="Write a message"
+                                maxlength=DirectMessage::MAX_LENGTH
+                                prop:value=draft
+                                on:input=move |ev| set_draft.set(event_target_value(&ev))
+                            />
+                            <button
+                                type="button"
+                                class="btn"
+                                data-theme="primary"
+                                disabled=move || draft.get().trim().is_empty()
+                                on:click=move |_| send()
+                            >
+                                "Send"
+                            </button>
+                        </div>
+                    </div>
+                </Show>
+            </div>
+        </div>
+    }
+}
@@ -1,21 +1,27 @@
-use crate::app_state::{AppStateReader, initialize_app_state};
+use crate::app_state::{AppStateReader, AppStateWriter, initialize_app_state};
 use crate::event_card::EventCardProps;
 use crate::nav_menu::NavMenu;
 use leptos::logging::log;
 use leptos::prelude::*;
 use leptos_router::components::{Route, Router, Routes};
 use leptos_router::{NavigateOptions, path};
-use peer_practice_shared::level::Level;
+use peer_practice_shared::level::{Level, LevelRange};
+use peer_practice_shared::messages::ClientToServer;
 use peer_practice_shared::post::PostId;
 use peer_practice_shared::ymd;
 use std::collections::HashSet;
 
 mod app_state;
+mod archive;
 mod components;
 pub mod event_card;
 pub mod home;
+mod inbox;
 mod login;
+mod notifications;
+mod rotation;
 mod settings;
+mod stats;
 mod websocket;
 
 #[component]
@@ -34,6 +40,8 @@ pub fn App() -> impl IntoView {
     let (read_new_post, write_new_post) = signal::<Option<EventCardProps>>(None);
     provide_context(read_new_post);
     provide_context(write_new_post);
+    let (recently_deleted, set_recently_deleted) = signal::<Option<PostId>>(None);
+    provide_context(set_recently_deleted);
 
     Effect::new(move |_| {
         log!("Redirecting on ws state");
@@ -109,6 +117,7 @@ pub fn App() -> impl IntoView {
                             class="nav-icon-bar"
                             style="display: flex; align-items: center; gap: 0.5rem;"
                         >
+                            <notifications::NotificationBell state />
                             <CreateNewPost state read_new_post write_new_post />
                             <ConnectionStatus state />
                         </div>
@@ -154,6 +163,22 @@ pub fn App() -> impl IntoView {
                                         path=path!("/")
                                         view=move || view! { <home::Home state /> }
                                     />
+                                    <Route
+                                        path=path!("/rotation")
+                                        view=move || view! { <rotation::Rotation state /> }
+                                    />
+                                    <Route
+                                        path=path!("/inbox")
+                                        view=move || view! { <inbox::Inbox state /> }
+                                    />
+                                    <Route
+                                        path=path!("/archive")
+                                        view=move || view! { <archive::Archive state /> }
+                                    />
+                                    <Route
+                                        path=path!("/stats")
+                                        view=move || view! { <stats::Stats state /> }
+                                    />
                                     <Route
                                         path=path!("/settings")
                                         view=move || view! { <settings::Settings state /> }
@@ -165,6 +190,11 @@ pub fn App() -> impl IntoView {
                     }}
                 </Show>
             </main>
+            <div class="toast-container">
+                <UndoDeleteToast state recently_deleted set_recently_deleted />
+                <NoticeToast state write_state />
+                <ReloadToast state />
+            </div>
         </Router>
     }
 }
@@ -203,8 +233,17 @@ fn CreateNewPost(
                 let draft = EventCardProps {
                     id: PostId::NULL,
                     title: String::new(),
+                    topics: Vec::new(),
                     date: ymd::create_date_options().first().unwrap().clone(),
-                    level: Level::Beginner1,
+                    level: LevelRange::single(
+                        state
+                            .levels
+                            .get_untracked()
+                            .all()
+                            .first()
+                            .cloned()
+                            .unwrap_or_else(|| Level::new("-")),
+                    ),
                     ideas: String::new(),
                     partaking: HashSet::new(),
                     author: author_name,
@@ -226,6 +265,126 @@ fn CreateNewPost(
     }
 }
 
+/// How long a deleted post can be brought back with a single click.
+const UNDO_DELETE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(8);
+
+#[component]
+fn UndoDeleteToast(
+    state: AppStateReader,
+    recently_deleted: ReadSignal<Option<PostId>>,
+    set_recently_deleted: WriteSignal<Option<PostId>>,
+) -> impl IntoView {
+    Effect::new(move |_| {
+        if let Some(id) = recently_deleted.get() {
+            set_timeout(
+                move || {
+                    set_recently_deleted.update(|deleted| {
+                        if *deleted == Some(id) {
+                            *deleted = None;
+                        }
+                    })
+                },
+                UNDO_DELETE_TIMEOUT,
+            );
+        }
+    });
+
+    view! {
+        <Show when=move || recently_deleted.get().is_some()>
+            <div
+                class="toast cluster"
+                role="status"
+                style="--cluster-gap: .75rem; pointer-events: auto;"
+            >
+                "Post deleted."
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="secondary"
+                    style="padding: .25rem .6rem;"
+                    on:click=move |_| {
+                        if let Some(id) = recently_deleted.get_untracked() {
+                            state.send(ClientToServer::RestorePost(id));
+                        }
+                        set_recently_deleted.set(None);
+                    }
+                >
+                    "Undo"
+                </button>
+            </div>
+        </Show>
+    }
+}
+
+const NOTICE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Tells the user whether the server carried out what they asked for.
+#[component]
+fn NoticeToast(state: AppStateReader, write_state: AppStateWriter) -> impl IntoView {
+    Effect::new(move |_| {
+        if let Some(notice) = state.notice.get() {
+            set_timeout(
+                move || {
+                    write_state.notice.update(|current| {
+                        if current.as_ref() == Some(&notice) {
+                            *current = None;
+                        }
+                    })
+                },
+                NOTICE_TIMEOUT,
+            );
+        }
+    });
+
+    view! {
+        {move || {
+            state
+                .notice
+                .get()
+                .map(|notice| {
+                    view! {
+                        <div
+                            class="toast"
+                            role=if notice.failed { "alert" } else { "status" }
+                            data-theme=if notice.failed { "danger" } else { "success" }
+                            style="pointer-events: auto;"
+                            on:click=move |_| write_state.notice.set(None)
+                        >
+                            {notice.text}
+                        </div>
+                    }
+                })
+        }}
+    }
+}
+
+/// Asks for a reload once the server stopped speaking this client's version,
+/// e.g. after a deploy while the page was open.
+#[component]
+fn ReloadToast(state: AppStateReader) -> impl IntoView {
+    view! {
+        <Show when=move || state.reload_required.get()>
+            <div
+                class="toast"
+                role="alert"
+                data-theme="danger"
+                style="pointer-events: auto; display:flex; align-items:center; gap:.75rem;"
+            >
+                <span>"A new version of this app is available."</span>
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="secondary"
+                    style="padding: .25rem .6rem;"
+                    on:click=move |_| _ = window().location().reload()
+                >
+                    "Reload"
+                </button>
+            </div>
+        </Show>
+    }
+}
+
 #[component]
 fn ConnectionStatus(state: AppStateReader) -> impl IntoView {
     let color = move || {
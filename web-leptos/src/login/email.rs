@@ -5,6 +5,7 @@ use crate::host;
 use peer_practice_shared::authentication::login_data::LoginData;
 use peer_practice_shared::authentication::method::AuthenticationMethod;
 use peer_practice_shared::email::Email;
+use peer_practice_shared::language::Language;
 use peer_practice_shared::user::UserId;
 
 #[component]
@@ -13,6 +14,7 @@ pub fn LoginEmailStep(
     #[prop(into)] write_user_id: WriteSignal<Option<UserId>>,
 ) -> impl IntoView {
     let (email_read, email_write) = signal(String::new());
+    let (error_read, error_write) = signal(None::<&'static str>);
 
     let on_submit = {
         move |ev: leptos::ev::SubmitEvent| {
@@ -26,6 +28,11 @@ pub fn LoginEmailStep(
                         // Assuming Email implements Into from String in the shared crate
                         email: Email::new(&email_clone).unwrap(),
                         auth: AuthenticationMethod::EmailOTP,
+                        language: window()
+                            .navigator()
+                            .language()
+                            .as_deref()
+                            .and_then(Language::from_tag),
                     };
 
                     log!("Initiating login with email: {}", email_clone);
@@ -36,6 +43,12 @@ pub fn LoginEmailStep(
                         .send()
                         .await
                     {
+                        Ok(resp) if resp.status() == reqwest::StatusCode::UNPROCESSABLE_ENTITY => {
+                            error_write.set(Some(
+                                "We couldn't send an email to this address. Please check it.",
+                            ));
+                            return;
+                        }
                         Ok(resp) => {
                             if let Err(e) = resp.error_for_status_ref() {
                                 log!("Login initiation failed (non-2xx): {}", e);
@@ -70,9 +83,17 @@ pub fn LoginEmailStep(
                     placeholder="you@example.com"
                     prop:value=Signal::derive(move || email_read.get())
                     autofocus=true
-                    on:input=move |ev| email_write.set(event_target_value(&ev).trim().to_string())
+                    on:input=move |ev| {
+                        error_write.set(None);
+                        email_write.set(event_target_value(&ev).trim().to_string())
+                    }
                 />
             </div>
+            <Show when=move || error_read.get().is_some()>
+                <p class="text-sm" style="color: var(--danger-color);">
+                    {move || error_read.get()}
+                </p>
+            </Show>
             <div class="mt-4 flex gap-2 justify-end">
                 <button
                     type="submit"
@@ -1,6 +1,8 @@
+use crate::app_state::AppStateReader;
 use crate::components::modal::CenterModal;
 use leptos::prelude::*;
 use leptos::{IntoView, component};
+use peer_practice_shared::direct_message::unread_count;
 
 #[component]
 pub fn NavMenu() -> impl IntoView {
@@ -36,8 +38,18 @@ pub fn NavMenu() -> impl IntoView {
         )
     }
 
+    let state = expect_context::<AppStateReader>();
+    let unread = move || match state.user_id.get() {
+        Some(me) => unread_count(me, &state.direct_messages.get()),
+        None => 0,
+    };
+
     let current_page_label = move || match location().as_str() {
         "/" => "Home".to_string(),
+        "/rotation" => "Rotation".to_string(),
+        "/inbox" => "Messages".to_string(),
+        "/archive" => "Archive".to_string(),
+        "/stats" => "Statistics".to_string(),
         "/settings" => "Settings".to_string(),
         other => {
             let seg = other.trim_end_matches('/').rsplit('/').next().unwrap_or("");
@@ -82,6 +94,9 @@ pub fn NavMenu() -> impl IntoView {
                     <span style="width:18px; height:2px; background: currentColor; border-radius:2px;"></span>
                 </span>
                 <span style="font-weight: 600;">{current_page_label}</span>
+                <Show when=move || { unread() > 0 }>
+                    <span title="Unread messages">"✉ " {unread}</span>
+                </Show>
             </button>
 
             <Show when=move || menu_open.get()>
@@ -102,6 +117,57 @@ pub fn NavMenu() -> impl IntoView {
                                 >
                                     "Home"
                                 </a>
+                                <a
+                                    href="/rotation"
+                                    class="btn"
+                                    data-theme="accent"
+                                    data-accent="base"
+                                    style=move || {
+                                        let active = &location() == "/rotation";
+                                        nav_link_style(active, &accent_name.get())
+                                    }
+                                >
+                                    "Rotation"
+                                </a>
+                                <a
+                                    href="/inbox"
+                                    class="btn"
+                                    data-theme="accent"
+                                    data-accent="base"
+                                    style=move || {
+                                        let active = &location() == "/inbox";
+                                        nav_link_style(active, &accent_name.get())
+                                    }
+                                >
+                                    {move || match unread() {
+                                        0 => "Messages".to_string(),
+                                        n => format!("Messages ({n})"),
+                                    }}
+                                </a>
+                                <a
+                                    href="/archive"
+                                    class="btn"
+                                    data-theme="accent"
+                                    data-accent="base"
+                                    style=move || {
+                                        let active = &location() == "/archive";
+                                        nav_link_style(active, &accent_name.get())
+                                    }
+                                >
+                                    "Archive"
+                                </a>
+                                <a
+                                    href="/stats"
+                                    class="btn"
+                                    data-theme="accent"
+                                    data-accent="base"
+                                    style=move || {
+                                        let active = &location() == "/stats";
+                                        nav_link_style(active, &accent_name.get())
+                                    }
+                                >
+                                    "Statistics"
+                                </a>
                                 <a
                                     href="/settings"
                                     class="btn"
@@ -0,0 +1,278 @@
+use leptos::logging::log;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::notification::{Channels, NotificationPreferences};
+
+mod push;
+
+/// Notifications shown in the panel, older ones stay on the server.
+const SHOWN_NOTIFICATIONS: usize = 30;
+
+/// Bell in the navigation bar that lists the user's notifications.
+#[component]
+pub fn NotificationBell(state: AppStateReader) -> impl IntoView {
+    let (open, set_open) = signal(false);
+    let unread = move || {
+        state
+            .notifications
+            .get()
+            .values()
+            .filter(|notification| !notification.read)
+            .count()
+    };
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "Someone".to_string())
+    };
+
+    view! {
+        <div style="position: relative;">
+            <button
+                type="button"
+                aria-label="Notifications"
+                title="Notifications"
+                style="background: none; border: none; cursor: pointer; font-size: 1.25rem; position: relative;"
+                on:click=move |_| {
+                    let opening = !open.get_untracked();
+                    set_open.set(opening);
+                    if opening && unread() > 0 {
+                        state.send(ClientToServer::MarkNotificationsRead);
+                    }
+                }
+            >
+                "🔔"
+                <Show when=move || { unread() > 0 }>
+                    <span style="position: absolute; top: -.25rem; right: -.4rem; font-size: .7rem; background: var(--danger-color); color: white; border-radius: 9999px; padding: 0 .3rem;">
+                        {unread}
+                    </span>
+                </Show>
+            </button>
+            <Show when=move || open.get()>
+                <div
+                    class="card"
+                    style="position: fixed; top: calc(var(--navbar-height, 48px) + 6px); right: .75rem; width: min(24rem, 90vw); max-height: 70vh; overflow-y: auto; z-index: 2000;"
+                >
+                    <h2 class="card-title">"Notifications"</h2>
+                    {move || {
+                        let mut notifications = state
+                            .notifications
+                            .get()
+                            .into_values()
+                            .collect::<Vec<_>>();
+                        if notifications.is_empty() {
+                            return view! { <p>"Nothing new."</p> }.into_any();
+                        }
+                        notifications.sort_by_key(|notification| std::cmp::Reverse(notification.at));
+                        notifications.truncate(SHOWN_NOTIFICATIONS);
+                        notifications
+                            .into_iter()
+                            .map(|notification| {
+                                let weight = if notification.read { "normal" } else { "600" };
+                                view! {
+                                    <div style=format!(
+                                        "margin-top: .5rem; font-weight: {weight};",
+                                    )>
+                                        {notification.text(name)}
+                                        <div style="font-size: .8em; opacity: .7;">
+                                            {convert_utc_to_local(notification.at)
+                                                .format("%Y-%m-%d %H:%M")
+                                                .to_string()}
+                                        </div>
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                            .into_any()
+                    }}
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+type Field = fn(&mut NotificationPreferences) -> &mut Channels;
+type Channel = fn(&mut Channels) -> &mut bool;
+
+/// Which notifications the user gets, and through which channel.
+#[component]
+pub fn NotificationSettings(state: AppStateReader) -> impl IntoView {
+    let kinds: [(&str, Field); 5] = [
+        ("Someone joins my post", |prefs| &mut prefs.joined_my_post),
+        ("A post I joined moves", |prefs| &mut prefs.post_changed),
+        ("A post I joined is deleted", |prefs| {
+            &mut prefs.post_deleted
+        }),
+        ("New post for my level", |prefs| {
+            &mut prefs.new_post_for_level
+        }),
+        ("Reminder before a post I joined", |prefs| {
+            &mut prefs.reminder
+        }),
+    ];
+    let channels: [(&str, Channel); 3] = [
+        ("In app", |channels| &mut channels.in_app),
+        ("Email", |channels| &mut channels.email),
+        ("Push", |channels| &mut channels.push),
+    ];
+
+    view! {
+        <div class="card" style="margin-top: 1rem;">
+            <h2 class="card-title">"Notifications"</h2>
+            <div style="display: grid; grid-template-columns: 1fr max-content max-content max-content; column-gap: .75rem; row-gap: .5rem; align-items: center; margin-top: 1rem;">
+                <span></span>
+                {channels.map(|(label, _)| view! { <strong>{label}</strong> }).collect_view()}
+                {kinds
+                    .map(|(label, field)| {
+                        view! {
+                            <span>{label}</span>
+                            {channels
+                                .map(|(_, channel)| {
+                                    let checked = move || {
+                                        state
+                                            .user_config
+                                            .get()
+                                            .map(|mut config| {
+                                                *channel(field(&mut config.notifications))
+                                            })
+                                            .unwrap_or_default()
+                                    };
+                                    view! {
+                                        <input
+                                            type="checkbox"
+                                            style="justify-self: center;"
+                                            prop:checked=checked
+                                            prop:disabled=move || state.user_config.get().is_none()
+                                            on:change=move |ev| {
+                                                let mut config = state
+                                                    .user_config
+                                                    .get_untracked()
+                                                    .unwrap_or_default();
+                                                *channel(field(&mut config.notifications)) = event_target_checked(
+                                                    &ev,
+                                                );
+                                                state.send(ClientToServer::UpdateUserConfig(config));
+                                            }
+                                        />
+                                    }
+                                })
+                                .collect_view()}
+                        }
+                    })
+                    .collect_view()}
+            </div>
+            <label style="margin-top: 1rem; display: flex; align-items: center; gap: .5rem;">
+                "Remind me"
+                <input
+                    type="number"
+                    min=*NotificationPreferences::REMINDER_HOURS_BEFORE.start()
+                    max=*NotificationPreferences::REMINDER_HOURS_BEFORE.end()
+                    style="width: 5rem;"
+                    prop:value=move || {
+                        state
+                            .user_config
+                            .get()
+                            .map(|config| config.notifications.reminder_hours_before)
+                            .unwrap_or_default()
+                    }
+                    prop:disabled=move || state.user_config.get().is_none()
+                    on:change=move |ev| {
+                        let Ok(hours) = event_target_value(&ev).parse::<u32>() else {
+                            return;
+                        };
+                        let mut config = state.user_config.get_untracked().unwrap_or_default();
+                        config.notifications.reminder_hours_before = hours.clamp(
+                            *NotificationPreferences::REMINDER_HOURS_BEFORE.start(),
+                            *NotificationPreferences::REMINDER_HOURS_BEFORE.end(),
+                        );
+                        state.send(ClientToServer::UpdateUserConfig(config));
+                    }
+                />
+                "hours before a practice I joined starts"
+            </label>
+            <PushDevice state />
+        </div>
+    }
+}
+
+/// Subscribes or unsubscribes this browser from Web Push.
+#[component]
+fn PushDevice(state: AppStateReader) -> impl IntoView {
+    let (subscribed, set_subscribed) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+    spawn_local(async move {
+        if let Ok(current) = push::current().await {
+            set_subscribed.set(current.is_some());
+        }
+    });
+
+    let toggle = move |_| {
+        set_error.set(None);
+        let key = state.vapid_public_key.get_untracked();
+        spawn_local(async move {
+            if subscribed.get_untracked() {
+                match push::unsubscribe().await {
+                    Ok(endpoint) => {
+                        if let Some(endpoint) = endpoint {
+                            state.send(ClientToServer::UnsubscribePush(endpoint));
+                        }
+                        set_subscribed.set(false);
+                    }
+                    Err(err) => log!("Failed to unsubscribe from push: {:?}", err),
+                }
+                return;
+            }
+            let Some(key) = key else {
+                return;
+            };
+            match push::subscribe(&key).await {
+                Ok(subscription) => {
+                    state.send(ClientToServer::SubscribePush(subscription));
+                    set_subscribed.set(true);
+                }
+                Err(err) => {
+                    log!("Failed to subscribe to push: {:?}", err);
+                    set_error.set(Some(
+                        "Push notifications are blocked or not supported by this browser."
+                            .to_string(),
+                    ));
+                }
+            }
+        });
+    };
+
+    view! {
+        <div style="margin-top: 1rem; display: flex; align-items: center; gap: .75rem; flex-wrap: wrap;">
+            <button
+                type="button"
+                class="btn"
+                prop:disabled=move || state.vapid_public_key.get().is_none()
+                on:click=toggle
+            >
+                {move || {
+                    if subscribed.get() {
+                        "Stop push on this device"
+                    } else {
+                        "Allow push on this device"
+                    }
+                }}
+            </button>
+            <span style="font-size: .85em; opacity: .8;">
+                {move || {
+                    error
+                        .get()
+                        .unwrap_or_else(|| {
+                            "Push notifications only reach devices you allowed.".to_string()
+                        })
+                }}
+            </span>
+        </div>
+    }
+}
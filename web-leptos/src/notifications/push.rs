@@ -0,0 +1,63 @@
+use leptos::prelude::*;
+use peer_practice_shared::notification::PushSubscription;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::wasm_bindgen::{JsCast, JsValue};
+use web_sys::{PushSubscriptionOptionsInit, ServiceWorkerRegistration};
+
+/// Service worker that shows pushed notifications, copied to the site root.
+const SERVICE_WORKER: &str = "/sw.js";
+
+async fn registration() -> Result<ServiceWorkerRegistration, JsValue> {
+    let navigator = window().navigator();
+    // Missing outside secure contexts.
+    if !js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker"))? {
+        return Err(JsValue::from_str("Service workers are not supported"));
+    }
+    let container = navigator.service_worker();
+    JsFuture::from(container.register(SERVICE_WORKER)).await?;
+    Ok(JsFuture::from(container.ready()?).await?.unchecked_into())
+}
+
+/// The push subscription of this browser, if it has one.
+pub async fn current() -> Result<Option<web_sys::PushSubscription>, JsValue> {
+    let subscription =
+        JsFuture::from(registration().await?.push_manager()?.get_subscription()?).await?;
+    Ok(subscription.dyn_into().ok())
+}
+
+/// Asks for permission and subscribes this browser to pushes signed with
+/// `vapid_public_key`.
+pub async fn subscribe(vapid_public_key: &str) -> Result<PushSubscription, JsValue> {
+    let options = PushSubscriptionOptionsInit::new();
+    options.set_user_visible_only(true);
+    let key = js_sys::Uint8Array::from(decode_base64url(vapid_public_key)?.as_slice());
+    options.set_application_server_key(&key.into());
+    let subscription = JsFuture::from(
+        registration()
+            .await?
+            .push_manager()?
+            .subscribe_with_options(&options)?,
+    )
+    .await?;
+    let json = js_sys::JSON::stringify(&subscription)?
+        .as_string()
+        .unwrap_or_default();
+    serde_json::from_str(&json).map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+/// Unsubscribes this browser, returning the endpoint it used.
+pub async fn unsubscribe() -> Result<Option<String>, JsValue> {
+    let Some(subscription) = current().await? else {
+        return Ok(None);
+    };
+    JsFuture::from(subscription.unsubscribe()?).await?;
+    Ok(Some(subscription.endpoint()))
+}
+
+fn decode_base64url(value: &str) -> Result<Vec<u8>, JsValue> {
+    let mut base64 = value.replace('-', "+").replace('_', "/");
+    while !base64.len().is_multiple_of(4) {
+        base64.push('=');
+    }
+    Ok(window().atob(&base64)?.chars().map(|c| c as u8).collect())
+}
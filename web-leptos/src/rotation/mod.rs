@@ -0,0 +1,225 @@
+use chrono::{NaiveDate, NaiveTime};
+use leptos::prelude::*;
+use std::sync::Arc;
+
+use crate::app_state::AppStateReader;
+use crate::components::buttons::ServerButton;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::rotation::{RotationRound, RotationSettings};
+use peer_practice_shared::topic::topic_names;
+use peer_practice_shared::user::UserId;
+use peer_practice_shared::ymd;
+
+const INPUT_STYLE: &str = "--accent: var(--bg-strongest-color); padding: .6rem .75rem; border-radius: .6rem; border: 1px solid currentColor;";
+
+#[component]
+pub fn Rotation(state: AppStateReader) -> impl IntoView {
+    let date_options = ymd::create_date_options();
+    let defaults = RotationSettings::new(
+        state
+            .rotation
+            .get_untracked()
+            .map(|schedule| schedule.settings.date)
+            .or_else(|| {
+                date_options
+                    .first()
+                    .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            })
+            .unwrap_or_else(|| chrono::Local::now().date_naive()),
+    );
+    let defaults = state
+        .rotation
+        .get_untracked()
+        .map(|schedule| schedule.settings)
+        .unwrap_or(defaults);
+
+    let (date, set_date) = signal(defaults.date.format("%Y-%m-%d").to_string());
+    let (start, set_start) = signal(defaults.start.format("%H:%M").to_string());
+    let (round_minutes, set_round_minutes) = signal(defaults.round_minutes.to_string());
+    let (rounds, set_rounds) = signal(defaults.rounds.to_string());
+
+    let settings = move || {
+        Some(RotationSettings {
+            date: NaiveDate::parse_from_str(&date.get(), "%Y-%m-%d").ok()?,
+            start: NaiveTime::parse_from_str(&start.get(), "%H:%M").ok()?,
+            round_minutes: round_minutes
+                .get()
+                .trim()
+                .parse()
+                .ok()
+                .filter(|m| RotationSettings::ROUND_MINUTES.contains(m))?,
+            rounds: rounds
+                .get()
+                .trim()
+                .parse()
+                .ok()
+                .filter(|r| RotationSettings::ROUNDS.contains(r))?,
+        })
+    };
+
+    let name = move |id: UserId| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let round_view = move |round: RotationRound| {
+        let sitting_out = round
+            .sitting_out
+            .iter()
+            .map(|id| name(*id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        view! {
+            <div class="card rotation-round" style="margin-top: 1rem;">
+                <h3 class="card-title">{round.start.format("%H:%M").to_string()}</h3>
+                <table style="width: 100%; border-collapse: collapse;">
+                    <thead>
+                        <tr style="text-align: left;">
+                            <th>"Topic"</th>
+                            <th>"Lead"</th>
+                            <th>"Follow"</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {round
+                            .pairs
+                            .into_iter()
+                            .map(|pair| {
+                                view! {
+                                    <tr>
+                                        <td>
+                                            {topic_names(&pair.topics, &state.topics.get())}
+                                        </td>
+                                        <td>{name(pair.lead)}</td>
+                                        <td>{name(pair.follow)}</td>
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+                <Show when={
+                    let empty = sitting_out.is_empty();
+                    move || !empty
+                }>
+                    <p style="margin-top: .5rem; opacity: .8;">
+                        "Sitting out: " {sitting_out.clone()}
+                    </p>
+                </Show>
+            </div>
+        }
+    };
+
+    view! {
+        <section class="container container-narrow pad-sm">
+            <div class="card no-print">
+                <h2 class="card-title">"Rotation"</h2>
+                <div
+                    class="grid"
+                    style="display: grid; grid-template-columns: max-content 1fr; column-gap: .75rem; row-gap: .5rem; align-items: center; margin-top: 1rem;"
+                >
+                    <label for="rotation_date" class="label" style="justify-self: end;">
+                        "Evening"
+                    </label>
+                    <select
+                        id="rotation_date"
+                        class="combo"
+                        prop:value=date
+                        on:change=move |ev| set_date.set(event_target_value(&ev))
+                    >
+                        {date_options
+                            .into_iter()
+                            .map(|d| view! { <option value=d.clone()>{d.clone()}</option> })
+                            .collect_view()}
+                    </select>
+                    <label for="rotation_start" class="label" style="justify-self: end;">
+                        "Start"
+                    </label>
+                    <input
+                        id="rotation_start"
+                        type="time"
+                        data-theme="base"
+                        style=INPUT_STYLE
+                        prop:value=start
+                        on:input=move |ev| set_start.set(event_target_value(&ev))
+                    />
+                    <label for="rotation_minutes" class="label" style="justify-self: end;">
+                        "Minutes per round"
+                    </label>
+                    <input
+                        id="rotation_minutes"
+                        type="number"
+                        min=*RotationSettings::ROUND_MINUTES.start()
+                        max=*RotationSettings::ROUND_MINUTES.end()
+                        data-theme="base"
+                        style=INPUT_STYLE
+                        prop:value=round_minutes
+                        on:input=move |ev| set_round_minutes.set(event_target_value(&ev))
+                    />
+                    <label for="rotation_rounds" class="label" style="justify-self: end;">
+                        "Rounds"
+                    </label>
+                    <input
+                        id="rotation_rounds"
+                        type="number"
+                        min=*RotationSettings::ROUNDS.start()
+                        max=*RotationSettings::ROUNDS.end()
+                        data-theme="base"
+                        style=INPUT_STYLE
+                        prop:value=rounds
+                        on:input=move |ev| set_rounds.set(event_target_value(&ev))
+                    />
+                    <div
+                        class="actions actions-inline gap-sm align-center"
+                        style="grid-column: 1 / -1; margin-top: .25rem;"
+                    >
+                        <ServerButton
+                            class=Signal::derive(|| "btn".to_string())
+                            data_theme=Arc::new(|| "primary")
+                            on_click=Callback::new(move |_| {
+                                if let Some(settings) = settings() {
+                                    state.send(ClientToServer::GetRotationSchedule(settings));
+                                }
+                            })
+                        >
+                            "Generate"
+                        </ServerButton>
+                        <button
+                            class="btn"
+                            data-theme="secondary"
+                            prop:disabled=move || state.rotation.get().is_none()
+                            on:click=move |_| _ = window().print()
+                        >
+                            "Print"
+                        </button>
+                    </div>
+                </div>
+            </div>
+            {move || {
+                state
+                    .rotation
+                    .get()
+                    .map(|schedule| {
+                        let title = format!(
+                            "Rotation {}",
+                            schedule.settings.date.format("%Y-%m-%d"),
+                        );
+                        let empty = schedule.rounds.iter().all(|round| round.pairs.is_empty());
+                        view! {
+                            <h2 class="card-title" style="margin-top: 1rem;">{title}</h2>
+                            <Show when=move || empty>
+                                <p style="opacity: .8;">
+                                    "Nobody can be paired for this evening yet."
+                                </p>
+                            </Show>
+                            {schedule.rounds.into_iter().map(round_view).collect_view()}
+                        }
+                    })
+            }}
+        </section>
+    }
+}
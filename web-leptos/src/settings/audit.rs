@@ -0,0 +1,76 @@
+use leptos::prelude::*;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::history::AuditAction;
+use peer_practice_shared::messages::ClientToServer;
+
+/// Recent mutations of all posts, for organizers.
+#[component]
+pub fn AuditLogCard(state: AppStateReader) -> impl IntoView {
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let describe = move |action: AuditAction| match action {
+        AuditAction::Created => "created".to_string(),
+        AuditAction::Edited => "edited".to_string(),
+        AuditAction::Joined(role) => format!("joined as {role}"),
+        AuditAction::Waitlisted(role) => format!("waitlisted as {role}"),
+        AuditAction::Left => "left".to_string(),
+        AuditAction::Promoted(user) => format!("moved {} up from the waitlist", name(user)),
+        AuditAction::Deleted => "deleted".to_string(),
+        AuditAction::Expired => "expired".to_string(),
+        AuditAction::Restored => "restored".to_string(),
+    };
+
+    view! {
+        <div class="card" style="margin-top: 1rem;">
+            <div class="cluster" style="--cluster-justify: space-between;">
+                <h2 class="card-title">"Audit log"</h2>
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="secondary"
+                    on:click=move |_| state.send(ClientToServer::GetAuditLog)
+                >
+                    {move || if state.audit_log.get().is_some() { "Refresh" } else { "Load" }}
+                </button>
+            </div>
+            <div style="display: grid; grid-template-columns: max-content max-content 1fr max-content; gap: .25rem .75rem; margin-top: 1rem; font-size: .9em;">
+                {move || {
+                    let posts = state.posts.get();
+                    let topics = state.topics.get();
+                    state
+                        .audit_log
+                        .get()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|entry| {
+                            let post = posts
+                                .get(&entry.post)
+                                .map(|post| post.title(&topics))
+                                .unwrap_or_else(|| "Removed post".to_string());
+                            let actor = entry
+                                .actor
+                                .map(name)
+                                .unwrap_or_else(|| "Server".to_string());
+                            view! {
+                                <span style="opacity: .7;">
+                                    {convert_utc_to_local(entry.at).format("%Y-%m-%d %H:%M").to_string()}
+                                </span>
+                                <span>{actor}</span>
+                                <span>{post}</span>
+                                <span>{describe(entry.action)}</span>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </div>
+        </div>
+    }
+}
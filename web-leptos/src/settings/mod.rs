@@ -5,9 +5,15 @@ use crate::app_state::AppStateReader;
 use crate::components::buttons::ServerButton;
 use crate::components::modal::CenterModal;
 use peer_practice_shared::accent_colors::AccentColor;
+use peer_practice_shared::language::Language;
+use peer_practice_shared::level::Level;
 use peer_practice_shared::messages::ClientToServer;
 use peer_practice_shared::user::display_user::UserDisplay;
 
+mod audit;
+mod topics;
+mod undelivered;
+
 #[component]
 pub fn Settings(state: AppStateReader) -> impl IntoView {
     let initial_name = {
@@ -24,6 +30,13 @@ pub fn Settings(state: AppStateReader) -> impl IntoView {
     };
 
     let (name, set_name) = signal(initial_name);
+    let (level, set_level) = signal(state.user_id.get_untracked().and_then(|uid| {
+        state
+            .users
+            .get_untracked()
+            .get(&uid)
+            .and_then(|u| u.level.clone())
+    }));
     let (saving, set_saving) = signal(false);
 
     let (accent_color, set_accent_color) = signal(AccentColor::Teal);
@@ -36,6 +49,36 @@ pub fn Settings(state: AppStateReader) -> impl IntoView {
     };
     let (show_palette, set_show_palette) = signal(false);
 
+    if state.calendar_token.get_untracked().is_none() {
+        state.send(ClientToServer::GetCalendarToken);
+    }
+    if state.user_config.get_untracked().is_none() {
+        state.send(ClientToServer::GetUserConfig);
+    }
+    let calendar_invites = move || {
+        state
+            .user_config
+            .get()
+            .map(|config| config.calendar_invites)
+            .unwrap_or_default()
+    };
+    let practice_digest = move || {
+        state
+            .user_config
+            .get()
+            .map(|config| config.practice_digest)
+            .unwrap_or_default()
+    };
+    let origin = window().location().origin().unwrap_or_default();
+    let public_feed = format!("{origin}/v1/calendar.ics");
+    let personal_feed = move || {
+        state
+            .calendar_token
+            .get()
+            .map(|token| format!("{origin}/v1/calendar/{token}.ics"))
+            .unwrap_or_default()
+    };
+
     let on_submit = move |ev: web_sys::SubmitEvent| {
         ev.prevent_default();
         set_saving.set(true);
@@ -44,6 +87,7 @@ pub fn Settings(state: AppStateReader) -> impl IntoView {
         state.send(ClientToServer::UpdateUser(UserDisplay {
             id,
             display_name: Some(new_name.clone()),
+            level: level.get(),
         }));
 
         set_saving.set(false);
@@ -74,6 +118,33 @@ pub fn Settings(state: AppStateReader) -> impl IntoView {
                             on:input=move |ev| set_name.set(event_target_value(&ev))
                             placeholder="Your name as shown to others"
                         />
+                        <label for="own_level" class="label" style="justify-self: end;">
+                            "Your level"
+                        </label>
+                        <select
+                            id="own_level"
+                            class="combo"
+                            title="Used to find posts that suit you"
+                            prop:value=move || level.get().map(|l| l.to_string()).unwrap_or_default()
+                            on:change=move |ev| {
+                                let v = event_target_value(&ev);
+                                set_level.set((!v.is_empty()).then(|| Level::new(v)));
+                            }
+                        >
+                            <option value="">"Not set"</option>
+                            {move || {
+                                state
+                                    .levels
+                                    .get()
+                                    .all()
+                                    .iter()
+                                    .map(|l| {
+                                        let v = l.to_string();
+                                        view! { <option value=v.clone()>{v.clone()}</option> }
+                                    })
+                                    .collect_view()
+                            }}
+                        </select>
                         //
                         // <label class="label" style="justify-self: end;">
                         //     "Accent color"
@@ -110,6 +181,124 @@ pub fn Settings(state: AppStateReader) -> impl IntoView {
                     </div>
                 </form>
             </div>
+            <div class="card" style="margin-top: 1rem;">
+                <h2 class="card-title">"Calendar"</h2>
+                <div
+                    class="grid"
+                    style="display: grid; grid-template-columns: max-content 1fr; column-gap: .75rem; row-gap: .5rem; align-items: center; margin-top: 1rem;"
+                >
+                    <label for="personal_feed" class="label" style="justify-self: end;">
+                        "Joined posts"
+                    </label>
+                    <input
+                        id="personal_feed"
+                        type="text"
+                        readonly=true
+                        class="w-full"
+                        data-theme="base"
+                        style="--accent: var(--bg-strongest-color); padding: .6rem .75rem; border-radius: .6rem; border: 1px solid currentColor; min-width: 20rem;"
+                        prop:value=personal_feed
+                        title="Keep this link private, it lists the posts you joined"
+                    />
+                    <label for="public_feed" class="label" style="justify-self: end;">
+                        "All sessions"
+                    </label>
+                    <input
+                        id="public_feed"
+                        type="text"
+                        readonly=true
+                        class="w-full"
+                        data-theme="base"
+                        style="--accent: var(--bg-strongest-color); padding: .6rem .75rem; border-radius: .6rem; border: 1px solid currentColor; min-width: 20rem;"
+                        prop:value=public_feed
+                    />
+                    <label for="calendar_invites" class="label" style="justify-self: end;">
+                        "Email invites"
+                    </label>
+                    <label style="display: inline-flex; align-items: center; gap: .5rem;">
+                        <input
+                            id="calendar_invites"
+                            type="checkbox"
+                            prop:checked=calendar_invites
+                            prop:disabled=move || state.user_config.get().is_none()
+                            on:change=move |ev| {
+                                let mut config = state.user_config.get_untracked().unwrap_or_default();
+                                config.calendar_invites = event_target_checked(&ev);
+                                state.send(ClientToServer::UpdateUserConfig(config));
+                            }
+                        />
+                        "Send me a calendar invite when I join a post"
+                    </label>
+                    <label for="practice_digest" class="label" style="justify-self: end;">
+                        "Digest"
+                    </label>
+                    <label style="display: inline-flex; align-items: center; gap: .5rem;">
+                        <input
+                            id="practice_digest"
+                            type="checkbox"
+                            prop:checked=practice_digest
+                            prop:disabled=move || state.user_config.get().is_none()
+                            on:change=move |ev| {
+                                let mut config = state.user_config.get_untracked().unwrap_or_default();
+                                config.practice_digest = event_target_checked(&ev);
+                                state.send(ClientToServer::UpdateUserConfig(config));
+                            }
+                        />
+                        "Email me the posts of each practice evening two days ahead"
+                    </label>
+                    <label for="email_language" class="label" style="justify-self: end;">
+                        "Email language"
+                    </label>
+                    <select
+                        id="email_language"
+                        class="combo"
+                        style="width: auto;"
+                        prop:value=move || {
+                            state
+                                .user_config
+                                .get()
+                                .and_then(|config| config.language)
+                                .map(|language| language.tag())
+                                .unwrap_or_default()
+                        }
+                        prop:disabled=move || state.user_config.get().is_none()
+                        on:change=move |ev| {
+                            let mut config = state.user_config.get_untracked().unwrap_or_default();
+                            config.language = Language::from_tag(&event_target_value(&ev));
+                            state.send(ClientToServer::UpdateUserConfig(config));
+                        }
+                    >
+                        <option value="">"Same as my browser"</option>
+                        {Language::ALL
+                            .iter()
+                            .map(|language| {
+                                view! { <option value=language.tag()>{language.name()}</option> }
+                            })
+                            .collect_view()}
+                    </select>
+                    <div
+                        class="actions actions-inline gap-sm align-center"
+                        style="grid-column: 1 / -1; margin-top: .25rem;"
+                    >
+                        <ServerButton
+                            class=Signal::derive(|| "btn".to_string())
+                            data_theme=Arc::new(|| "secondary")
+                            title="Invalidate the current link and create a new one".to_string()
+                            on_click=Callback::new(move |_| {
+                                state.send(ClientToServer::RenewCalendarToken);
+                            })
+                        >
+                            "Renew link"
+                        </ServerButton>
+                    </div>
+                </div>
+            </div>
+            <crate::notifications::NotificationSettings state />
+            <Show when=move || state.organizer.get()>
+                <topics::TopicsCard state />
+                <audit::AuditLogCard state />
+                <undelivered::UndeliveredEmailsCard state />
+            </Show>
         </section>
 
         <CenterModal
@@ -0,0 +1,135 @@
+use leptos::prelude::*;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::app_state::AppStateReader;
+use crate::components::buttons::ServerButton;
+use peer_practice_shared::accent_colors::AccentColor;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::topic::{Topic, TopicId};
+
+const INPUT_STYLE: &str = "--accent: var(--bg-strongest-color); padding: .5rem .75rem; border-radius: .6rem; border: 1px solid currentColor; min-width: 0;";
+
+/// Lets organizers create, rename, recolor and archive topics.
+#[component]
+pub fn TopicsCard(state: AppStateReader) -> impl IntoView {
+    let (new_name, set_new_name) = signal(String::new());
+    let (new_color, set_new_color) = signal(AccentColor::Teal);
+
+    let sorted_topics = move || {
+        let mut topics = state.topics.get().into_iter().collect::<Vec<_>>();
+        topics.sort_by(|a, b| {
+            a.1.archived
+                .cmp(&b.1.archived)
+                .then_with(|| a.1.name.cmp(&b.1.name))
+        });
+        topics
+    };
+
+    view! {
+        <div class="card" style="margin-top: 1rem;">
+            <h2 class="card-title">"Topics"</h2>
+            <div style="display: grid; grid-template-columns: 1fr max-content max-content; gap: .5rem; align-items: center; margin-top: 1rem;">
+                {move || {
+                    sorted_topics()
+                        .into_iter()
+                        .map(|(id, topic)| topic_row(state, id, topic))
+                        .collect_view()
+                }}
+                <input
+                    type="text"
+                    data-theme="base"
+                    style=INPUT_STYLE
+                    placeholder="New topic"
+                    prop:value=new_name
+                    on:input=move |ev| set_new_name.set(event_target_value(&ev))
+                />
+                {color_select(new_color.get_untracked(), move |color| set_new_color.set(color))}
+                <ServerButton
+                    class=Signal::derive(|| "btn".to_string())
+                    data_theme=Arc::new(|| "secondary")
+                    disabled=Signal::derive(move || new_name.get().trim().is_empty())
+                    on_click=Callback::new(move |_| {
+                        state
+                            .send(
+                                ClientToServer::NewTopic(Topic {
+                                    name: new_name.get_untracked(),
+                                    color: new_color.get_untracked(),
+                                    archived: false,
+                                }),
+                            );
+                        set_new_name.set(String::new());
+                    })
+                >
+                    "Add"
+                </ServerButton>
+            </div>
+        </div>
+    }
+}
+
+fn topic_row(state: AppStateReader, id: TopicId, topic: Topic) -> impl IntoView {
+    let update = {
+        let topic = topic.clone();
+        move |change: &dyn Fn(&mut Topic)| {
+            let mut updated = topic.clone();
+            change(&mut updated);
+            if updated != topic && !updated.name.trim().is_empty() {
+                state.send(ClientToServer::UpdateTopic(id, updated));
+            }
+        }
+    };
+    let rename = update.clone();
+    let recolor = update.clone();
+    let archived = topic.archived;
+
+    view! {
+        <input
+            type="text"
+            data-theme="base"
+            style=format!("{INPUT_STYLE} opacity: {};", if archived { ".5" } else { "1" })
+            prop:value=topic.name.clone()
+            on:change=move |ev| {
+                let name = event_target_value(&ev);
+                rename(&|topic| topic.name = name.trim().to_string());
+            }
+        />
+        {color_select(topic.color, move |color| recolor(&|topic| topic.color = color))}
+        <button
+            type="button"
+            class="btn"
+            data-theme="secondary"
+            title=if archived {
+                "Allow picking this topic for new posts again"
+            } else {
+                "Keep the topic on existing posts but stop offering it"
+            }
+            on:click=move |_| update(&|topic| topic.archived = !archived)
+        >
+            {if archived { "Restore" } else { "Archive" }}
+        </button>
+    }
+}
+
+fn color_select(selected: AccentColor, on_change: impl Fn(AccentColor) + 'static) -> impl IntoView {
+    view! {
+        <select
+            class="combo"
+            style=format!("width: auto; --accent: {};", selected.css_var())
+            prop:value=selected.to_string()
+            on:change=move |ev| {
+                if let Ok(color) = AccentColor::from_str(&event_target_value(&ev)) {
+                    on_change(color);
+                }
+            }
+        >
+            {AccentColor::base()
+                .iter()
+                .map(|color| {
+                    let value = color.to_string();
+                    view! { <option value=value.clone()>{value.clone()}</option> }
+                })
+                .collect_view()}
+        </select>
+    }
+}
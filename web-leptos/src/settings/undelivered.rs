@@ -0,0 +1,84 @@
+use leptos::prelude::*;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_utc_to_local;
+use peer_practice_shared::email::DeliveryStatus;
+use peer_practice_shared::messages::ClientToServer;
+
+/// Emails the server is still retrying and the ones it gave up on, for
+/// organizers.
+#[component]
+pub fn UndeliveredEmailsCard(state: AppStateReader) -> impl IntoView {
+    let format = |at| {
+        convert_utc_to_local(at)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    };
+
+    view! {
+        <div class="card" style="margin-top: 1rem;">
+            <div class="cluster" style="--cluster-justify: space-between;">
+                <h2 class="card-title">"Undelivered emails"</h2>
+                <button
+                    type="button"
+                    class="btn"
+                    data-theme="secondary"
+                    on:click=move |_| state.send(ClientToServer::GetUndeliveredEmails)
+                >
+                    {move || {
+                        if state.undelivered_emails.get().is_some() { "Refresh" } else { "Load" }
+                    }}
+                </button>
+            </div>
+            <Show when=move || {
+                state.undelivered_emails.get().is_some_and(|emails| emails.is_empty())
+            }>
+                <p style="margin-top: 1rem; opacity: .7;">"All emails were delivered."</p>
+            </Show>
+            <div style="display: grid; grid-template-columns: max-content 1fr 1fr max-content; gap: .25rem .75rem; margin-top: 1rem; font-size: .9em;">
+                {move || {
+                    state
+                        .undelivered_emails
+                        .get()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|email| {
+                            let id = email.id;
+                            let status = match email.status {
+                                DeliveryStatus::Retrying { next_attempt } => {
+                                    format!("Retrying at {}", format(next_attempt))
+                                }
+                                DeliveryStatus::Failed { at } => format!("Failed {}", format(at)),
+                            };
+                            let failed = matches!(email.status, DeliveryStatus::Failed { .. });
+                            view! {
+                                <span style="opacity: .7;">{format(email.created)}</span>
+                                <span>{email.to.join(", ")}</span>
+                                <span title=email.last_error.clone()>
+                                    {email.subject} <br />
+                                    <small style="color: var(--danger-color);">
+                                        {format!("{} attempts: {}", email.attempts, email.last_error)}
+                                    </small>
+                                </span>
+                                <span>
+                                    {status}
+                                    <Show when=move || failed>
+                                        " "
+                                        <button
+                                            type="button"
+                                            class="btn"
+                                            data-theme="secondary"
+                                            on:click=move |_| state.send(ClientToServer::RetryEmail(id))
+                                        >
+                                            "Retry"
+                                        </button>
+                                    </Show>
+                                </span>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,157 @@
+use leptos::prelude::*;
+
+use crate::app_state::AppStateReader;
+use peer_practice_shared::convert_utc_to_local_date;
+use peer_practice_shared::messages::ClientToServer;
+use peer_practice_shared::topic::topic_names;
+
+/// Past sessions of the signed-in user and what they add up to.
+#[component]
+pub fn Stats(state: AppStateReader) -> impl IntoView {
+    state.send(ClientToServer::GetPracticeStats);
+
+    let name = move |id| {
+        state
+            .users
+            .get()
+            .get(&id)
+            .and_then(|user| user.display_name.clone())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let weeks = |n: u32| match n {
+        1 => "1 week".to_string(),
+        n => format!("{n} weeks"),
+    };
+
+    view! {
+        {move || {
+            let Some(stats) = state.practice_stats.get() else {
+                return view! { <p style="margin: 1rem;">"Loading..."</p> }.into_any();
+            };
+            let topics = state.topics.get();
+            let mut months = stats
+                .topics_by_month
+                .iter()
+                .map(|entry| entry.month)
+                .collect::<Vec<_>>();
+            months.dedup();
+            months.reverse();
+            view! {
+                <div class="card" style="margin: 1rem;">
+                    <h2 class="card-title">"Statistics"</h2>
+                    <div class="cluster" style="--cluster-justify: flex-start; --cluster-gap: 2rem;">
+                        <div>
+                            <strong style="font-size: 1.5em;">{stats.sessions.len()}</strong>
+                            " sessions attended"
+                        </div>
+                        <div>
+                            <strong style="font-size: 1.5em;">{stats.hosted}</strong>
+                            " hosted"
+                        </div>
+                        <div>
+                            <strong style="font-size: 1.5em;">
+                                {weeks(stats.current_streak_weeks)}
+                            </strong>
+                            " current streak"
+                        </div>
+                        <div>
+                            <strong style="font-size: 1.5em;">
+                                {weeks(stats.longest_streak_weeks)}
+                            </strong>
+                            " longest streak"
+                        </div>
+                    </div>
+                </div>
+                <div class="card" style="margin: 1rem;">
+                    <h2 class="card-title">"Topics over time"</h2>
+                    {if months.is_empty() {
+                        view! { <p>"No topics practiced yet."</p> }.into_any()
+                    } else {
+                        months
+                            .into_iter()
+                            .map(|month| {
+                                let practiced = stats
+                                    .topics_by_month
+                                    .iter()
+                                    .filter(|entry| entry.month == month)
+                                    .map(|entry| {
+                                        let topic = topic_names(&[entry.topic], &topics);
+                                        view! {
+                                            <span class="surface" style="padding: .15rem .5rem; border-radius: .5rem;">
+                                                {format!("{topic} × {}", entry.sessions)}
+                                            </span>
+                                        }
+                                    })
+                                    .collect_view();
+                                view! {
+                                    <div
+                                        class="cluster"
+                                        style="--cluster-justify: flex-start; --cluster-gap: .5rem; margin-top: .5rem;"
+                                    >
+                                        <strong style="min-width: 6rem;">
+                                            {month.format("%B %Y").to_string()}
+                                        </strong>
+                                        {practiced}
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                            .into_any()
+                    }}
+                </div>
+                <div class="card" style="margin: 1rem;">
+                    <h2 class="card-title">"Frequent partners"</h2>
+                    {if stats.partners.is_empty() {
+                        view! { <p>"Nobody yet."</p> }.into_any()
+                    } else {
+                        view! {
+                            <div style="display: grid; grid-template-columns: 1fr max-content; gap: .25rem .75rem;">
+                                {stats
+                                    .partners
+                                    .iter()
+                                    .map(|partner| {
+                                        let id = partner.partner;
+                                        view! {
+                                            <span>{move || name(id)}</span>
+                                            <span>{partner.sessions}</span>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        }
+                            .into_any()
+                    }}
+                </div>
+                <div class="card" style="margin: 1rem;">
+                    <h2 class="card-title">"History"</h2>
+                    {if stats.sessions.is_empty() {
+                        view! { <p>"No past sessions yet."</p> }.into_any()
+                    } else {
+                        view! {
+                            <div style="display: grid; grid-template-columns: max-content 1fr max-content; gap: .25rem .75rem;">
+                                {stats
+                                    .sessions
+                                    .iter()
+                                    .map(|session| {
+                                        let hosted = if session.hosted { "hosted, " } else { "" };
+                                        view! {
+                                            <span>
+                                                {convert_utc_to_local_date(session.date).to_string()}
+                                            </span>
+                                            <span>{topic_names(&session.topics, &topics)}</span>
+                                            <span>
+                                                {format!("{hosted}{} dancers", session.participants)}
+                                            </span>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </div>
+                        }
+                            .into_any()
+                    }}
+                </div>
+            }
+                .into_any()
+        }}
+    }
+}
@@ -1,15 +1,19 @@
-use crate::app_state::{AppStateReader, AppStateWriter};
+use crate::app_state::{AppStateReader, AppStateWriter, Notice};
 use crate::host;
 use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::StreamExt;
 use leptos::logging::log;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use peer_practice_shared::Version;
+use peer_practice_shared::handshake::{self, Welcome};
 use peer_practice_shared::messages::{ClientToServer, ServerToClient};
+use peer_practice_shared::notification::NotificationKind;
+use peer_practice_shared::request::{ClientRequest, RequestError};
 use std::cell::Cell;
 use std::rc::Rc;
 use web_sys::wasm_bindgen::prelude::*;
-use web_sys::{MessageEvent, WebSocket};
+use web_sys::{CloseEvent, MessageEvent, WebSocket};
 
 pub fn attempt_connect(
     write_state: AppStateWriter,
@@ -32,7 +36,7 @@ fn connect(
     first_ws_attempt_completed: WriteSignal<bool>,
     count: u8,
 ) {
-    if state.connected_to_server_untracked() {
+    if state.connected_to_server_untracked() || state.reload_required.get_untracked() {
         return;
     }
 
@@ -58,7 +62,11 @@ fn connect(
         .protocol()
         .unwrap_or_else(|_| "http:".into());
     let ws_scheme = if protocol == "https:" { "wss" } else { "ws" };
-    let url = format!("{ws_scheme}://{}/v1/ws", host());
+    let url = format!(
+        "{ws_scheme}://{}/v1/ws?{}",
+        host(),
+        handshake::query(&[Version::CURRENT])
+    );
 
     let ws = match WebSocket::new(&url) {
         Ok(ws) => ws,
@@ -71,22 +79,29 @@ fn connect(
     };
 
     let (tx, mut rx): (
-        UnboundedSender<ClientToServer>,
-        UnboundedReceiver<ClientToServer>,
+        UnboundedSender<ClientRequest>,
+        UnboundedReceiver<ClientRequest>,
     ) = unbounded();
 
     let connected = Rc::new(Cell::new(false));
     let connected_onopen = connected.clone();
     let onopen = Closure::<dyn FnMut()>::wrap(Box::new(move || {
         connected_onopen.set(true);
-        let mut tx_get = tx.clone();
         write_state.set_tx(Some(tx.clone()));
         if count == 0 {
             first_ws_attempt_completed.set(true);
         }
-        spawn_local(async move {
-            _ = tx_get.send(ClientToServer::GetPosts).await;
-        });
+        let cursor = state.posts_cursor.get_untracked();
+        for msg in [
+            ClientToServer::GetTopics,
+            ClientToServer::SyncPosts(cursor),
+            ClientToServer::GetCommentCounts,
+            ClientToServer::GetDirectMessages,
+            ClientToServer::GetNotifications,
+            ClientToServer::GetVapidPublicKey,
+        ] {
+            state.send(msg);
+        }
     }));
     ws.set_onopen(Some(onopen.as_ref().unchecked_ref()));
     onopen.forget();
@@ -95,18 +110,30 @@ fn connect(
         if let Some(txt) = e.data().as_string() {
             match serde_json::from_str::<ServerToClient>(&txt) {
                 Ok(msg) => handle_websocket_messages(write_state, state, msg),
-                Err(err) => log!("Failed to deserialize ServerToClient: {}", err),
+                Err(err) => match serde_json::from_str::<Welcome>(&txt) {
+                    Ok(welcome) => log!("Speaking protocol version {}", welcome.version),
+                    Err(_) => log!("Failed to deserialize ServerToClient: {}", err),
+                },
             }
         }
     }));
     ws.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
     onmessage.forget();
 
-    let onclose = Closure::<dyn FnMut()>::wrap(Box::new(move || {
+    let onclose = Closure::<dyn FnMut(CloseEvent)>::wrap(Box::new(move |e: CloseEvent| {
         log!("WebSocket closed start");
         write_state.set_tx(None);
+        state.fail_pending(write_state.notice);
         first_ws_attempt_completed.set(true);
         log!("WebSocket closed raise event");
+        if e.code() == handshake::RELOAD_REQUIRED {
+            log!(
+                "Server no longer speaks protocol version {}",
+                Version::CURRENT
+            );
+            write_state.reload_required.set(true);
+            return;
+        }
 
         let next_count = count + 1;
         connect(write_state, state, first_ws_attempt_completed, next_count);
@@ -141,6 +168,11 @@ fn handle_websocket_messages(
             });
         }
         ServerToClient::Post(id, post) => {
+            state_writer.archive.update(|archive| {
+                if let Some(archive) = archive {
+                    archive.retain(|(archived, _)| *archived != id);
+                }
+            });
             state_writer.users.update(|s| {
                 if !s.contains_key(&post.owner) {
                     state.send(ClientToServer::GetUser(post.owner));
@@ -152,6 +184,138 @@ fn handle_websocket_messages(
             state_writer.user_id.set(Some(id));
             state.send(ClientToServer::GetUser(id));
         }
-        ServerToClient::RemovedPost(id) => _ = state_writer.posts.write().remove(&id),
+        ServerToClient::RemovedPost(id) => {
+            state_writer.posts.write().remove(&id);
+            state_writer.reactions.write().remove(&id);
+            state_writer.my_reactions.write().remove(&id);
+        }
+        ServerToClient::CalendarToken(token) => state_writer.calendar_token.set(Some(token)),
+        ServerToClient::UserConfig(config) => state_writer.user_config.set(Some(config)),
+        ServerToClient::RotationSchedule(schedule) => state_writer.rotation.set(Some(schedule)),
+        ServerToClient::Topic(id, topic) => _ = state_writer.topics.write().insert(id, topic),
+        ServerToClient::Organizer(organizer) => state_writer.organizer.set(organizer),
+        ServerToClient::Levels(levels) => state_writer.levels.set(levels),
+        ServerToClient::Comment(id, comment) => {
+            if !state.users.get_untracked().contains_key(&comment.author) {
+                state.send(ClientToServer::GetUser(comment.author));
+            }
+            state_writer.comments.write().insert(id, comment);
+        }
+        ServerToClient::RemovedComment(id) => _ = state_writer.comments.write().remove(&id),
+        ServerToClient::CommentCounts(counts) => state_writer.comment_counts.write().extend(counts),
+        ServerToClient::Reactions(id, counts) => {
+            _ = state_writer.reactions.write().insert(id, counts)
+        }
+        ServerToClient::MyReactions(id, mine) => {
+            _ = state_writer.my_reactions.write().insert(id, mine)
+        }
+        ServerToClient::PostsSync(sync) => {
+            let users = state.users.get_untracked();
+            for (_, post) in &sync.posts {
+                if !users.contains_key(&post.owner) {
+                    state.send(ClientToServer::GetUser(post.owner));
+                }
+            }
+            state_writer.posts.update(|posts| {
+                if sync.full_resync {
+                    posts.clear();
+                }
+                for id in &sync.removed {
+                    posts.remove(id);
+                }
+                posts.extend(sync.posts);
+            });
+            if sync.full_resync {
+                state_writer.reactions.write().clear();
+                state_writer.my_reactions.write().clear();
+            }
+            for id in &sync.removed {
+                state_writer.reactions.write().remove(id);
+                state_writer.my_reactions.write().remove(id);
+            }
+            if let Some(cursor) = sync.cursor {
+                state_writer.posts_cursor.set(Some(cursor));
+            }
+        }
+        ServerToClient::PostHistory(id, versions) => {
+            let users = state.users.get_untracked();
+            for version in &versions {
+                if !users.contains_key(&version.by) {
+                    state.send(ClientToServer::GetUser(version.by));
+                }
+            }
+            state_writer.post_history.write().insert(id, versions);
+        }
+        ServerToClient::AuditLog(entries) => {
+            let users = state.users.get_untracked();
+            let mut unknown = entries
+                .iter()
+                .filter_map(|entry| entry.actor)
+                .filter(|actor| !users.contains_key(actor))
+                .collect::<Vec<_>>();
+            unknown.sort();
+            unknown.dedup();
+            for user in unknown {
+                state.send(ClientToServer::GetUser(user));
+            }
+            state_writer.audit_log.set(Some(entries));
+        }
+        ServerToClient::Ack(request) => state.reply(request, Ok(())),
+        ServerToClient::Error {
+            request,
+            code,
+            message,
+        } => {
+            log!("Request {} failed: {:?} {}", request, code, message);
+            state_writer
+                .notice
+                .set(Some(Notice::failure(message.clone())));
+            state.reply(request, Err(RequestError { code, message }));
+        }
+        ServerToClient::UndeliveredEmails(emails) => {
+            state_writer.undelivered_emails.set(Some(emails))
+        }
+        ServerToClient::SearchResults(query, results) => {
+            state_writer.search_results.set(Some((query, results)))
+        }
+        ServerToClient::DirectMessage(id, message) => {
+            let users = state.users.get_untracked();
+            for user in [message.from, message.to] {
+                if !users.contains_key(&user) {
+                    state.send(ClientToServer::GetUser(user));
+                }
+            }
+            state_writer.direct_messages.write().insert(id, message);
+        }
+        ServerToClient::Archive(mut archive) => {
+            let users = state.users.get_untracked();
+            for (_, archived) in &archive {
+                if !users.contains_key(&archived.post.owner) {
+                    state.send(ClientToServer::GetUser(archived.post.owner));
+                }
+            }
+            archive.sort_by_key(|(_, archived)| std::cmp::Reverse(archived.removed_at));
+            state_writer.archive.set(Some(archive));
+        }
+        ServerToClient::PracticeStats(stats) => {
+            let users = state.users.get_untracked();
+            for partner in &stats.partners {
+                if !users.contains_key(&partner.partner) {
+                    state.send(ClientToServer::GetUser(partner.partner));
+                }
+            }
+            state_writer.practice_stats.set(Some(stats));
+        }
+        ServerToClient::Notification(id, notification) => {
+            if let NotificationKind::Joined(user) = notification.kind
+                && !state.users.get_untracked().contains_key(&user)
+            {
+                state.send(ClientToServer::GetUser(user));
+            }
+            state_writer.notifications.write().insert(id, notification);
+        }
+        ServerToClient::VapidPublicKey(key) => {
+            state_writer.vapid_public_key.set(Some(key));
+        }
     }
 }